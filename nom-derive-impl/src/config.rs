@@ -1,7 +1,8 @@
 use crate::endian::ParserEndianness;
 use crate::meta::attr::{MetaAttr, MetaAttrType};
+use crate::parsertree::BitsField;
 use proc_macro2::{Span, TokenStream};
-use syn::{spanned::Spanned, Error};
+use syn::{spanned::Spanned, Error, Ident, Type, WherePredicate};
 
 #[derive(Debug)]
 pub struct Config {
@@ -14,12 +15,32 @@ pub struct Config {
     pub debug: bool,
     pub debug_derive: bool,
     pub generic_errors: bool,
+    /// Use a generic type parameter for input, instead of `&[u8]`
+    pub generic_input: bool,
+    /// Use `&str` for input, instead of `&[u8]`
+    pub str_input: bool,
+    /// Additional requirements on the generic input type (for ex. `Compare` for tags)
+    pub input_bounds: Vec<WherePredicate>,
+    /// Generic type parameters used as collections (with the `Elem` attribute), and the type of
+    /// their items
+    pub collection_params: Vec<(Ident, Type)>,
+    /// Also generate an implementation of the `Serialize` trait
+    pub serialize: bool,
+    /// Parse all fields as bit fields
+    pub bits: bool,
+    /// Bit order for bit fields (least significant bit first)
+    pub bits_lsb0: bool,
+    /// Position of the bit field being parsed, if any
+    pub bits_field: Option<BitsField>,
+    /// Name of the object (or variant) used in error contexts, if enabled
+    pub context: Option<String>,
     selector_type: Option<TokenStream>,
     selector_name: Option<String>,
     input_name: String,
     orig_input_name: String,
     lifetime_name: String,
     error_name: String,
+    input_type_name: String,
 }
 
 impl Config {
@@ -30,7 +51,14 @@ impl Config {
         let mut debug = false;
         let mut debug_derive = false;
         let mut generic_errors = false;
+        let mut generic_input = false;
+        let mut str_input = false;
+        let mut serialize = false;
+        let mut bits = false;
+        let mut bits_lsb0 = false;
+        let mut context = None;
         let mut span_endian = None;
+        let mut span_str_input = None;
         for meta in l {
             match meta.attr_type {
                 MetaAttrType::BigEndian => {
@@ -42,6 +70,23 @@ impl Config {
                 MetaAttrType::Debug => debug = true,
                 MetaAttrType::DebugDerive => debug_derive = true,
                 MetaAttrType::GenericErrors => generic_errors = true,
+                MetaAttrType::GenericInput => generic_input = true,
+                MetaAttrType::StrInput => {
+                    str_input = true;
+                    span_str_input = Some(meta.span());
+                }
+                MetaAttrType::Serialize => serialize = true,
+                MetaAttrType::Context => context = Some(String::new()),
+                MetaAttrType::Bits => {
+                    if meta.arg().is_some() {
+                        return Err(Error::new(
+                            meta.span(),
+                            "Nom-derive: the Bits top-level attribute does not take a width",
+                        ));
+                    }
+                    bits = true
+                }
+                MetaAttrType::BitOrder => bits_lsb0 = get_bit_order_lsb0(meta)?,
                 _ => (),
             }
         }
@@ -51,6 +96,12 @@ impl Config {
                 "Struct cannot be both big and little endian",
             ));
         }
+        if generic_input && str_input {
+            return Err(Error::new(
+                span_str_input.unwrap_or_else(Span::call_site),
+                "Nom-derive: StrInput cannot be used with GenericInput",
+            ));
+        }
         let object_endianness = if req_big_endian {
             ParserEndianness::BigEndian
         } else if req_little_endian {
@@ -87,15 +138,32 @@ impl Config {
             debug,
             debug_derive,
             generic_errors,
+            generic_input,
+            str_input,
+            input_bounds: Vec::new(),
+            collection_params: Vec::new(),
+            serialize,
+            bits,
+            bits_lsb0,
+            bits_field: None,
+            context,
             selector_type,
             selector_name,
             orig_input_name: "orig_".to_string() + &input_name,
             lifetime_name: String::from("'nom"),
             error_name: String::from("NomErr"),
+            input_type_name: String::from("NomInput"),
             input_name,
         })
     }
 
+    /// Set the name used as prefix for error contexts (if enabled)
+    pub fn set_context_name(&mut self, name: String) {
+        if self.context.is_some() {
+            self.context = Some(name);
+        }
+    }
+
     #[inline]
     pub fn selector_type(&self) -> Option<&TokenStream> {
         self.selector_type.as_ref()
@@ -125,4 +193,50 @@ impl Config {
     pub fn error_name(&self) -> &str {
         &self.error_name
     }
+
+    #[inline]
+    pub fn input_type_name(&self) -> &str {
+        &self.input_type_name
+    }
+
+    /// Type of the input of parsers: the generic type parameter, `&'nom str` or `&'nom [u8]`
+    pub fn input_type(&self) -> TokenStream {
+        let lft = syn::Lifetime::new(&self.lifetime_name, Span::call_site());
+        if self.generic_input {
+            let ident = syn::Ident::new(&self.input_type_name, Span::call_site());
+            quote! { #ident }
+        } else if self.str_input {
+            quote! { &#lft str }
+        } else {
+            quote! { &#lft [u8] }
+        }
+    }
+
+    /// Copy of the input variable `ident` (a generic input is only `Clone`)
+    pub fn input_copy(&self, ident: &syn::Ident) -> TokenStream {
+        if self.generic_input {
+            quote! { #ident.clone() }
+        } else {
+            quote! { #ident }
+        }
+    }
+
+    /// Add a requirement on the generic input type, if not already present
+    pub fn add_input_bound(&mut self, wh: WherePredicate) {
+        if self.generic_input && !self.input_bounds.contains(&wh) {
+            self.input_bounds.push(wh);
+        }
+    }
+}
+
+/// Parse the argument of the `BitOrder` attribute, and return true if least significant bit is first
+pub(crate) fn get_bit_order_lsb0(meta: &MetaAttr) -> Result<bool, Error> {
+    match meta.arg().map(|ts| ts.to_string()).as_deref() {
+        Some("msb") => Ok(false),
+        Some("lsb") => Ok(true),
+        _ => Err(Error::new(
+            meta.span(),
+            "Nom-derive: BitOrder must be \"msb\" or \"lsb\"",
+        )),
+    }
 }
@@ -20,13 +20,26 @@ pub(crate) fn parse_variant(
     // eprintln!("variant: {:?}", variant);
     let meta_list =
         meta::parse_nom_attribute(&variant.attrs).expect("Parsing the 'nom' meta attribute failed");
-    let selector = get_selector(&meta_list).ok_or_else(|| {
-        Error::new(
+    let unknown = meta_list.iter().any(|m| m.is_type(MetaAttrType::Unknown));
+    let selector = match get_selector(&meta_list) {
+        Some(s) => s,
+        // catch-all variant: matches any value
+        None if unknown => "_".to_string(),
+        None => return Err(Error::new(
             variant.span(),
             "Nom-derive: the 'Selector' attribute must be used to give the value of selector item",
-        )
-    })?;
-    let mut struct_def = parse_fields(&variant.fields, config)?;
+        )),
+    };
+    let context = config.context.clone();
+    if let Some(name) = &context {
+        config.set_context_name(format!("{}::{}", name, variant.ident));
+    }
+    let mut struct_def = if unknown {
+        parse_unknown_fields(variant, &selector, config)?
+    } else {
+        parse_fields(&variant.fields, config)?
+    };
+    config.context = context;
     if variant.fields == syn::Fields::Unit {
         let mut p = None;
         for meta in &meta_list {
@@ -49,6 +62,46 @@ pub(crate) fn parse_variant(
     })
 }
 
+/// Build the parsers for the fields of the catch-all variant: the first field captures the
+/// selector, and the second one (optional) the remaining bytes
+fn parse_unknown_fields(
+    variant: &syn::Variant,
+    selector: &str,
+    config: &Config,
+) -> Result<StructParserTree> {
+    let n = variant.fields.len();
+    if selector != "_" || n == 0 || n > 2 {
+        return Err(Error::new(
+            variant.span(),
+            "Nom-derive: the 'Unknown' variant must use the '_' selector, and have one or two fields",
+        ));
+    }
+    let selector_name = Ident::new(config.selector_name().unwrap(), variant.span());
+    let parsers = variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => format!("_{}", idx),
+            };
+            let expr = if idx == 0 {
+                ParserExpr::Value(quote! { #selector_name })
+            } else {
+                ParserExpr::Rest
+            };
+            let item = ParserTreeItem::new(field.ident.clone(), expr);
+            StructParser::new(name, item, None, None)
+        })
+        .collect();
+    Ok(StructParserTree {
+        empty: false,
+        unnamed: matches!(variant.fields, Fields::Unnamed(_)),
+        parsers,
+    })
+}
+
 fn get_selector(meta_list: &[MetaAttr]) -> Option<String> {
     for meta in meta_list {
         if MetaAttrType::Selector == meta.attr_type {
@@ -58,6 +111,24 @@ fn get_selector(meta_list: &[MetaAttr]) -> Option<String> {
     None
 }
 
+/// Get the catch-all variant of an enum (with the `Unknown` attribute), if present
+pub(crate) fn get_unknown_variant(data_enum: &DataEnum) -> Result<Option<&Variant>> {
+    let mut unknown = None;
+    for v in data_enum.variants.iter() {
+        let meta_list = meta::parse_nom_attribute(&v.attrs)?;
+        if meta_list.iter().any(|m| m.is_type(MetaAttrType::Unknown)) {
+            if unknown.is_some() {
+                return Err(Error::new(
+                    v.span(),
+                    "Nom-derive: only one variant can have the 'Unknown' attribute",
+                ));
+            }
+            unknown = Some(v);
+        }
+    }
+    Ok(unknown)
+}
+
 pub(crate) fn get_repr(attrs: &[syn::Attribute]) -> Option<Ident> {
     attrs.iter().find_map(|attr| {
         //
@@ -81,8 +152,14 @@ pub(crate) fn is_input_fieldless_enum(ast: &syn::DeriveInput) -> bool {
     match ast.data {
         syn::Data::Enum(ref data_enum) => {
             // eprintln!("{:?}", data_enum);
+            // the catch-all variant stores the value
+            let unknown = match get_unknown_variant(data_enum) {
+                Ok(Some(v)) => matches!(&v.fields, Fields::Unnamed(f) if f.unnamed.len() == 1)
+                    .then_some(&v.ident),
+                _ => None,
+            };
             for v in data_enum.variants.iter() {
-                if syn::Fields::Unit != v.fields {
+                if syn::Fields::Unit != v.fields && Some(&v.ident) != unknown {
                     return false;
                 }
             }
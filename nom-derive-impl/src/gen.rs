@@ -42,7 +42,10 @@ pub(crate) fn gen_impl(
         syn::Data::Union(_) => panic!("Unions not supported"),
     };
 
-    let impl_tokens = generator.gen_impl()?;
+    let mut impl_tokens = generator.gen_impl()?;
+    if generator.config().serialize {
+        impl_tokens.extend(generator.gen_serialize_impl()?);
+    }
     // eprintln!("\n***\nglobal_impl: {}\n---\n", impl_tokens);
     Ok(impl_tokens)
 }
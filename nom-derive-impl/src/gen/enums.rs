@@ -7,8 +7,10 @@ use crate::config::Config;
 use crate::endian::*;
 use crate::enums::*;
 use crate::meta;
+use crate::parsertree::ParserExpr;
 use crate::r#gen::get_extra_args;
-use crate::structs::get_pre_post_exec;
+use crate::serialize::{gen_fields_binding, gen_fields_serializer, gen_magic_serializer};
+use crate::structs::{add_input_bounds, get_magic_parsers, get_pre_post_exec, quote_magic};
 use crate::Result;
 
 use super::Generator;
@@ -22,6 +24,7 @@ pub struct GenEnum {
     orig_generics: Generics,
     tl_pre: Option<TokenStream>,
     tl_post: Option<TokenStream>,
+    magic: Vec<(ParserExpr, TokenStream)>,
     variants_defs: Vec<VariantParserTree>,
 }
 
@@ -65,8 +68,10 @@ impl Generator for GenEnum {
 
     fn gen_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream> {
         let orig_input = Ident::new(self.config.orig_input_name(), Span::call_site());
+        let orig_copy = self.config.input_copy(&orig_input);
         let input = Ident::new(self.config.input_name(), Span::call_site());
         let (tl_pre, tl_post) = (&self.tl_pre, &self.tl_post);
+        let magic = quote_magic(&self.magic, endianness, &self.config);
         // generate body
         let (default_case_handled, variants_code) = self.gen_variants(endianness)?;
         let default_case = if default_case_handled {
@@ -75,8 +80,9 @@ impl Generator for GenEnum {
             quote! { _ => Err(nom::Err::Error(nom::error_position!(#input, nom::error::ErrorKind::Switch))) }
         };
         let tokens = quote! {
-            let #input = #orig_input;
+            let #input = #orig_copy;
             #tl_pre
+            #magic
             let (#input, enum_def) = match selector {
                 #(#variants_code)*
                 #default_case
@@ -86,6 +92,30 @@ impl Generator for GenEnum {
         };
         Ok(tokens)
     }
+
+    fn gen_serialize_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream> {
+        let name = &self.name;
+        let variants_code = self
+            .variants_defs
+            .iter()
+            .map(|def| {
+                let variantname = &def.ident;
+                let binding = gen_fields_binding(&quote! { #name::#variantname }, &def.struct_def);
+                let fields = gen_fields_serializer(&def.struct_def.parsers, endianness)?;
+                Ok(quote! {
+                    #[allow(unused_variables)]
+                    #binding => { #fields }
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let magic = gen_magic_serializer(&self.magic, endianness)?;
+        Ok(quote! {
+            #magic
+            match self {
+                #(#variants_code)*
+            }
+        })
+    }
 }
 
 impl GenEnum {
@@ -102,6 +132,7 @@ impl GenEnum {
         let meta = meta::parse_nom_top_level_attribute(attrs)?;
         // eprintln!("top-level meta: {:?}", meta);
         let mut config = Config::from_meta_list(&meta)?;
+        config.set_context_name(name.to_string());
 
         // endianness must be set before parsing struct
         set_object_endianness(name.span(), endianness, &meta, &mut config)?;
@@ -117,6 +148,8 @@ impl GenEnum {
 
         // save global pre/post exec
         let (tl_pre, tl_post) = get_pre_post_exec(&meta, &config);
+        let magic = get_magic_parsers(&meta, &config)?;
+        add_input_bounds(&meta, &mut config);
 
         // fieldless enums should not be handled by this generator
         assert!(config.selector_type().is_some());
@@ -135,6 +168,7 @@ impl GenEnum {
             orig_generics: generics.clone(),
             tl_pre,
             tl_post,
+            magic,
             variants_defs,
         })
     }
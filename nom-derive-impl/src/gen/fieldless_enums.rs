@@ -1,4 +1,5 @@
 use proc_macro2::Ident;
+use proc_macro2::Literal;
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
 use syn::*;
@@ -9,7 +10,8 @@ use crate::enums::*;
 use crate::meta;
 use crate::parsertree::{ParserExpr, TypeItem};
 use crate::r#gen::get_extra_args;
-use crate::structs::get_pre_post_exec;
+use crate::serialize::{gen_expr_serializer, gen_magic_serializer};
+use crate::structs::{add_input_bounds, get_magic_parsers, get_pre_post_exec, quote_magic};
 use crate::Result;
 
 use super::Generator;
@@ -23,7 +25,14 @@ pub struct GenFieldlessEnum {
     orig_generics: Generics,
     tl_pre: Option<TokenStream>,
     tl_post: Option<TokenStream>,
+    magic: Vec<(ParserExpr, TokenStream)>,
     repr_parser: ParserExpr,
+    repr_type: Type,
+    variants: Vec<Ident>,
+    /// Value of each variant (the discriminant)
+    values: Vec<TokenStream>,
+    /// Catch-all variant, storing the value
+    unknown: Option<Ident>,
     variants_code: Vec<TokenStream>,
 }
 
@@ -67,24 +76,56 @@ impl Generator for GenFieldlessEnum {
 
     fn gen_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream> {
         let orig_input = Ident::new(self.config.orig_input_name(), Span::call_site());
+        let orig_copy = self.config.input_copy(&orig_input);
         let input = Ident::new(self.config.input_name(), Span::call_site());
         let (tl_pre, tl_post) = (&self.tl_pre, &self.tl_post);
         let variants_code = &self.variants_code;
+        let magic = quote_magic(&self.magic, endianness, &self.config);
         let parser = &self.repr_parser.with_endianness(endianness);
+        let default_case = match &self.unknown {
+            Some(unknown) => {
+                let name = &self.name;
+                quote! { { #name::#unknown(selector) } }
+            }
+            None => quote! {
+                { return Err(nom::Err::Error(nom::error::make_error(#orig_input, nom::error::ErrorKind::Switch))); }
+            },
+        };
         // generate body
         let tokens = quote! {
-            let #input = #orig_input;
+            let #input = #orig_copy;
             #tl_pre
+            #magic
             let (#input, selector) = #parser(#input)?;
             let enum_def =
                 #(#variants_code else)*
-            { return Err(nom::Err::Error(nom::error::make_error(#orig_input, nom::error::ErrorKind::Switch))); };
+                #default_case;
             #tl_post
             Ok((#input, enum_def))
         };
 
         Ok(tokens)
     }
+
+    fn gen_serialize_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream> {
+        let name = &self.name;
+        let repr_type = &self.repr_type;
+        let (variants, values) = (&self.variants, &self.values);
+        let unknown = self.unknown.as_ref().map(|unknown| {
+            quote! { #name::#unknown(v) => *v, }
+        });
+        let parser = self.repr_parser.with_endianness(endianness);
+        let magic = gen_magic_serializer(&self.magic, endianness)?;
+        let ser = gen_expr_serializer(&parser, &quote! { &selector }, &[], &[])?;
+        Ok(quote! {
+            #magic
+            let selector = match self {
+                #(#name::#variants => #values as #repr_type,)*
+                #unknown
+            };
+            #ser
+        })
+    }
 }
 
 impl GenFieldlessEnum {
@@ -116,6 +157,8 @@ impl GenFieldlessEnum {
 
         // save global pre/post exec
         let (tl_pre, tl_post) = get_pre_post_exec(&meta, &config);
+        let magic = get_magic_parsers(&meta, &config)?;
+        add_input_bounds(&meta, &mut config);
 
         if extra_args.is_some() {
             panic!("fieldless enums cannot have extra_args");
@@ -155,12 +198,25 @@ impl GenFieldlessEnum {
             }
         };
 
-        let variants_code: Vec<_> = data_enum
+        let unknown = get_unknown_variant(data_enum)?.map(|v| v.ident.clone());
+        let variants: Vec<_> = data_enum
             .variants
             .iter()
-            .map(|variant| {
-                let id = &variant.ident;
-                quote! { if selector == #name::#id as #repr_type { #name::#id } }
+            .filter(|v| Some(&v.ident) != unknown.as_ref())
+            .map(|v| v.ident.clone())
+            .collect();
+        // enums with a catch-all variant cannot be cast, so use the discriminants
+        let values = if unknown.is_some() {
+            get_discriminants(data_enum, unknown.as_ref())
+        } else {
+            variants.iter().map(|id| quote! { #name::#id }).collect()
+        };
+
+        let variants_code: Vec<_> = variants
+            .iter()
+            .zip(values.iter())
+            .map(|(id, value)| {
+                quote! { if selector == #value as #repr_type { #name::#id } }
             })
             .collect();
 
@@ -171,8 +227,37 @@ impl GenFieldlessEnum {
             orig_generics: generics.clone(),
             tl_pre,
             tl_post,
+            magic,
             repr_parser,
+            repr_type,
+            variants,
+            values,
+            unknown,
             variants_code,
         })
     }
 }
+
+/// Get the discriminants of the unit variants of an enum (implicit values are the previous value
+/// plus one)
+fn get_discriminants(data_enum: &DataEnum, unknown: Option<&Ident>) -> Vec<TokenStream> {
+    let mut values = Vec::new();
+    let mut last = None;
+    let mut offset = 0;
+    for v in data_enum.variants.iter() {
+        if let Some((_, expr)) = &v.discriminant {
+            last = Some(expr);
+            offset = 0;
+        }
+        if Some(&v.ident) != unknown {
+            let k = Literal::usize_unsuffixed(offset);
+            let value = match last {
+                Some(expr) => quote! { ((#expr) + #k) },
+                None => quote! { #k },
+            };
+            values.push(value);
+        }
+        offset += 1;
+    }
+    values
+}
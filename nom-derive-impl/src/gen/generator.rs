@@ -30,10 +30,14 @@ pub(crate) trait Generator {
 
     fn gen_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream>;
 
+    /// Generate the body of the serializer function (inverse of `gen_fn_body`)
+    fn gen_serialize_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream>;
+
     fn gen_parse_be(&self) -> Result<TokenStream> {
         let fn_decl = gen_fn_decl(
             ParserEndianness::BigEndian,
             self.extra_args(),
+            self.orig_generics(),
             self.config(),
         );
         if self.has_impl_for_endianness(ParserEndianness::BigEndian) {
@@ -61,6 +65,7 @@ pub(crate) trait Generator {
         let fn_decl = gen_fn_decl(
             ParserEndianness::LittleEndian,
             self.extra_args(),
+            self.orig_generics(),
             self.config(),
         );
         if self.has_impl_for_endianness(ParserEndianness::LittleEndian) {
@@ -85,38 +90,17 @@ pub(crate) trait Generator {
     }
 
     fn gen_parse(&self) -> Result<TokenStream> {
-        let ident_e = Ident::new(self.config().error_name(), Span::call_site());
-        let lft = Lifetime::new(self.config().lifetime_name(), Span::call_site());
         // 'parse' function
-        let maybe_err = if self.config().generic_errors {
-            quote!( , #ident_e )
-        } else {
-            quote!()
-        };
-        let special_case = self.extra_args().is_some() || self.config().selector_type().is_some();
-        let scope = if special_case {
-            quote! { pub }
-        } else {
-            quote! {}
-        };
-        let tokens_parse = {
-            let (fn_generics, where_clause) = if self.config().generic_errors && special_case {
-                (
-                    quote!(<#ident_e>),
-                    quote! {where
-                        #ident_e: nom_derive::nom::error::ParseError<&#lft [u8]>,
-                        #ident_e: std::fmt::Debug,
-                    },
-                )
-            } else {
-                (quote!(), quote!())
-            };
-            let call_args = self.get_call_args();
-            let fn_args = get_fn_args(self.extra_args(), self.config());
-            quote! {
-               #scope fn parse #fn_generics(#fn_args) -> nom::IResult<&'nom [u8], Self #maybe_err> #where_clause {
-                    Self::parse_be(#call_args)
-                }
+        let fn_decl = gen_fn_decl(
+            ParserEndianness::Unspecified,
+            self.extra_args(),
+            self.orig_generics(),
+            self.config(),
+        );
+        let call_args = self.get_call_args();
+        let tokens_parse = quote! {
+            #fn_decl {
+                Self::parse_be(#call_args)
             }
         };
         Ok(tokens_parse)
@@ -126,11 +110,6 @@ pub(crate) trait Generator {
         let name = self.name();
         let lft = Lifetime::new(self.config().lifetime_name(), Span::call_site());
         let ident_e = Ident::new(self.config().error_name(), Span::call_site());
-        let maybe_err = if self.config().generic_errors {
-            quote!( , #ident_e )
-        } else {
-            quote!()
-        };
 
         let tokens_parse = self.gen_parse()?;
         let tokens_parse_be = self.gen_parse_be()?;
@@ -141,10 +120,14 @@ pub(crate) trait Generator {
         let (impl_generics, ty_generics, where_clause) = orig_generics.split_for_impl();
 
         let mut gen_impl: Generics = parse_quote!(#impl_generics);
-        gen_impl
-            .params
-            .push(GenericParam::Lifetime(LifetimeParam::new(lft.clone())));
+        if !self.config().generic_input {
+            gen_impl
+                .params
+                .push(GenericParam::Lifetime(LifetimeParam::new(lft.clone())));
+        }
         let param_e = TypeParam::from(ident_e.clone());
+        let input_type = self.config().input_type();
+        let special_case = self.extra_args().is_some() || self.config().selector_type().is_some();
 
         let mut gen_wh: WhereClause = if where_clause.is_none() {
             parse_quote!(where)
@@ -152,17 +135,18 @@ pub(crate) trait Generator {
             parse_quote!(#where_clause)
         };
         let lfts: Vec<_> = orig_generics.lifetimes().collect();
-        if !lfts.is_empty() {
+        if !lfts.is_empty() && !self.config().generic_input {
             // input slice must outlive all lifetimes from Self
             let wh: WherePredicate = parse_quote! { #lft: #(#lfts)+* };
             gen_wh.predicates.push(wh);
         };
 
-        // make sure generic parameters inplement Parse
-        for param in orig_generics.type_params() {
-            let param_ident = &param.ident;
-            let dep: WherePredicate = parse_quote! { #param_ident: Parse< &#lft [u8] #maybe_err > };
-            gen_wh.predicates.push(dep);
+        // if input type is generic, it is a parameter of the functions (not the impl) in the
+        // special case
+        if !(special_case && self.config().generic_input) {
+            gen_wh
+                .predicates
+                .extend(gen_input_where_predicates(orig_generics, self.config()));
         }
         if let Some(impl_where_predicates) = self.impl_where_predicates() {
             for wh in impl_where_predicates {
@@ -171,8 +155,7 @@ pub(crate) trait Generator {
         }
 
         // Global impl
-        let impl_tokens = if self.extra_args().is_some() || self.config().selector_type().is_some()
-        {
+        let impl_tokens = if special_case {
             // There are extra arguments, so we can't generate the Parse impl
             // Generate an equivalent implementation
             if self.config().generic_errors {
@@ -193,17 +176,27 @@ pub(crate) trait Generator {
             }
         } else {
             // Generate an impl block for the Parse trait
+            if self.config().generic_input {
+                let ident_i = Ident::new(self.config().input_type_name(), Span::call_site());
+                gen_impl
+                    .params
+                    .push(GenericParam::Type(TypeParam::from(ident_i)));
+            }
             let error = if self.config().generic_errors {
                 let wh: WherePredicate =
-                    parse_quote!(#ident_e: nom::error::ParseError<& #lft [u8]>);
+                    parse_quote!(#ident_e: nom::error::ParseError<#input_type>);
                 gen_wh.predicates.push(wh);
+                if self.config().context.is_some() {
+                    let wh: WherePredicate = parse_quote!(#ident_e: nom_derive::collections::IndexContextError<#input_type>);
+                    gen_wh.predicates.push(wh);
+                }
                 gen_impl.params.push(GenericParam::Type(param_e));
                 quote! { #ident_e }
             } else {
-                quote! { nom::error::Error<&#lft [u8]> }
+                quote! { nom::error::Error<#input_type> }
             };
             quote! {
-                    impl #gen_impl nom_derive::Parse<& #lft [u8], #error> for #name #ty_generics #gen_wh {
+                    impl #gen_impl nom_derive::Parse<#input_type, #error> for #name #ty_generics #gen_wh {
                         #tokens_parse_be
                         #tokens_parse_le
                         #tokens_parse
@@ -219,6 +212,78 @@ pub(crate) trait Generator {
         Ok(impl_tokens)
     }
 
+    fn gen_serialize_fn(&self, endianness: ParserEndianness) -> Result<TokenStream> {
+        let (serialize, other) = match endianness {
+            ParserEndianness::BigEndian => ("serialize_be", "serialize_le"),
+            ParserEndianness::LittleEndian => ("serialize_le", "serialize_be"),
+            _ => panic!("gen_serialize_fn should only receive BigEndian or LittleEndian"),
+        };
+        let serialize = Ident::new(serialize, Span::call_site());
+        let fn_decl = quote! {
+            fn #serialize<NomOut: nom_derive::SerializeOutput + ?Sized>(&self, __out: &mut NomOut)
+                -> core::result::Result<(), nom_derive::SerializeError>
+        };
+        if self.has_impl_for_endianness(endianness) {
+            let fn_body = self.gen_serialize_fn_body(endianness)?;
+            Ok(quote! {
+                #fn_decl {
+                    use nom_derive::SerializeOutput;
+                    #[allow(unused_variables)]
+                    let __start = __out.position();
+                    #fn_body
+                    Ok(())
+                }
+            })
+        } else {
+            let other = Ident::new(other, Span::call_site());
+            Ok(quote! {
+                #fn_decl {
+                    self.#other(__out)
+                }
+            })
+        }
+    }
+
+    fn gen_serialize_impl(&self) -> Result<TokenStream> {
+        let name = self.name();
+        // note: the selector and extra arguments are not serialized, they are provided by the
+        // caller when parsing
+        let tokens_ser_be = self.gen_serialize_fn(ParserEndianness::BigEndian)?;
+        let tokens_ser_le = self.gen_serialize_fn(ParserEndianness::LittleEndian)?;
+
+        let orig_generics = &self.orig_generics();
+        let (impl_generics, ty_generics, where_clause) = orig_generics.split_for_impl();
+        let mut gen_wh: WhereClause = if where_clause.is_none() {
+            parse_quote!(where)
+        } else {
+            parse_quote!(#where_clause)
+        };
+        // make sure generic parameters inplement Serialize
+        for param in orig_generics.type_params() {
+            let param_ident = &param.ident;
+            let dep: WherePredicate = parse_quote! { #param_ident: nom_derive::Serialize };
+            gen_wh.predicates.push(dep);
+        }
+
+        let impl_tokens = quote! {
+            impl #impl_generics nom_derive::Serialize for #name #ty_generics #gen_wh {
+                fn serialize<NomOut: nom_derive::SerializeOutput + ?Sized>(&self, __out: &mut NomOut)
+                    -> core::result::Result<(), nom_derive::SerializeError>
+                {
+                    self.serialize_be(__out)
+                }
+                #tokens_ser_be
+                #tokens_ser_le
+            }
+        };
+
+        if self.config().debug_derive {
+            eprintln!("tokens:\n{}", impl_tokens);
+        }
+
+        Ok(impl_tokens)
+    }
+
     fn has_impl_for_endianness(&self, endianness: ParserEndianness) -> bool {
         assert!(
             endianness == ParserEndianness::BigEndian
@@ -275,6 +340,7 @@ pub(crate) trait Generator {
 pub(crate) fn gen_fn_decl(
     endianness: ParserEndianness,
     extra_args: Option<&TokenStream>,
+    orig_generics: &Generics,
     config: &Config,
 ) -> TokenStream {
     let parse = match endianness {
@@ -285,8 +351,7 @@ pub(crate) fn gen_fn_decl(
     };
     let parse = Ident::new(parse, Span::call_site());
     let fn_args = get_fn_args(extra_args, config);
-    // get lifetimes
-    let lft = Lifetime::new(config.lifetime_name(), Span::call_site());
+    let input_type = config.input_type();
     let mut fn_where_clause = WhereClause {
         where_token: Token![where](Span::call_site()),
         predicates: punctuated::Punctuated::new(),
@@ -300,36 +365,81 @@ pub(crate) fn gen_fn_decl(
         quote! {}
     };
 
+    // special case: not implementing the Parse trait, generic parameters must be added to
+    // function, not struct
+    let mut fn_generics: Punctuated<Ident, Token![,]> = Punctuated::new();
+    if special_case && config.generic_input {
+        fn_generics.push(Ident::new(config.input_type_name(), Span::call_site()));
+        fn_where_clause
+            .predicates
+            .extend(gen_input_where_predicates(orig_generics, config));
+    }
+
     // function declaration line
     if config.generic_errors {
         let ident_e = Ident::new(config.error_name(), Span::call_site());
-        let mut fn_generics = None;
         if special_case {
-            // special case: not implementing the Parse trait,
-            // generic errors must be added to function, not struct
-            //
             // extend where clause for generic parameters
             let dep: WherePredicate = parse_quote! {
-                #ident_e: nom_derive::nom::error::ParseError<&#lft [u8]>
+                #ident_e: nom_derive::nom::error::ParseError<#input_type>
             };
             fn_where_clause.predicates.push(dep);
+            if config.context.is_some() {
+                let dep: WherePredicate = parse_quote! {
+                    #ident_e: nom_derive::collections::IndexContextError<#input_type>
+                };
+                fn_where_clause.predicates.push(dep);
+            }
             let dep: WherePredicate = parse_quote! { #ident_e: std::fmt::Debug };
             fn_where_clause.predicates.push(dep);
             // add error type to function generics
-            fn_generics = Some(quote!(<#ident_e>));
+            fn_generics.push(ident_e.clone());
         }
+        let fn_generics = (!fn_generics.is_empty()).then(|| quote!(<#fn_generics>));
         quote! {
-           #scope fn #parse #fn_generics(#fn_args) -> nom::IResult<&#lft [u8], Self, #ident_e>
+           #scope fn #parse #fn_generics(#fn_args) -> nom::IResult<#input_type, Self, #ident_e>
             #fn_where_clause
         }
     } else {
+        let fn_generics = (!fn_generics.is_empty()).then(|| quote!(<#fn_generics>));
         quote! {
-           #scope fn #parse(#fn_args) -> nom::IResult<&#lft [u8], Self>
+           #scope fn #parse #fn_generics(#fn_args) -> nom::IResult<#input_type, Self>
             #fn_where_clause
         }
     }
 }
 
+/// Requirements on the input type and on the generic type parameters of Self
+fn gen_input_where_predicates(orig_generics: &Generics, config: &Config) -> Vec<WherePredicate> {
+    let input_type = config.input_type();
+    let mut predicates = Vec::new();
+    if config.generic_input {
+        let ident_i = Ident::new(config.input_type_name(), Span::call_site());
+        predicates.push(parse_quote! { #ident_i: nom_derive::InputSlice });
+        predicates.extend(config.input_bounds.iter().cloned());
+    }
+    let maybe_err = if config.generic_errors {
+        let ident_e = Ident::new(config.error_name(), Span::call_site());
+        quote!( , #ident_e )
+    } else {
+        quote!()
+    };
+    // make sure generic parameters inplement Parse (or FromParsedIter, for collections)
+    for param in orig_generics.type_params() {
+        let param_ident = &param.ident;
+        match config
+            .collection_params
+            .iter()
+            .find(|(ident, _)| ident == param_ident)
+        {
+            Some((_, elem)) => predicates
+                .push(parse_quote! { #param_ident: nom_derive::FromParsedIter<Item = #elem> }),
+            None => predicates.push(parse_quote! { #param_ident: Parse< #input_type #maybe_err > }),
+        }
+    }
+    predicates
+}
+
 pub(crate) fn get_extra_args(meta_list: &[MetaAttr]) -> Option<&TokenStream> {
     meta_list
         .iter()
@@ -342,12 +452,11 @@ pub(crate) fn get_fn_args(
     config: &Config,
 ) -> Punctuated<FnArg, Comma> {
     let orig_input = Ident::new(config.orig_input_name(), Span::call_site());
-    // get lifetimes
-    let lft = Lifetime::new(config.lifetime_name(), Span::call_site());
+    let input_type = config.input_type();
 
     // function arguments: input first
     let mut fn_args: Punctuated<_, Token![,]> = Punctuated::new();
-    let arg_input: FnArg = parse_quote!(#orig_input: &#lft [u8]);
+    let arg_input: FnArg = parse_quote!(#orig_input: #input_type);
     fn_args.push(arg_input);
     // selector, if present
     if let Some(sel_type) = config.selector_type() {
@@ -4,6 +4,8 @@ use syn::*;
 use crate::config::Config;
 use crate::endian::*;
 use crate::meta;
+use crate::parsertree::ParserExpr;
+use crate::serialize::{gen_fields_binding, gen_fields_serializer, gen_magic_serializer};
 use crate::structs::*;
 
 use super::*;
@@ -17,6 +19,7 @@ pub struct GenStruct {
     orig_generics: Generics,
     tl_pre: Option<TokenStream>,
     tl_post: Option<TokenStream>,
+    magic: Vec<(ParserExpr, TokenStream)>,
     parser_tree: StructParserTree,
     impl_where_predicates: Option<Vec<WherePredicate>>,
 }
@@ -68,6 +71,8 @@ impl Generator for GenStruct {
         let (tl_pre, tl_post) = (&self.tl_pre, &self.tl_post);
         let input = syn::Ident::new(self.config.input_name(), Span::call_site());
         let orig_input = syn::Ident::new(self.config.orig_input_name(), Span::call_site());
+        let orig_copy = self.config.input_copy(&orig_input);
+        let magic = quote_magic(&self.magic, endianness, &self.config);
 
         // prepare tokens
         let (idents, parser_tokens): (Vec<_>, Vec<_>) = self
@@ -103,8 +108,9 @@ impl Generator for GenStruct {
 
         let fn_body = quote! {
             use nom::Parser;
-            let #input = #orig_input;
+            let #input = #orig_copy;
             #tl_pre
+            #magic
             #(#pre let (#input, #idents) = #parser_tokens (#input) ?; #post)*
             let struct_def = #struct_def;
             #tl_post
@@ -112,6 +118,18 @@ impl Generator for GenStruct {
         };
         Ok(fn_body)
     }
+
+    fn gen_serialize_fn_body(&self, endianness: ParserEndianness) -> Result<TokenStream> {
+        let binding = gen_fields_binding(&quote! { Self }, &self.parser_tree);
+        let magic = gen_magic_serializer(&self.magic, endianness)?;
+        let fields = gen_fields_serializer(&self.parser_tree.parsers, endianness)?;
+        Ok(quote! {
+            #[allow(unused_variables)]
+            let #binding = self;
+            #magic
+            #fields
+        })
+    }
 }
 
 impl GenStruct {
@@ -128,6 +146,7 @@ impl GenStruct {
         let meta = meta::parse_nom_top_level_attribute(attrs)?;
         // eprintln!("top-level meta: {:?}", meta);
         let mut config = Config::from_meta_list(&meta)?;
+        config.set_context_name(name.to_string());
 
         // endianness must be set before parsing struct
         set_object_endianness(name.span(), endianness, &meta, &mut config)?;
@@ -143,6 +162,8 @@ impl GenStruct {
 
         // save global pre/post exec
         let (tl_pre, tl_post) = get_pre_post_exec(&meta, &config);
+        let magic = get_magic_parsers(&meta, &config)?;
+        add_input_bounds(&meta, &mut config);
 
         let s = parse_struct(datastruct, &mut config)?;
 
@@ -155,29 +176,64 @@ impl GenStruct {
             orig_generics: generics.clone(),
             tl_pre,
             tl_post,
+            magic,
             parser_tree: s,
             impl_where_predicates,
         })
     }
 }
 
+/// Get the type used to find requirements on the Error type
+///
+/// For items of collections (`<C as FromParsedIter>::Item`), this is the generic argument of the
+/// collection, if it has only one (for ex. `String` for `Vec<String>`).
+fn get_bounds_type(ty: &Type) -> &Type {
+    if let Type::Path(TypePath { qself: Some(q), .. }) = ty {
+        if let Type::Path(container) = q.ty.as_ref() {
+            let segment = container.path.segments.last().expect("empty segments list");
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let (1, Some(GenericArgument::Type(arg))) = (args.args.len(), args.args.first())
+                {
+                    return arg;
+                }
+            }
+        }
+    }
+    ty
+}
+
 /// Find additional where clauses to add (for ex. `String` requires `FromExternalError<&[u8], Utf8Error>`)
-#[allow(clippy::single_match)]
 fn add_extra_where_predicates(
     parser_tree: &StructParserTree,
     config: &Config,
 ) -> Option<Vec<WherePredicate>> {
     if config.generic_errors {
         let mut v = Vec::new();
-        let lft = Lifetime::new(config.lifetime_name(), Span::call_site());
+        let input_type = config.input_type();
         let err = Ident::new(config.error_name(), Span::call_site());
         // visit parser tree and look for types with requirement on Error type
         for p in &parser_tree.parsers {
+            if p.item.expr.contains_str() {
+                let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<#input_type, core::str::Utf8Error>};
+                v.push(wh)
+            }
+            if p.item.expr.contains_encoded() {
+                let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<#input_type, nom_derive::strings::EncodingError>};
+                v.push(wh)
+            }
             if let Some(ty) = p.item.expr.last_type() {
-                if let Ok(s) = get_type_first_ident(&ty.0) {
+                if let Ok(s) = get_type_first_ident(get_bounds_type(&ty.0)) {
                     match s.as_ref() {
                         "String" => {
-                            let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<&#lft [u8], core::str::Utf8Error>};
+                            let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<#input_type, core::str::Utf8Error>};
+                            v.push(wh)
+                        }
+                        "char" => {
+                            let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<#input_type, core::char::CharTryFromError>};
+                            v.push(wh)
+                        }
+                        s if s.starts_with("NonZero") => {
+                            let wh: WherePredicate = parse_quote! {#err: nom::error::FromExternalError<#input_type, core::num::TryFromIntError>};
                             v.push(wh)
                         }
                         _ => (),
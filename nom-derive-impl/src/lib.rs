@@ -22,6 +22,7 @@ mod enums;
 mod r#gen;
 mod meta;
 mod parsertree;
+mod serialize;
 mod structs;
 
 use crate::endian::*;
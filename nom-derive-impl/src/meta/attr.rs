@@ -11,34 +11,67 @@ use syn::{token, Expr, ExprLit, FnArg, Ident, Lit, Meta, Pat, Stmt, Token};
 pub enum MetaAttrType {
     AlignAfter,
     AlignBefore,
+    AtOffset,
     BigEndian,
+    BitOrder,
+    Bits,
+    Checksum,
     Complete,
     Cond,
+    Context,
     Count,
+    CountOf,
     Debug,
     DebugDerive,
+    Elem,
+    Encoding,
     ErrorIf,
     Exact,
     ExtraArgs,
+    Flags,
     GenericErrors,
+    GenericInput,
+    Hex,
     Ignore,
     InputName,
+    Int24,
     Into,
     LengthCount,
+    LengthOf,
+    LengthPrefix,
     LittleEndian,
+    Magic,
     Map,
     Move,
     MoveAbs,
+    NulTerminated,
+    OffsetBase,
+    PadByte,
+    Padded,
     Parse,
+    Peek,
     PostExec,
     PreExec,
+    RepeatUntil,
+    RepeatUntilTag,
     Selector,
+    Serialize,
+    SerializeWith,
     SetEndian,
+    Size,
     SkipAfter,
     SkipBefore,
+    StrInput,
+    StrictSize,
     Tag,
     Take,
+    Terminator,
+    Trim,
+    Uint24,
+    Uint48,
+    Unknown,
     Value,
+    Varint,
     Verify,
 }
 
@@ -47,34 +80,67 @@ impl MetaAttrType {
         match ident.to_string().as_ref() {
             "AlignAfter" => Some(MetaAttrType::AlignAfter),
             "AlignBefore" => Some(MetaAttrType::AlignBefore),
+            "AtOffset" | "Pointer" => Some(MetaAttrType::AtOffset),
             "BigEndian" => Some(MetaAttrType::BigEndian),
+            "BitOrder" => Some(MetaAttrType::BitOrder),
+            "Bits" => Some(MetaAttrType::Bits),
+            "Checksum" => Some(MetaAttrType::Checksum),
             "Complete" => Some(MetaAttrType::Complete),
+            "Context" => Some(MetaAttrType::Context),
             "Count" => Some(MetaAttrType::Count),
+            "CountOf" => Some(MetaAttrType::CountOf),
             "Debug" => Some(MetaAttrType::Debug),
             "DebugDerive" => Some(MetaAttrType::DebugDerive),
+            "Elem" => Some(MetaAttrType::Elem),
+            "Encoding" => Some(MetaAttrType::Encoding),
             "ErrorIf" => Some(MetaAttrType::ErrorIf),
             "Exact" => Some(MetaAttrType::Exact),
             "ExtraArgs" => Some(MetaAttrType::ExtraArgs),
+            "Flags" => Some(MetaAttrType::Flags),
             "GenericErrors" => Some(MetaAttrType::GenericErrors),
+            "GenericInput" => Some(MetaAttrType::GenericInput),
+            "Hex" => Some(MetaAttrType::Hex),
             "If" | "Cond" => Some(MetaAttrType::Cond),
             "Ignore" | "Default" => Some(MetaAttrType::Ignore),
             "InputName" => Some(MetaAttrType::InputName),
+            "Int24" => Some(MetaAttrType::Int24),
             "Into" => Some(MetaAttrType::Into),
             "LengthCount" => Some(MetaAttrType::LengthCount),
+            "LengthOf" => Some(MetaAttrType::LengthOf),
+            "LengthPrefix" => Some(MetaAttrType::LengthPrefix),
             "LittleEndian" => Some(MetaAttrType::LittleEndian),
+            "Magic" => Some(MetaAttrType::Magic),
             "Map" => Some(MetaAttrType::Map),
             "Move" => Some(MetaAttrType::Move),
             "MoveAbs" => Some(MetaAttrType::MoveAbs),
+            "NulTerminated" => Some(MetaAttrType::NulTerminated),
+            "OffsetBase" => Some(MetaAttrType::OffsetBase),
+            "PadByte" => Some(MetaAttrType::PadByte),
+            "Padded" => Some(MetaAttrType::Padded),
             "Parse" => Some(MetaAttrType::Parse),
+            "Peek" => Some(MetaAttrType::Peek),
             "PostExec" => Some(MetaAttrType::PostExec),
             "PreExec" => Some(MetaAttrType::PreExec),
+            "RepeatUntil" => Some(MetaAttrType::RepeatUntil),
+            "RepeatUntilTag" => Some(MetaAttrType::RepeatUntilTag),
             "Selector" => Some(MetaAttrType::Selector),
+            "Serialize" => Some(MetaAttrType::Serialize),
+            "SerializeWith" => Some(MetaAttrType::SerializeWith),
             "SetEndian" => Some(MetaAttrType::SetEndian),
+            "Size" => Some(MetaAttrType::Size),
             "SkipAfter" => Some(MetaAttrType::SkipAfter),
             "SkipBefore" => Some(MetaAttrType::SkipBefore),
+            "StrInput" => Some(MetaAttrType::StrInput),
+            "StrictSize" => Some(MetaAttrType::StrictSize),
             "Tag" => Some(MetaAttrType::Tag),
             "Take" => Some(MetaAttrType::Take),
+            "Terminator" => Some(MetaAttrType::Terminator),
+            "Trim" => Some(MetaAttrType::Trim),
+            "Uint24" => Some(MetaAttrType::Uint24),
+            "Uint48" => Some(MetaAttrType::Uint48),
+            "Unknown" => Some(MetaAttrType::Unknown),
             "Value" => Some(MetaAttrType::Value),
+            "Varint" => Some(MetaAttrType::Varint),
             "Verify" => Some(MetaAttrType::Verify),
             _ => None,
         }
@@ -85,25 +151,46 @@ impl MetaAttrType {
             self,
             MetaAttrType::AlignAfter
                 | MetaAttrType::AlignBefore
+                | MetaAttrType::AtOffset
+                | MetaAttrType::BitOrder
+                | MetaAttrType::Bits
+                | MetaAttrType::Checksum
                 | MetaAttrType::Cond
                 | MetaAttrType::Count
+                | MetaAttrType::CountOf
+                | MetaAttrType::Elem
+                | MetaAttrType::Encoding
                 | MetaAttrType::ErrorIf
                 | MetaAttrType::ExtraArgs
+                | MetaAttrType::Flags
                 | MetaAttrType::InputName
                 | MetaAttrType::LengthCount
+                | MetaAttrType::LengthOf
+                | MetaAttrType::LengthPrefix
+                | MetaAttrType::Magic
                 | MetaAttrType::Map
                 | MetaAttrType::Move
                 | MetaAttrType::MoveAbs
+                | MetaAttrType::OffsetBase
+                | MetaAttrType::PadByte
+                | MetaAttrType::Padded
                 | MetaAttrType::Parse
                 | MetaAttrType::PostExec
                 | MetaAttrType::PreExec
+                | MetaAttrType::RepeatUntil
+                | MetaAttrType::RepeatUntilTag
                 | MetaAttrType::Selector
+                | MetaAttrType::SerializeWith
                 | MetaAttrType::SetEndian
+                | MetaAttrType::Size
                 | MetaAttrType::SkipAfter
                 | MetaAttrType::SkipBefore
                 | MetaAttrType::Tag
                 | MetaAttrType::Take
+                | MetaAttrType::Terminator
+                | MetaAttrType::Trim
                 | MetaAttrType::Value
+                | MetaAttrType::Varint
                 | MetaAttrType::Verify
         )
     }
@@ -114,41 +201,74 @@ impl fmt::Display for MetaAttrType {
         let s = match self {
             MetaAttrType::AlignAfter => "AlignAfter",
             MetaAttrType::AlignBefore => "AlignBefore",
+            MetaAttrType::AtOffset => "AtOffset",
             MetaAttrType::BigEndian => "BigEndian",
+            MetaAttrType::BitOrder => "BitOrder",
+            MetaAttrType::Bits => "Bits",
+            MetaAttrType::Checksum => "Checksum",
             MetaAttrType::Complete => "Complete",
             MetaAttrType::Cond => "Cond",
+            MetaAttrType::Context => "Context",
             MetaAttrType::Count => "Count",
+            MetaAttrType::CountOf => "CountOf",
             MetaAttrType::Debug => "Debug",
             MetaAttrType::DebugDerive => "DebugDerive",
+            MetaAttrType::Elem => "Elem",
+            MetaAttrType::Encoding => "Encoding",
             MetaAttrType::ErrorIf => "ErrorIf",
             MetaAttrType::Exact => "Exact",
             MetaAttrType::ExtraArgs => "ExtraArgs",
+            MetaAttrType::Flags => "Flags",
             MetaAttrType::GenericErrors => "GenericErrors",
+            MetaAttrType::GenericInput => "GenericInput",
+            MetaAttrType::Hex => "Hex",
             MetaAttrType::Ignore => "Ignore",
             MetaAttrType::InputName => "InputName",
+            MetaAttrType::Int24 => "Int24",
             MetaAttrType::Into => "Into",
             MetaAttrType::LengthCount => "LengthCount",
+            MetaAttrType::LengthOf => "LengthOf",
+            MetaAttrType::LengthPrefix => "LengthPrefix",
             MetaAttrType::LittleEndian => "LittleEndian",
+            MetaAttrType::Magic => "Magic",
             MetaAttrType::Map => "Map",
             MetaAttrType::Move => "Move",
             MetaAttrType::MoveAbs => "MoveAbs",
+            MetaAttrType::NulTerminated => "NulTerminated",
+            MetaAttrType::OffsetBase => "OffsetBase",
+            MetaAttrType::PadByte => "PadByte",
+            MetaAttrType::Padded => "Padded",
             MetaAttrType::Parse => "Parse",
+            MetaAttrType::Peek => "Peek",
             MetaAttrType::PostExec => "PostExec",
             MetaAttrType::PreExec => "PreExec",
+            MetaAttrType::RepeatUntil => "RepeatUntil",
+            MetaAttrType::RepeatUntilTag => "RepeatUntilTag",
             MetaAttrType::Selector => "Selector",
+            MetaAttrType::Serialize => "Serialize",
+            MetaAttrType::SerializeWith => "SerializeWith",
             MetaAttrType::SetEndian => "SetEndian",
+            MetaAttrType::Size => "Size",
             MetaAttrType::SkipAfter => "SkipAfter",
             MetaAttrType::SkipBefore => "SkipBefore",
+            MetaAttrType::StrInput => "StrInput",
+            MetaAttrType::StrictSize => "StrictSize",
             MetaAttrType::Tag => "Tag",
             MetaAttrType::Take => "Take",
+            MetaAttrType::Terminator => "Terminator",
+            MetaAttrType::Trim => "Trim",
+            MetaAttrType::Uint24 => "Uint24",
+            MetaAttrType::Uint48 => "Uint48",
+            MetaAttrType::Unknown => "Unknown",
             MetaAttrType::Value => "Value",
+            MetaAttrType::Varint => "Varint",
             MetaAttrType::Verify => "Verify",
         };
         f.write_str(s)
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MetaAttr {
     pub attr_type: MetaAttrType,
     arg0: Option<TokenStream>,
@@ -183,6 +303,14 @@ impl MetaAttr {
                 | MetaAttrType::PostExec
                 | MetaAttrType::Exact
                 | MetaAttrType::Selector
+                | MetaAttrType::Serialize
+                | MetaAttrType::BitOrder
+                | MetaAttrType::Bits
+                | MetaAttrType::GenericInput
+                | MetaAttrType::StrInput
+                | MetaAttrType::Context
+                | MetaAttrType::Magic
+                | MetaAttrType::Tag
         )
     }
 
@@ -195,6 +323,11 @@ impl MetaAttr {
                 | MetaAttrType::ExtraArgs
                 | MetaAttrType::GenericErrors
                 | MetaAttrType::InputName
+                | MetaAttrType::Serialize
+                | MetaAttrType::GenericInput
+                | MetaAttrType::StrInput
+                | MetaAttrType::Context
+                | MetaAttrType::Magic
         )
     }
 
@@ -227,7 +360,12 @@ impl TryFrom<&'_ Meta> for MetaAttr {
         let attr_type =
             MetaAttrType::from_ident(&ident).unwrap_or_else(|| panic!("Wrong meta name {}", ident));
 
-        let arg0 = if attr_type.takes_argument() {
+        // the argument of Bits is optional (used without argument at top-level), and the
+        // argument of Flags has a default value
+        let optional_arg = matches!(attr_type, MetaAttrType::Bits | MetaAttrType::Flags);
+        let arg0 = if optional_arg && matches!(meta, Meta::Path(_)) {
+            None
+        } else if attr_type.takes_argument() {
             let token_stream = match attr_type {
                 MetaAttrType::ExtraArgs => {
                     let list = meta.require_list()?;
@@ -237,6 +375,7 @@ impl TryFrom<&'_ Meta> for MetaAttr {
                 }
                 MetaAttrType::PreExec | MetaAttrType::PostExec => parse_meta_content::<Stmt>(meta)?,
                 MetaAttrType::Selector => parse_meta_content::<PatternAndGuard>(meta)?,
+                MetaAttrType::Checksum => parse_meta_content::<ExprList>(meta)?,
                 _ => parse_meta_content::<Expr>(meta)?,
             };
             Some(token_stream)
@@ -286,6 +425,13 @@ where
             {
                 let p: P = lit_str.parse()?;
                 Ok(quote! { #p })
+            } else if let Expr::Lit(ExprLit {
+                lit: Lit::Int(lit_int),
+                ..
+            }) = &meta_name_value.value
+            {
+                // integer literals can be used directly, for ex. `Bits = 4`
+                Ok(quote! { #lit_int })
             } else {
                 Err(syn::Error::new(
                     meta.span(),
@@ -296,6 +442,22 @@ where
     }
 }
 
+/// A comma-separated list of expressions
+#[derive(Debug)]
+struct ExprList(Punctuated<Expr, Token![,]>);
+
+impl Parse for ExprList {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Punctuated::parse_terminated(input).map(ExprList)
+    }
+}
+
+impl quote::ToTokens for ExprList {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.0.to_tokens(tokens);
+    }
+}
+
 #[derive(Debug)]
 struct PatternAndGuard {
     pat: syn::Pat,
@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Group, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::Ident;
 
@@ -44,28 +44,57 @@ impl ToTokens for ParserTreeItem {
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug)]
 pub enum ParserExpr {
+    /// Parser applied at an offset (relative to a base input), and optional flag (for `Option`)
+    AtOffset(Box<ParserExpr>, TokenStream, TokenStream, bool),
+    Bits(BitsField, TypeItem),
     CallParse(TypeItem),
     CallParseBE(TypeItem),
     CallParseLE(TypeItem),
     Complete(Box<ParserExpr>),
-    Cond(Box<ParserExpr>, TokenStream),
-    Count(Box<ParserExpr>, TokenStream),
+    /// Parser, condition, and type of the container
+    Cond(Box<ParserExpr>, TokenStream, TypeItem),
+    Context(Box<ParserExpr>, String),
+    /// Parser, number of items, and type of the container
+    Count(Box<ParserExpr>, TokenStream, TypeItem),
     DbgDmp(Box<ParserExpr>, Ident),
+    /// String in a non-UTF-8 encoding, decoded to `String`
+    Encoded(EncodedLength, StringEncoding),
+    /// `bitflags` type, built from the parsed bits
+    Flags(Box<ParserExpr>, FlagsPolicy, TypeItem),
+    /// Parser of the elements of a collection, adding the index of the element to errors
+    Indexed(Box<ParserExpr>),
     Into(Box<ParserExpr>),
-    LengthCount(Box<ParserExpr>, TokenStream),
+    /// Parser, parser for the number of items, and type of the container
+    LengthCount(Box<ParserExpr>, TokenStream, TypeItem),
     Map(Box<ParserExpr>, TokenStream),
     Nop,
+    Peek(Box<ParserExpr>),
     PhantomData,
     Raw(TokenStream),
+    RepeatUntil(Box<ParserExpr>, RepeatTerminator, TerminatorMode),
+    /// Remaining bytes of input
+    Rest,
+    SerializeWith(Box<ParserExpr>, TokenStream),
+    /// Parser applied to a window of input of the given size, and flag to reject leftover bytes
+    Size(Box<ParserExpr>, TokenStream, bool),
+    Str(StringFormat, TypeItem),
     Tag(TokenStream),
     Take(TokenStream),
     Value(TokenStream),
     Verify(Box<ParserExpr>, Ident, TokenStream),
+    /// Parser for a wrapper type (tuple struct with one field), and constructor of the wrapper
+    Wrapped(Box<ParserExpr>, TokenStream),
 }
 
 impl ParserExpr {
     pub fn with_endianness(&self, endianness: ParserEndianness) -> Self {
         match self {
+            ParserExpr::AtOffset(expr, offset, base, optional) => ParserExpr::AtOffset(
+                expr.with_endianness(endianness).into(),
+                offset.clone(),
+                base.clone(),
+                *optional,
+            ),
             ParserExpr::CallParse(item) => match endianness {
                 ParserEndianness::BigEndian => ParserExpr::CallParseBE(item.clone()),
                 ParserEndianness::LittleEndian => ParserExpr::CallParseLE(item.clone()),
@@ -74,27 +103,80 @@ impl ParserExpr {
             ParserExpr::Complete(expr) => {
                 ParserExpr::Complete(expr.with_endianness(endianness).into())
             }
-            ParserExpr::Cond(expr, c) => {
-                ParserExpr::Cond(expr.with_endianness(endianness).into(), c.clone())
-            }
-            ParserExpr::Count(expr, n) => {
-                ParserExpr::Count(expr.with_endianness(endianness).into(), n.clone())
+            ParserExpr::Cond(expr, c, ty) => ParserExpr::Cond(
+                expr.with_endianness(endianness).into(),
+                c.clone(),
+                ty.clone(),
+            ),
+            ParserExpr::Context(expr, c) => {
+                ParserExpr::Context(expr.with_endianness(endianness).into(), c.clone())
             }
+            ParserExpr::Count(expr, n, ty) => ParserExpr::Count(
+                expr.with_endianness(endianness).into(),
+                n.clone(),
+                ty.clone(),
+            ),
             ParserExpr::DbgDmp(expr, i) => {
                 ParserExpr::DbgDmp(expr.with_endianness(endianness).into(), i.clone())
             }
-            ParserExpr::Into(expr) => ParserExpr::Into(expr.with_endianness(endianness).into()),
-            ParserExpr::LengthCount(expr, n) => {
-                ParserExpr::LengthCount(expr.with_endianness(endianness).into(), n.clone())
+            ParserExpr::Encoded(len, enc) => {
+                let len = match len {
+                    EncodedLength::Prefix(expr, ty) => {
+                        EncodedLength::Prefix(expr.with_endianness(endianness).into(), ty.clone())
+                    }
+                    len => len.clone(),
+                };
+                let enc = match (enc, endianness) {
+                    (StringEncoding::Utf16, ParserEndianness::BigEndian) => {
+                        StringEncoding::Fixed(quote! { nom_derive::strings::Encoding::Utf16Be })
+                    }
+                    (StringEncoding::Utf16, ParserEndianness::LittleEndian) => {
+                        StringEncoding::Fixed(quote! { nom_derive::strings::Encoding::Utf16Le })
+                    }
+                    (enc, _) => enc.clone(),
+                };
+                ParserExpr::Encoded(len, enc)
+            }
+            ParserExpr::Flags(expr, p, ty) => {
+                ParserExpr::Flags(expr.with_endianness(endianness).into(), *p, ty.clone())
             }
+            ParserExpr::Indexed(expr) => {
+                ParserExpr::Indexed(expr.with_endianness(endianness).into())
+            }
+            ParserExpr::Into(expr) => ParserExpr::Into(expr.with_endianness(endianness).into()),
+            ParserExpr::LengthCount(expr, n, ty) => ParserExpr::LengthCount(
+                expr.with_endianness(endianness).into(),
+                n.clone(),
+                ty.clone(),
+            ),
             ParserExpr::Map(expr, m) => {
                 ParserExpr::Map(expr.with_endianness(endianness).into(), m.clone())
             }
+            ParserExpr::Peek(expr) => ParserExpr::Peek(expr.with_endianness(endianness).into()),
+            ParserExpr::RepeatUntil(expr, t, m) => {
+                ParserExpr::RepeatUntil(expr.with_endianness(endianness).into(), t.clone(), *m)
+            }
+            ParserExpr::SerializeWith(expr, f) => {
+                ParserExpr::SerializeWith(expr.with_endianness(endianness).into(), f.clone())
+            }
+            ParserExpr::Size(expr, n, strict) => {
+                ParserExpr::Size(expr.with_endianness(endianness).into(), n.clone(), *strict)
+            }
+            ParserExpr::Str(StringFormat::LengthPrefixed(expr, len_ty), ty) => ParserExpr::Str(
+                StringFormat::LengthPrefixed(
+                    expr.with_endianness(endianness).into(),
+                    len_ty.clone(),
+                ),
+                ty.clone(),
+            ),
             ParserExpr::Verify(expr, i, v) => ParserExpr::Verify(
                 expr.with_endianness(endianness).into(),
                 i.clone(),
                 v.clone(),
             ),
+            ParserExpr::Wrapped(expr, w) => {
+                ParserExpr::Wrapped(expr.with_endianness(endianness).into(), w.clone())
+            }
             expr => expr.clone(),
         }
     }
@@ -104,27 +186,122 @@ impl ParserExpr {
         ParserExpr::Complete(Box::new(self))
     }
 
+    /// Add the index of elements to errors, for `Count` and `LengthCount` collections
+    pub fn with_indices(self) -> Self {
+        match self {
+            ParserExpr::Complete(expr) => ParserExpr::Complete(expr.with_indices().into()),
+            ParserExpr::Count(expr, n, ty) => {
+                ParserExpr::Count(ParserExpr::Indexed(expr).into(), n, ty)
+            }
+            ParserExpr::DbgDmp(expr, i) => ParserExpr::DbgDmp(expr.with_indices().into(), i),
+            ParserExpr::LengthCount(expr, n, ty) => {
+                ParserExpr::LengthCount(ParserExpr::Indexed(expr).into(), n, ty)
+            }
+            ParserExpr::Verify(expr, i, v) => ParserExpr::Verify(expr.with_indices().into(), i, v),
+            expr => expr,
+        }
+    }
+
     pub fn last_type(&self) -> Option<&TypeItem> {
         match self {
             ParserExpr::CallParse(e) | ParserExpr::CallParseBE(e) | ParserExpr::CallParseLE(e) => {
                 Some(e)
             }
-            ParserExpr::Complete(expr)
-            | ParserExpr::Cond(expr, _)
-            | ParserExpr::Count(expr, _)
+            ParserExpr::AtOffset(expr, _, _, _)
+            | ParserExpr::Complete(expr)
+            | ParserExpr::Cond(expr, _, _)
+            | ParserExpr::Context(expr, _)
+            | ParserExpr::Count(expr, _, _)
             | ParserExpr::DbgDmp(expr, _)
+            | ParserExpr::Flags(expr, _, _)
+            | ParserExpr::Indexed(expr)
             | ParserExpr::Into(expr)
-            | ParserExpr::LengthCount(expr, _)
+            | ParserExpr::LengthCount(expr, _, _)
             | ParserExpr::Map(expr, _)
-            | ParserExpr::Verify(expr, _, _) => expr.last_type(),
+            | ParserExpr::Peek(expr)
+            | ParserExpr::RepeatUntil(expr, _, _)
+            | ParserExpr::SerializeWith(expr, _)
+            | ParserExpr::Size(expr, _, _)
+            | ParserExpr::Verify(expr, _, _)
+            | ParserExpr::Wrapped(expr, _) => expr.last_type(),
             _ => None,
         }
     }
+
+    /// Does the parser build a string field (requiring UTF-8 errors)?
+    pub fn contains_str(&self) -> bool {
+        self.contains(&|e| matches!(e, ParserExpr::Str(_, _)))
+    }
+
+    /// Does the parser decode a non-UTF-8 string (requiring encoding errors)?
+    pub fn contains_encoded(&self) -> bool {
+        self.contains(&|e| matches!(e, ParserExpr::Encoded(_, _)))
+    }
+
+    fn contains(&self, f: &dyn Fn(&ParserExpr) -> bool) -> bool {
+        if f(self) {
+            return true;
+        }
+        match self {
+            ParserExpr::AtOffset(expr, _, _, _)
+            | ParserExpr::Complete(expr)
+            | ParserExpr::Cond(expr, _, _)
+            | ParserExpr::Context(expr, _)
+            | ParserExpr::Count(expr, _, _)
+            | ParserExpr::DbgDmp(expr, _)
+            | ParserExpr::Flags(expr, _, _)
+            | ParserExpr::Indexed(expr)
+            | ParserExpr::Into(expr)
+            | ParserExpr::LengthCount(expr, _, _)
+            | ParserExpr::Map(expr, _)
+            | ParserExpr::Peek(expr)
+            | ParserExpr::RepeatUntil(expr, _, _)
+            | ParserExpr::SerializeWith(expr, _)
+            | ParserExpr::Size(expr, _, _)
+            | ParserExpr::Verify(expr, _, _)
+            | ParserExpr::Wrapped(expr, _) => expr.contains(f),
+            _ => false,
+        }
+    }
 }
 
 impl ToTokens for ParserExpr {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let ts = match self {
+            ParserExpr::AtOffset(expr, offset, base, optional) => {
+                let (null_check, value) = if *optional {
+                    (
+                        quote! { if __offset == 0 { return Ok((__i__, None)); } },
+                        quote! { Some(__v) },
+                    )
+                } else {
+                    (quote! {}, quote! { __v })
+                };
+                quote! {
+                    { |__i__| {
+                        let __offset = (#offset) as usize;
+                        #null_check
+                        match nom_derive::input_at(&#base, __offset) {
+                            Some(__target) => nom::Parser::parse(&mut #expr, __target)
+                                .map(|(_, __v)| (__i__, #value)),
+                            None => Err(nom::Err::Error(nom::error::ParseError::from_error_kind(
+                                #base.clone(),
+                                nom::error::ErrorKind::Eof,
+                            ))),
+                        }
+                    } }
+                }
+            }
+            ParserExpr::Bits(b, ty) => {
+                let (data, offset, width) = (&b.data, b.offset, b.width);
+                let order = b.order_tokens();
+                quote! {
+                    { |__i__| Ok((__i__, <#ty as nom_derive::bits::BitField>::from_bits(
+                        nom_derive::bits::extract_bits(&#data, #offset, #width, #order),
+                        #width,
+                    ))) }
+                }
+            }
             ParserExpr::CallParse(s) => {
                 quote! { <#s>::parse }
             }
@@ -137,21 +314,61 @@ impl ToTokens for ParserExpr {
             ParserExpr::Complete(expr) => {
                 quote! { nom::combinator::complete(#expr) }
             }
-            ParserExpr::Cond(expr, c) => {
-                quote! { nom::combinator::cond(#c, #expr) }
+            ParserExpr::Cond(expr, c, ty) => {
+                let ty_ = ty.elided();
+                quote! { nom_derive::collections::cond::<#ty_, _, _, _>(#c, #expr) }
             }
-            ParserExpr::Count(expr, n) => {
-                quote! { nom::multi::count(#expr, #n as usize) }
+            ParserExpr::Context(expr, c) => {
+                quote! { nom::error::context(#c, #expr) }
+            }
+            ParserExpr::Count(expr, n, ty) => {
+                let ty_ = ty.elided();
+                quote! { nom_derive::collections::count::<#ty_, _, _, _>(#expr, #n as usize) }
             }
             ParserExpr::DbgDmp(expr, i) => {
                 let ident = format!("{}", i);
                 quote! { nom::error::dbg_dmp(#expr, #ident) }
             }
+            ParserExpr::Encoded(len, enc) => {
+                let bytes = match len {
+                    EncodedLength::Bytes(n) => {
+                        quote! { nom::bytes::streaming::take((#n) as usize) }
+                    }
+                    EncodedLength::Units(n) => {
+                        quote! { nom::bytes::streaming::take(((#n) as usize) * #enc.unit_size()) }
+                    }
+                    EncodedLength::NulTerminated => {
+                        quote! { nom_derive::strings::nul_terminated_units(#enc.unit_size()) }
+                    }
+                    EncodedLength::Prefix(expr, _) => {
+                        quote! { nom::multi::length_data(#expr) }
+                    }
+                };
+                quote! { nom::combinator::map_res(#bytes, |__b| #enc.decode(nom::AsBytes::as_bytes(&__b))) }
+            }
+            ParserExpr::Flags(expr, policy, ty) => match policy {
+                FlagsPolicy::Strict => quote! {
+                    nom::combinator::map_opt(#expr, <#ty as nom_derive::flags::Flags>::from_bits)
+                },
+                FlagsPolicy::Truncate => quote! {
+                    nom::combinator::map(#expr, <#ty as nom_derive::flags::Flags>::from_bits_truncate)
+                },
+                FlagsPolicy::Retain => quote! {
+                    nom::combinator::map(#expr, <#ty as nom_derive::flags::Flags>::from_bits_retain)
+                },
+            },
+            ParserExpr::Indexed(expr) => {
+                quote! { nom_derive::collections::indexed(#expr) }
+            }
             ParserExpr::Into(expr) => {
                 quote! { nom::combinator::into(#expr) }
             }
-            ParserExpr::LengthCount(expr, n) => {
-                quote! { nom::multi::length_count(#n, #expr) }
+            ParserExpr::LengthCount(expr, n, ty) => {
+                let ty_ = ty.elided();
+                quote! { nom_derive::collections::length_count::<#ty_, _, _, _, _, _>(#n, #expr) }
+            }
+            ParserExpr::Peek(expr) => {
+                quote! { nom::combinator::peek(#expr) }
             }
             ParserExpr::Map(expr, m) => {
                 quote! { nom::combinator::map(#expr, #m) }
@@ -163,10 +380,47 @@ impl ToTokens for ParserExpr {
             }
             ParserExpr::PhantomData => {
                 quote! {
-                    { |__i__| Ok((__i__, PhantomData)) }
+                    { |__i__| Ok((__i__, core::marker::PhantomData)) }
                 }
             }
             ParserExpr::Raw(s) => s.to_token_stream(),
+            ParserExpr::RepeatUntil(expr, t, m) => {
+                let term = match t {
+                    RepeatTerminator::Predicate(pred, ty) => {
+                        quote! { nom::combinator::verify::<_, #ty, _, _, _>(#expr, #pred) }
+                    }
+                    RepeatTerminator::Tag(s) => quote! { nom::bytes::streaming::tag(&#s[..]) },
+                };
+                let term = if *m == TerminatorMode::Keep {
+                    quote! { nom::combinator::peek(#term) }
+                } else {
+                    term
+                };
+                let f = if *m == TerminatorMode::Include {
+                    quote! { |(mut __v, __t)| { __v.push(__t); __v } }
+                } else {
+                    quote! { |(__v, _)| __v }
+                };
+                quote! { nom::combinator::map(nom::multi::many_till(#expr, #term), #f) }
+            }
+            ParserExpr::Rest => {
+                quote! { nom::combinator::map(nom::combinator::rest, core::convert::Into::into) }
+            }
+            ParserExpr::SerializeWith(expr, _) => expr.to_token_stream(),
+            ParserExpr::Size(expr, n, strict) => {
+                quote! { nom_derive::sized((#n) as usize, #strict, #expr) }
+            }
+            ParserExpr::Str(format, ty) => match format {
+                StringFormat::NulTerminated => {
+                    quote! { nom_derive::strings::nul_terminated::<#ty, _> }
+                }
+                StringFormat::Padded(width, pad, trim) => {
+                    quote! { nom_derive::strings::padded::<#ty, _>(#width as usize, #pad, #trim) }
+                }
+                StringFormat::LengthPrefixed(expr, _) => {
+                    quote! { nom_derive::strings::length_prefixed::<#ty, _, _>(#expr) }
+                }
+            },
             ParserExpr::Tag(s) => {
                 quote! { nom::bytes::streaming::tag(&#s[..]) }
             }
@@ -183,14 +437,149 @@ impl ToTokens for ParserExpr {
                     nom::combinator::verify(#expr, |#i| { #v })
                 }
             }
+            ParserExpr::Wrapped(expr, _) => {
+                quote! { nom::combinator::map(#expr, |__v| __v.0) }
+            }
+        };
+        tokens.extend(ts);
+    }
+}
+
+/// Format of a string field
+#[derive(Clone, Debug)]
+pub enum StringFormat {
+    NulTerminated,
+    /// Width, pad byte, and trim policy
+    Padded(TokenStream, TokenStream, TokenStream),
+    /// Parser for the length prefix, and type of the length
+    LengthPrefixed(Box<ParserExpr>, TokenStream),
+}
+
+/// Length of an encoded string
+#[derive(Clone, Debug)]
+pub enum EncodedLength {
+    /// Length in bytes
+    Bytes(TokenStream),
+    /// Length in code units
+    Units(TokenStream),
+    /// Terminated by a NUL code unit
+    NulTerminated,
+    /// Parser for the length prefix (in bytes), and type of the length
+    Prefix(Box<ParserExpr>, TokenStream),
+}
+
+/// Encoding of a string field
+#[derive(Clone, Debug)]
+pub enum StringEncoding {
+    /// Encoding known at compile-time
+    Fixed(TokenStream),
+    /// UTF-16, using the endianness of the parser
+    Utf16,
+    /// UTF-16, using the endianness given by `SetEndian`
+    Utf16SetEndian,
+}
+
+impl ToTokens for StringEncoding {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let ts = match self {
+            StringEncoding::Fixed(ts) => ts.clone(),
+            StringEncoding::Utf16 => unreachable!("UTF-16 endianness not resolved"),
+            StringEncoding::Utf16SetEndian => quote! {
+                (if __endianness == nom::number::Endianness::Big {
+                    nom_derive::strings::Encoding::Utf16Be
+                } else {
+                    nom_derive::strings::Encoding::Utf16Le
+                })
+            },
         };
         tokens.extend(ts);
     }
 }
 
+/// What to do with unknown bits when building a `bitflags` type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagsPolicy {
+    /// Return an error
+    Strict,
+    /// Remove unknown bits
+    Truncate,
+    /// Keep unknown bits
+    Retain,
+}
+
+/// Terminator of a repetition: a predicate on elements (with the element type), or a tag
+#[derive(Clone, Debug)]
+pub enum RepeatTerminator {
+    Predicate(TokenStream, TokenStream),
+    Tag(TokenStream),
+}
+
+/// What to do with the terminator of a repetition
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminatorMode {
+    /// Consume the terminator, and add it to the elements (predicates only)
+    Include,
+    /// Consume the terminator, and discard it
+    Consume,
+    /// Do not consume the terminator
+    Keep,
+}
+
+/// Position of a bit field in its group of bytes
+#[derive(Clone, Debug)]
+pub struct BitsField {
+    /// Name of the variable holding the bytes of the group
+    pub data: Ident,
+    pub offset: usize,
+    pub width: usize,
+    /// Least significant bit first
+    pub lsb0: bool,
+    /// The field is a `bitflags` type, stored using its bits
+    pub flags: bool,
+}
+
+impl BitsField {
+    pub fn order_tokens(&self) -> TokenStream {
+        if self.lsb0 {
+            quote! { nom_derive::bits::BitOrder::Lsb0 }
+        } else {
+            quote! { nom_derive::bits::BitOrder::Msb0 }
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TypeItem(pub syn::Type);
 
+impl TypeItem {
+    /// Type tokens with all named lifetimes replaced by `'_`, so that a container type used in a
+    /// turbofish does not constrain the lifetime of the input
+    pub fn elided(&self) -> TokenStream {
+        fn elide(ts: TokenStream) -> TokenStream {
+            let mut out = Vec::new();
+            let mut iter = ts.into_iter().peekable();
+            while let Some(tt) = iter.next() {
+                match tt {
+                    TokenTree::Punct(ref p) if p.as_char() == '\'' => {
+                        if let Some(TokenTree::Ident(_)) = iter.peek() {
+                            let _ = iter.next();
+                        }
+                        out.extend(quote! { '_ });
+                    }
+                    TokenTree::Group(g) => {
+                        let mut ng = Group::new(g.delimiter(), elide(g.stream()));
+                        ng.set_span(g.span());
+                        out.push(TokenTree::Group(ng));
+                    }
+                    tt => out.push(tt),
+                }
+            }
+            out.into_iter().collect()
+        }
+        elide(self.0.to_token_stream())
+    }
+}
+
 impl ToTokens for TypeItem {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         self.0.to_tokens(tokens)
@@ -0,0 +1,595 @@
+use crate::endian::ParserEndianness;
+use crate::meta::attr::{MetaAttr, MetaAttrType};
+use crate::parsertree::{
+    BitsField, EncodedLength, ParserExpr, RepeatTerminator, StringEncoding, StringFormat,
+    TerminatorMode,
+};
+use crate::structs::{ComputedField, StructParser, StructParserTree};
+use proc_macro2::{Delimiter, Group, Ident, Spacing, Span, TokenStream, TokenTree};
+use syn::spanned::Spanned;
+use syn::{Error, Result};
+
+/// Generate the code serializing `value` (an expression of type `&T`) using the inverse
+/// operation of the parser expression
+///
+/// `fields` are the names of the fields bound (as references) by the serializer, used to evaluate
+/// user expressions. Expressions using `computed` fields (`LengthOf`, `CountOf`) are not checked,
+/// since the values of these fields are ignored.
+pub(crate) fn gen_expr_serializer(
+    expr: &ParserExpr,
+    value: &TokenStream,
+    fields: &[Ident],
+    computed: &[Ident],
+) -> Result<TokenStream> {
+    let ts = match expr {
+        // bit fields are written by group, see `gen_bits_group_serializer`
+        ParserExpr::Bits(_, _) => quote! {},
+        ParserExpr::CallParse(_) => {
+            quote! { nom_derive::Serialize::serialize(#value, __out)?; }
+        }
+        ParserExpr::CallParseBE(_) => {
+            quote! { nom_derive::Serialize::serialize_be(#value, __out)?; }
+        }
+        ParserExpr::CallParseLE(_) => {
+            quote! { nom_derive::Serialize::serialize_le(#value, __out)?; }
+        }
+        ParserExpr::Complete(expr)
+        | ParserExpr::Context(expr, _)
+        | ParserExpr::DbgDmp(expr, _)
+        | ParserExpr::Indexed(expr)
+        | ParserExpr::Verify(expr, _, _) => gen_expr_serializer(expr, value, fields, computed)?,
+        ParserExpr::Wrapped(expr, w) => {
+            gen_expr_serializer(expr, &quote! { &#w(*#value) }, fields, computed)?
+        }
+        ParserExpr::Cond(expr, c, _) => {
+            let item = gen_expr_serializer(expr, &quote! { __v }, fields, computed)?;
+            let check = if uses_fields(c, computed) || !only_uses_fields(c, fields) {
+                None
+            } else {
+                let c = field_values(c, fields);
+                Some(quote! {
+                    if (nom_derive::SerializeIter::item_count(#value) > 0) != (#c) {
+                        return Err(nom_derive::SerializeError::ValueOutOfRange);
+                    }
+                })
+            };
+            quote! {
+                #check
+                nom_derive::SerializeIter::try_for_each_item(#value, |__v| {
+                    #item
+                    Ok(())
+                })?;
+            }
+        }
+        ParserExpr::Count(expr, n, _) => {
+            let item = gen_expr_serializer(expr, &quote! { __v }, fields, computed)?;
+            let check = if uses_fields(n, computed) || !only_uses_fields(n, fields) {
+                None
+            } else {
+                let n = field_values(n, fields);
+                Some(quote! {
+                    if nom_derive::SerializeIter::item_count(#value) != (#n) as usize {
+                        return Err(nom_derive::SerializeError::ValueOutOfRange);
+                    }
+                })
+            };
+            quote! {
+                #check
+                nom_derive::SerializeIter::try_for_each_item(#value, |__v| {
+                    #item
+                    Ok(())
+                })?;
+            }
+        }
+        ParserExpr::RepeatUntil(expr, t, m) => {
+            let item = gen_expr_serializer(expr, &quote! { __v }, fields, computed)?;
+            let term = match (t, m) {
+                (RepeatTerminator::Tag(s), TerminatorMode::Consume) => {
+                    quote! { __out.write_bytes(&#s[..])?; }
+                }
+                (RepeatTerminator::Predicate(pred, _), TerminatorMode::Consume) => {
+                    return Err(Error::new(
+                        pred.span(),
+                        "Nom-derive: cannot serialize a consumed terminator (use Terminator=\"include\")",
+                    ));
+                }
+                // included in elements, or written by the following fields
+                _ => quote! {},
+            };
+            quote! {
+                for __v in #value {
+                    #item
+                }
+                #term
+            }
+        }
+        ParserExpr::LengthCount(expr, n, _) => {
+            let (ty, endian) = get_number_parser_type(n).ok_or_else(|| {
+                Error::new(
+                    n.span(),
+                    "Nom-derive: cannot serialize LengthCount with this length parser (use SerializeWith)",
+                )
+            })?;
+            let f = match endian {
+                ParserEndianness::BigEndian => quote! { serialize_be },
+                ParserEndianness::LittleEndian => quote! { serialize_le },
+                _ => quote! { serialize },
+            };
+            let item = gen_expr_serializer(expr, &quote! { __v }, fields, computed)?;
+            quote! {
+                let __n = <#ty as core::convert::TryFrom<usize>>::try_from(
+                    nom_derive::SerializeIter::item_count(#value),
+                )
+                .map_err(|_| nom_derive::SerializeError::ValueOutOfRange)?;
+                nom_derive::Serialize::#f(&__n, __out)?;
+                nom_derive::SerializeIter::try_for_each_item(#value, |__v| {
+                    #item
+                    Ok(())
+                })?;
+            }
+        }
+        ParserExpr::AtOffset(_, offset, _, _) => {
+            return Err(Error::new(
+                offset.span(),
+                "Nom-derive: cannot serialize a field using AtOffset (use SerializeWith)",
+            ));
+        }
+        ParserExpr::Encoded(len, enc) => {
+            if !matches!(enc, StringEncoding::Fixed(_)) {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "Nom-derive: cannot serialize a UTF-16 string using SetEndian (use SerializeWith)",
+                ));
+            }
+            let (prefix, suffix) = match len {
+                EncodedLength::Bytes(_) | EncodedLength::Units(_) => (quote! {}, quote! {}),
+                EncodedLength::NulTerminated => (
+                    quote! {},
+                    quote! {
+                        for _ in 0..#enc.unit_size() {
+                            __out.write_bytes(&[0])?;
+                        }
+                    },
+                ),
+                EncodedLength::Prefix(expr, ty) => {
+                    let len = gen_expr_serializer(expr, &quote! { &__len }, fields, computed)?;
+                    let prefix = quote! {
+                        let __len = <#ty as core::convert::TryFrom<usize>>::try_from(__bytes.len())
+                            .map_err(|_| nom_derive::SerializeError::ValueOutOfRange)?;
+                        #len
+                    };
+                    (prefix, quote! {})
+                }
+            };
+            quote! {
+                let __bytes = #enc.encode(#value)?;
+                #prefix
+                __out.write_bytes(&__bytes)?;
+                #suffix
+            }
+        }
+        ParserExpr::Flags(expr, _, _) => {
+            let bits = gen_expr_serializer(expr, &quote! { &__bits }, fields, computed)?;
+            quote! {
+                {
+                    let __bits = nom_derive::flags::Flags::bits(#value);
+                    #bits
+                }
+            }
+        }
+        ParserExpr::Into(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Nom-derive: cannot serialize a field using Into (use SerializeWith)",
+            ));
+        }
+        ParserExpr::Map(_, m) => {
+            return Err(Error::new(
+                m.span(),
+                "Nom-derive: cannot serialize a field using Map (use SerializeWith)",
+            ));
+        }
+        ParserExpr::Raw(s) => {
+            return Err(Error::new(
+                s.span(),
+                "Nom-derive: cannot serialize a field using a custom parser (use SerializeWith)",
+            ));
+        }
+        // peeked bytes are written by the following fields
+        ParserExpr::Nop | ParserExpr::Peek(_) | ParserExpr::PhantomData | ParserExpr::Value(_) => {
+            quote! {}
+        }
+        ParserExpr::SerializeWith(_, f) => {
+            quote! { #f(#value, __out)?; }
+        }
+        ParserExpr::Size(expr, n, strict) => {
+            let ser = gen_expr_serializer(expr, value, fields, computed)?;
+            if uses_fields(n, computed) {
+                // the size is computed from the value, so it always matches
+                return Ok(ser);
+            }
+            let n = field_values(n, fields);
+            let check = if *strict {
+                quote! { __len != __size }
+            } else {
+                quote! { __len > __size }
+            };
+            // skipped bytes are written as zeros
+            quote! {
+                {
+                    let __size = nom_derive::ToSize::to_size(&(#n));
+                    let __start = __out.position();
+                    #ser
+                    let __len = __out.position() - __start;
+                    if #check {
+                        return Err(nom_derive::SerializeError::ValueOutOfRange);
+                    }
+                    __out.write_fill(0, __size - __len)?;
+                }
+            }
+        }
+        ParserExpr::Str(format, _) => match format {
+            StringFormat::NulTerminated => {
+                quote! { nom_derive::strings::write_nul_terminated(#value, __out)?; }
+            }
+            StringFormat::Padded(width, pad, _) => {
+                quote! { nom_derive::strings::write_padded(#value, #width as usize, #pad, __out)?; }
+            }
+            StringFormat::LengthPrefixed(expr, ty) => {
+                let len = gen_expr_serializer(expr, &quote! { &__len }, fields, computed)?;
+                quote! {
+                    let __bytes = nom_derive::strings::StringType::string_bytes(#value);
+                    let __len = <#ty as core::convert::TryFrom<usize>>::try_from(__bytes.len())
+                        .map_err(|_| nom_derive::SerializeError::ValueOutOfRange)?;
+                    #len
+                    __out.write_bytes(__bytes)?;
+                }
+            }
+        },
+        ParserExpr::Tag(s) => {
+            quote! { __out.write_bytes(&#s[..])?; }
+        }
+        ParserExpr::Rest | ParserExpr::Take(_) => {
+            quote! { __out.write_bytes(#value)?; }
+        }
+    };
+    Ok(ts)
+}
+
+/// Generate the code writing the top-level `Tag` and `Magic` values
+pub(crate) fn gen_magic_serializer(
+    magic: &[(ParserExpr, TokenStream)],
+    endianness: ParserEndianness,
+) -> Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    for (expr, value) in magic {
+        tokens.extend(gen_expr_serializer(
+            &expr.with_endianness(endianness),
+            value,
+            &[],
+            &[],
+        )?);
+    }
+    Ok(tokens)
+}
+
+/// Generate the serialization code for all fields of a struct or variant
+///
+/// Fields must be bound (as references) to variables with the same names
+pub(crate) fn gen_fields_serializer(
+    parsers: &[StructParser],
+    endianness: ParserEndianness,
+) -> Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    let fields: Vec<_> = parsers.iter().map(field_ident).collect();
+    let fields = &fields[..];
+    let computed: Vec<_> = parsers
+        .iter()
+        .filter(|sp| sp.computed.is_some())
+        .map(field_ident)
+        .collect();
+    let computed = &computed[..];
+    for (idx, sp) in parsers.iter().enumerate() {
+        let id = &fields[idx];
+        let item = sp.item.with_endianness(endianness);
+        let ser = match &sp.computed {
+            Some(c) if c.attr_type == MetaAttrType::CountOf => {
+                // write the number of elements instead of the field value
+                let (ty, target) = (&c.ty, &c.target);
+                let ser = gen_expr_serializer(&item.expr, &quote! { &__v }, fields, computed)?;
+                quote! {
+                    {
+                        let __v = <#ty as core::convert::TryFrom<usize>>::try_from(#target.len())
+                            .map_err(|_| nom_derive::SerializeError::ValueOutOfRange)?;
+                        #ser
+                    }
+                }
+            }
+            Some(c) => {
+                // write the field value as a placeholder, it will be patched later
+                let pos = format_ident!("__pos_{}", c.field);
+                let ser = gen_expr_serializer(&item.expr, &quote! { #id }, fields, computed)?;
+                quote! {
+                    let #pos = __out.position();
+                    #ser
+                }
+            }
+            None => gen_expr_serializer(&item.expr, &quote! { #id }, fields, computed)?,
+        };
+        let is_length_target = parsers.iter().any(|other| {
+            matches!(&other.computed, Some(c) if c.attr_type == MetaAttrType::LengthOf && c.target_index == idx)
+        });
+        let (start, end) = if is_length_target {
+            let start = format_ident!("__start_{}", id);
+            let end = format_ident!("__end_{}", id);
+            (
+                Some(quote! { let #start = __out.position(); }),
+                Some(quote! { let #end = __out.position(); }),
+            )
+        } else {
+            (None, None)
+        };
+        let (pre, post) = (&sp.ser_pre, &sp.ser_post);
+        tokens.extend(quote! { #pre #start #ser #end #post });
+        // patch length fields, once both the field and its target are serialized
+        for (other_idx, other) in parsers.iter().enumerate() {
+            match &other.computed {
+                Some(c)
+                    if c.attr_type == MetaAttrType::LengthOf
+                        && idx == c.target_index.max(other_idx) =>
+                {
+                    tokens.extend(gen_length_patch(other, c, endianness)?);
+                }
+                _ => (),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Generate the code writing the length of the target to the position of the length field
+fn gen_length_patch(
+    sp: &StructParser,
+    c: &ComputedField,
+    endianness: ParserEndianness,
+) -> Result<TokenStream> {
+    let ty = &c.ty;
+    let pos = format_ident!("__pos_{}", c.field);
+    let start = format_ident!("__start_{}", c.target);
+    let end = format_ident!("__end_{}", c.target);
+    let item = sp.item.with_endianness(endianness);
+    let ser = gen_expr_serializer(&item.expr, &quote! { &__v }, &[], &[])?;
+    let tokens = quote! {
+        {
+            let __v = <#ty as core::convert::TryFrom<usize>>::try_from(#end - #start)
+                .map_err(|_| nom_derive::SerializeError::ValueOutOfRange)?;
+            let mut __buf = [0u8; 16];
+            let mut __tmp = nom_derive::SliceOutput::new(&mut __buf);
+            {
+                let __out = &mut __tmp;
+                #ser
+            }
+            __out.patch(#pos, __tmp.written())?;
+        }
+    };
+    Ok(tokens)
+}
+
+/// Generate the code writing all bit fields of a group
+pub(crate) fn gen_bits_group_serializer(
+    len_bytes: usize,
+    fields: &[(Ident, BitsField)],
+) -> TokenStream {
+    let inserts = fields.iter().map(|(ident, b)| {
+        let (offset, width) = (b.offset, b.width);
+        let order = b.order_tokens();
+        let value = if b.flags {
+            quote! { &nom_derive::flags::Flags::bits(#ident) }
+        } else {
+            quote! { #ident }
+        };
+        quote! {
+            nom_derive::bits::insert_bits(
+                &mut __bits,
+                #offset,
+                #width,
+                nom_derive::bits::BitField::to_bits(#value),
+                #order,
+            );
+        }
+    });
+    quote! {
+        {
+            let mut __bits = [0u8; #len_bytes];
+            #(#inserts)*
+            __out.write_bytes(&__bits)?;
+        }
+    }
+}
+
+/// Get the identifier of the variable bound to a field
+fn field_ident(sp: &StructParser) -> Ident {
+    if sp.name.starts_with("r#") {
+        // raw identifier: r#type
+        syn::Ident::new_raw(&sp.name[2..], Span::call_site())
+    } else {
+        syn::Ident::new(&sp.name, Span::call_site())
+    }
+}
+
+/// Generate the pattern binding all fields of a struct or variant (by reference)
+pub(crate) fn gen_fields_binding(path: &TokenStream, tree: &StructParserTree) -> TokenStream {
+    let idents = tree.parsers.iter().map(field_ident);
+    match (tree.empty, tree.unnamed) {
+        (true, _) => quote! { #path },
+        (_, true) => quote! { #path ( #(#idents),* ) },
+        (_, false) => quote! { #path { #(#idents),* } },
+    }
+}
+
+/// Get the type and endianness of a `nom` number parser, for ex. `be_u16`
+fn get_number_parser_type(ts: &TokenStream) -> Option<(Ident, ParserEndianness)> {
+    let path = syn::parse2::<syn::Path>(ts.clone()).ok()?;
+    let s = path.segments.last()?.ident.to_string();
+    let (endian, ty) = if let Some(ty) = s.strip_prefix("be_") {
+        (ParserEndianness::BigEndian, ty)
+    } else if let Some(ty) = s.strip_prefix("le_") {
+        (ParserEndianness::LittleEndian, ty)
+    } else {
+        (ParserEndianness::Unspecified, s.as_ref())
+    };
+    match ty {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+            Some((Ident::new(ty, ts.span()), endian))
+        }
+        _ => None,
+    }
+}
+
+fn quote_ser_align(align: &TokenStream) -> TokenStream {
+    quote! {
+        {
+            let offset = __out.position() - __start;
+            let align = #align as usize;
+            let align = ((align - (offset % align)) % align);
+            __out.write_fill(0, align)?;
+        }
+    }
+}
+
+fn quote_ser_skip(skip: &TokenStream) -> TokenStream {
+    quote! {
+        __out.write_fill(0, #skip as usize)?;
+    }
+}
+
+/// Replace field identifiers by their (dereferenced) values in user expression
+///
+/// Serializers bind fields as references, while parsers bind them as values. Identifiers used as
+/// receivers of a method call or a field access are left unchanged (auto-deref applies).
+pub(crate) fn field_values(ts: &TokenStream, fields: &[Ident]) -> TokenStream {
+    let tokens: Vec<_> = ts.clone().into_iter().collect();
+    let mut out = TokenStream::new();
+    for (idx, tt) in tokens.iter().enumerate() {
+        match tt {
+            TokenTree::Ident(id) if fields.contains(id) => {
+                let prev_is_path = idx > 0
+                    && matches!(&tokens[idx - 1], TokenTree::Punct(p) if p.as_char() == '.' || p.as_char() == ':');
+                let next_is_access = matches!(
+                    tokens.get(idx + 1),
+                    Some(TokenTree::Punct(p)) if p.as_char() == '.' || p.as_char() == ':'
+                        // macro call, but not `!=`
+                        || (p.as_char() == '!' && p.spacing() == Spacing::Alone)
+                ) || matches!(
+                    tokens.get(idx + 1),
+                    Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis
+                );
+                if prev_is_path || next_is_access {
+                    out.extend(Some(tt.clone()));
+                } else {
+                    out.extend(quote! { (*#id) });
+                }
+            }
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), field_values(&g.stream(), fields));
+                group.set_span(g.span());
+                out.extend(Some(TokenTree::Group(group)));
+            }
+            _ => out.extend(Some(tt.clone())),
+        }
+    }
+    out
+}
+
+/// Test if user expression uses any of the fields
+fn uses_fields(ts: &TokenStream, fields: &[Ident]) -> bool {
+    ts.clone().into_iter().any(|tt| match tt {
+        TokenTree::Ident(id) => fields.contains(&id),
+        TokenTree::Group(g) => uses_fields(&g.stream(), fields),
+        _ => false,
+    })
+}
+
+/// Test if all the variables of user expression are fields
+///
+/// Other variables (`ExtraArgs`, selector, or variables defined in `PreExec`) are not available
+/// when serializing. Method, function and path names, and the type of casts, are ignored.
+fn only_uses_fields(ts: &TokenStream, fields: &[Ident]) -> bool {
+    const KNOWN: &[&str] = &[
+        "as", "true", "false", "if", "else", "match", "u8", "u16", "u32", "u64", "u128", "usize",
+        "i8", "i16", "i32", "i64", "i128", "isize", "bool", "char", "f32", "f64",
+    ];
+    let tokens: Vec<_> = ts.clone().into_iter().collect();
+    tokens.iter().enumerate().all(|(idx, tt)| match tt {
+        TokenTree::Ident(id) => {
+            let prev_is_path = idx > 0
+                && matches!(&tokens[idx - 1], TokenTree::Punct(p) if p.as_char() == '.' || p.as_char() == ':');
+            let prev_is_cast = idx > 0 && matches!(&tokens[idx - 1], TokenTree::Ident(i) if i == "as");
+            let next_is_call = matches!(
+                tokens.get(idx + 1),
+                Some(TokenTree::Punct(p)) if p.as_char() == ':'
+                    || (p.as_char() == '!' && p.spacing() == Spacing::Alone)
+            ) || matches!(
+                tokens.get(idx + 1),
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis
+            );
+            let name = id.to_string();
+            fields.contains(id)
+                || prev_is_path
+                || prev_is_cast
+                || next_is_call
+                || KNOWN.contains(&name.as_str())
+                // constants
+                || name.starts_with(char::is_uppercase)
+        }
+        TokenTree::Group(g) => only_uses_fields(&g.stream(), fields),
+        _ => true,
+    })
+}
+
+/// Get the serialization code for the padding attributes of a field
+pub(crate) fn get_serialize_pre_post(
+    meta_list: &[MetaAttr],
+    fields: &[Ident],
+) -> Result<(Option<TokenStream>, Option<TokenStream>)> {
+    let mut tk_pre = TokenStream::new();
+    let mut tk_post = TokenStream::new();
+    for m in meta_list {
+        match m.attr_type {
+            MetaAttrType::AlignAfter => {
+                let align = field_values(m.arg().unwrap(), fields);
+                tk_post.extend(quote_ser_align(&align));
+            }
+            MetaAttrType::AlignBefore => {
+                let align = field_values(m.arg().unwrap(), fields);
+                tk_pre.extend(quote_ser_align(&align));
+            }
+            MetaAttrType::SkipAfter => {
+                let skip = field_values(m.arg().unwrap(), fields);
+                tk_post.extend(quote_ser_skip(&skip));
+            }
+            MetaAttrType::SkipBefore => {
+                let skip = field_values(m.arg().unwrap(), fields);
+                tk_pre.extend(quote_ser_skip(&skip));
+            }
+            MetaAttrType::Move | MetaAttrType::MoveAbs => {
+                return Err(Error::new(
+                    m.span(),
+                    format!("Nom-derive: cannot serialize a field using {}", m.attr_type),
+                ));
+            }
+            _ => (),
+        }
+    }
+    let pre = if tk_pre.is_empty() {
+        None
+    } else {
+        Some(tk_pre)
+    };
+    let post = if tk_post.is_empty() {
+        None
+    } else {
+        Some(tk_post)
+    };
+    Ok((pre, post))
+}
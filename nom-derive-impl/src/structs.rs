@@ -3,8 +3,11 @@ use crate::endian::*;
 use crate::meta;
 use crate::meta::attr::{MetaAttr, MetaAttrType};
 use crate::parsertree::*;
+use crate::serialize::{gen_bits_group_serializer, get_serialize_pre_post};
 use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::*;
 
@@ -14,6 +17,20 @@ pub(crate) struct StructParser {
     pub item: ParserTreeItem,
     pub pre_exec: Option<TokenStream>,
     pub post_exec: Option<TokenStream>,
+    pub ser_pre: Option<TokenStream>,
+    pub ser_post: Option<TokenStream>,
+    pub computed: Option<ComputedField>,
+}
+
+/// A field whose value is computed from another field (the target) when serializing
+#[derive(Clone, Debug)]
+pub(crate) struct ComputedField {
+    /// `LengthOf` (length in bytes) or `CountOf` (number of elements)
+    pub attr_type: MetaAttrType,
+    pub field: Ident,
+    pub ty: Type,
+    pub target: Ident,
+    pub target_index: usize,
 }
 
 impl StructParser {
@@ -28,6 +45,9 @@ impl StructParser {
             item,
             pre_exec,
             post_exec,
+            ser_pre: None,
+            ser_post: None,
+            computed: None,
         }
     }
 }
@@ -40,11 +60,15 @@ pub(crate) struct StructParserTree {
 }
 
 fn get_type_parser(ty: &Type, meta_list: &[MetaAttr], config: &Config) -> Result<ParserExpr> {
-    // special case: PhantomData
-    let ident = get_type_first_ident(ty)?;
-    if ident == "PhantomData" {
+    // special case: PhantomData (tuples have no path, and use the `Parse` trait)
+    if !matches!(ty, Type::Tuple(_)) && get_type_first_ident(ty)? == "PhantomData" {
         return Ok(ParserExpr::PhantomData);
     }
+    get_endian_parser(ty, meta_list, config)
+}
+
+/// Call the parser of the type, using the endianness of the field
+fn get_endian_parser(ty: &Type, meta_list: &[MetaAttr], config: &Config) -> Result<ParserExpr> {
     let endian = get_local_endianness(ty.span(), meta_list, config)?;
     match endian {
         ParserEndianness::BigEndian => Ok(ParserExpr::CallParseBE(TypeItem(ty.clone()))),
@@ -65,22 +89,30 @@ fn get_type_parser(ty: &Type, meta_list: &[MetaAttr], config: &Config) -> Result
     }
 }
 
+/// Get the last segment of a type path, if the type can be identified by its name
+///
+/// Qualified paths (for ex. `std::vec::Vec<T>`) are identified by their last segment only if they
+/// are in the standard library (`std`, `core` or `alloc`), so that user types with the same name
+/// (for ex. `mymod::Option<T>`) are not mistaken for standard types.
+fn get_std_path_segment(path: &Path) -> Option<&PathSegment> {
+    let first = path.segments.first()?;
+    if path.segments.len() == 1 || ["std", "core", "alloc"].iter().any(|s| first.ident == s) {
+        path.segments.last()
+    } else {
+        None
+    }
+}
+
 fn get_item_subtype_parser(ty: &Type, expected: &str, attr: &str) -> Result<TokenStream> {
     if let Type::Path(typepath) = ty {
-        let path = &typepath.path;
-        if path.segments.len() != 1 {
-            return Err(Error::new(
-                ty.span(),
-                "Nom-derive: multiple segments in type path are not supported",
-            ));
-        }
-        let segment = path.segments.last().expect("empty segments list");
-        let ident_s = segment.ident.to_string();
-        if ident_s == expected {
-            // segment.arguments should contain the values, wrapped in AngleBracketed
-            if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                return Ok(args.args.to_token_stream());
+        match get_std_path_segment(&typepath.path) {
+            Some(segment) if segment.ident == expected => {
+                // segment.arguments should contain the values, wrapped in AngleBracketed
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    return Ok(args.args.to_token_stream());
+                }
             }
+            _ => (),
         }
     }
     Err(Error::new(
@@ -92,20 +124,58 @@ fn get_item_subtype_parser(ty: &Type, expected: &str, attr: &str) -> Result<Toke
     ))
 }
 
+/// Get the type of the items of a container (implementing `FromParsedIter`)
+///
+/// If the `Elem` attribute is present, this is its value. For containers of slices (for ex.
+/// `Box<[T]>`), this is the type of the elements of the slice. Otherwise, this is the `Item`
+/// associated type: the generic argument of the type cannot be used, since it may not be the type
+/// of items (for ex. `SmallVec<[T; 4]>`, a type alias, or a user type).
+fn get_container_item_type(ty: &Type, meta: &MetaAttr, meta_list: &[MetaAttr]) -> Result<Type> {
+    if let Some(elem) = get_elem_type(meta_list)? {
+        return Ok(elem);
+    }
+    if get_type_bits_width(ty).is_some() {
+        return Err(Error::new(
+            ty.span(),
+            format!(
+                "Nom-derive: unexpected type for {} attribute. Expected a collection type (implementing FromParsedIter)",
+                meta.attr_type
+            ),
+        ));
+    }
+    if let Type::Path(typepath) = ty {
+        let segment = typepath.path.segments.last().expect("empty segments list");
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            // slices cannot be items, so this is a container of a slice
+            if let (1, Some(GenericArgument::Type(Type::Slice(s)))) =
+                (args.args.len(), args.args.first())
+            {
+                return Ok(s.elem.as_ref().clone());
+            }
+        }
+    }
+    Ok(parse_quote! { <#ty as nom_derive::FromParsedIter>::Item })
+}
+
+/// Get the type of items from the `Elem` attribute, if present
+fn get_elem_type(meta_list: &[MetaAttr]) -> Result<Option<Type>> {
+    match meta_list.iter().find(|m| m.is_type(MetaAttrType::Elem)) {
+        Some(m) => Ok(Some(syn::parse2::<Type>(m.arg().unwrap().clone())?)),
+        None => Ok(None),
+    }
+}
+
 pub(crate) fn get_type_first_ident(ty: &Type) -> Result<String> {
     match ty {
-        Type::Path(typepath) => {
-            let path = &typepath.path;
-            if path.segments.len() != 1 {
-                return Err(Error::new(
-                    ty.span(),
-                    "Nom-derive: multiple segments in type path are not supported",
-                ));
+        Type::Path(typepath) => match get_std_path_segment(&typepath.path) {
+            Some(segment) => Ok(segment.ident.to_string()),
+            // user types with a qualified path are identified by their full path
+            None => {
+                let path = &typepath.path;
+                let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+                Ok(segments.join("::"))
             }
-            let segment = path.segments.last().expect("empty segments list");
-            let ident_s = segment.ident.to_string();
-            Ok(ident_s)
-        }
+        },
         Type::Array(typearray) => get_type_first_ident(&typearray.elem),
         _ => Err(Error::new(
             ty.span(),
@@ -114,16 +184,159 @@ pub(crate) fn get_type_first_ident(ty: &Type) -> Result<String> {
     }
 }
 
+fn get_terminator_mode(meta: &MetaAttr, terminator: &RepeatTerminator) -> Result<TerminatorMode> {
+    match meta.arg().map(|ts| ts.to_string()).as_deref() {
+        Some("include") => match terminator {
+            RepeatTerminator::Predicate(_, _) => Ok(TerminatorMode::Include),
+            RepeatTerminator::Tag(_) => Err(Error::new(
+                meta.span(),
+                "Nom-derive: a tag terminator cannot be included in elements",
+            )),
+        },
+        Some("consume") => Ok(TerminatorMode::Consume),
+        Some("keep") => Ok(TerminatorMode::Keep),
+        _ => Err(Error::new(
+            meta.span(),
+            "Nom-derive: Terminator must be \"include\", \"consume\" or \"keep\"",
+        )),
+    }
+}
+
+fn get_trim_policy(meta: &MetaAttr) -> Result<TokenStream> {
+    match meta.arg().map(|ts| ts.to_string()).as_deref() {
+        Some("keep") => Ok(quote! { nom_derive::strings::Trim::Keep }),
+        Some("padding") => Ok(quote! { nom_derive::strings::Trim::Padding }),
+        Some("nul") => Ok(quote! { nom_derive::strings::Trim::Nul }),
+        _ => Err(Error::new(
+            meta.span(),
+            "Nom-derive: Trim must be \"keep\", \"padding\" or \"nul\"",
+        )),
+    }
+}
+
+/// Get the argument of an attribute as an expression (parsing the content if it is a string)
+fn get_expr_arg(meta: &MetaAttr) -> Result<TokenStream> {
+    let ts = meta.arg().unwrap();
+    if let Some(TokenTree::Literal(_)) = ts.clone().into_iter().next() {
+        let expr = syn::parse2::<Expr>(ts.clone())?;
+        return Ok(expr.to_token_stream());
+    }
+    Ok(ts.clone())
+}
+
+fn get_string_encoding(
+    meta: &MetaAttr,
+    meta_list: &[MetaAttr],
+    config: &Config,
+) -> Result<StringEncoding> {
+    let arg = meta.arg().map(|ts| ts.to_string().replace(' ', ""));
+    let enc = match arg.as_deref() {
+        Some("utf16le") => quote! { nom_derive::strings::Encoding::Utf16Le },
+        Some("utf16be") => quote! { nom_derive::strings::Encoding::Utf16Be },
+        Some("utf16") => {
+            return match get_local_endianness(meta.span(), meta_list, config)? {
+                ParserEndianness::BigEndian => Ok(StringEncoding::Fixed(
+                    quote! { nom_derive::strings::Encoding::Utf16Be },
+                )),
+                ParserEndianness::LittleEndian => Ok(StringEncoding::Fixed(
+                    quote! { nom_derive::strings::Encoding::Utf16Le },
+                )),
+                ParserEndianness::SetEndian => Ok(StringEncoding::Utf16SetEndian),
+                ParserEndianness::Unspecified => Ok(StringEncoding::Utf16),
+            };
+        }
+        Some("latin1") => quote! { nom_derive::strings::Encoding::Latin1 },
+        Some("windows-1252") | Some("cp1252") => {
+            quote! { nom_derive::strings::Encoding::Windows1252 }
+        }
+        _ => {
+            return Err(Error::new(
+                meta.span(),
+                "Nom-derive: Encoding must be \"utf16\", \"utf16le\", \"utf16be\", \"latin1\" or \"windows-1252\"",
+            ))
+        }
+    };
+    Ok(StringEncoding::Fixed(enc))
+}
+
+fn get_encoded_parser(ty: &Type, meta_list: &[MetaAttr], config: &Config) -> Result<ParserExpr> {
+    let meta = meta_list
+        .iter()
+        .find(|m| m.is_type(MetaAttrType::Encoding))
+        .expect("no Encoding attribute");
+    let enc = get_string_encoding(meta, meta_list, config)?;
+    let len = meta_list.iter().find_map(|m| match m.attr_type {
+        MetaAttrType::Take => Some(get_expr_arg(m).map(EncodedLength::Bytes)),
+        MetaAttrType::Count => Some(get_expr_arg(m).map(EncodedLength::Units)),
+        MetaAttrType::NulTerminated => Some(Ok(EncodedLength::NulTerminated)),
+        MetaAttrType::LengthPrefix => Some(syn::parse2::<Type>(m.arg().unwrap().clone()).and_then(
+            |len_ty| {
+                let expr = get_type_parser(&len_ty, meta_list, config)?;
+                Ok(EncodedLength::Prefix(
+                    Box::new(expr),
+                    len_ty.to_token_stream(),
+                ))
+            },
+        )),
+        _ => None,
+    });
+    let len = match len {
+        Some(len) => len?,
+        None => return Err(Error::new(
+            meta.span(),
+            "Nom-derive: Encoding requires a length (Take, Count, NulTerminated or LengthPrefix)",
+        )),
+    };
+    if get_type_first_ident(ty)? != "String" {
+        return Err(Error::new(
+            ty.span(),
+            "Nom-derive: Encoding can only be used with String fields",
+        ));
+    }
+    Ok(ParserExpr::Encoded(len, enc))
+}
+
+fn get_flags_policy(meta: &MetaAttr) -> Result<FlagsPolicy> {
+    match meta.arg().map(|ts| ts.to_string()).as_deref() {
+        Some("strict") => Ok(FlagsPolicy::Strict),
+        Some("truncate") | None => Ok(FlagsPolicy::Truncate),
+        Some("retain") => Ok(FlagsPolicy::Retain),
+        _ => Err(Error::new(
+            meta.span(),
+            "Nom-derive: Flags must be \"strict\", \"truncate\" or \"retain\"",
+        )),
+    }
+}
+
+fn get_varint_wrapper(meta: &MetaAttr) -> Result<TokenStream> {
+    let wrapper = match meta.arg().map(|ts| ts.to_string()).as_deref() {
+        Some("leb128") => quote! { nom_derive::Leb128 },
+        Some("sleb128") => quote! { nom_derive::Sleb128 },
+        Some("zigzag") => quote! { nom_derive::ZigZag },
+        Some("quic") => quote! { nom_derive::QuicVarInt },
+        Some("ber") => quote! { nom_derive::BerLength },
+        _ => return Err(Error::new(
+            meta.span(),
+            "Nom-derive: Varint must be \"leb128\", \"sleb128\", \"zigzag\", \"quic\" or \"ber\"",
+        )),
+    };
+    Ok(wrapper)
+}
+
 fn get_type_default(ty: &Type) -> Result<ParserExpr> {
-    let ident_s = get_type_first_ident(ty)?;
+    // tuples have no path, and use the `Default` trait
+    let ident_s = match ty {
+        Type::Tuple(_) => String::new(),
+        _ => get_type_first_ident(ty)?,
+    };
     let default = match ident_s.as_ref() {
         // "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
         //     "0".to_string()
         // }
         // "f32" | "f64" => "0.0".to_string(),
         "Option" => quote! { None },
-        "PhantomData" => quote! { PhantomData },
-        "Vec" => quote! { Vec::new() },
+        "PhantomData" => quote! { core::marker::PhantomData },
+        "Vec" => quote! { <#ty>::new() },
         _ => quote! { <#ty>::default() },
     };
     // ParserTree::Raw(format!("{{ |i| Ok((i, {})) }}", default))
@@ -150,6 +363,17 @@ fn get_parser(
         sub_meta_list = rem;
         // eprintln!("  meta {:?}", meta.attr_type);
         // eprintln!("  sub_meta_list: {:?}", sub_meta_list);
+        let encoded = matches!(
+            meta.attr_type,
+            MetaAttrType::Encoding
+                | MetaAttrType::Take
+                | MetaAttrType::Count
+                | MetaAttrType::NulTerminated
+                | MetaAttrType::LengthPrefix
+        );
+        if encoded && meta_list.iter().any(|m| m.is_type(MetaAttrType::Encoding)) {
+            return get_encoded_parser(ty, meta_list, config);
+        }
         match meta.attr_type {
             MetaAttrType::Tag => {
                 let s = meta.arg().unwrap();
@@ -176,10 +400,73 @@ fn get_parser(
             MetaAttrType::Ignore => {
                 return get_type_default(ty);
             }
+            MetaAttrType::Hex => {
+                return Ok(ParserExpr::Raw(
+                    quote! { nom_derive::text::hex::<#ty, _, _> },
+                ));
+            }
             MetaAttrType::Complete => {
                 let expr = get_parser(ident, ty, sub_meta_list, meta_list, config)?;
                 return Ok(expr.complete());
             }
+            MetaAttrType::NulTerminated => {
+                return Ok(ParserExpr::Str(
+                    StringFormat::NulTerminated,
+                    TypeItem(ty.clone()),
+                ));
+            }
+            MetaAttrType::Padded => {
+                let width = meta.arg().unwrap().clone();
+                let pad = match meta_list.iter().find(|m| m.is_type(MetaAttrType::PadByte)) {
+                    Some(m) => m.arg().unwrap().clone(),
+                    None => quote! { 0 },
+                };
+                let trim = match meta_list.iter().find(|m| m.is_type(MetaAttrType::Trim)) {
+                    Some(m) => get_trim_policy(m)?,
+                    None => quote! { nom_derive::strings::Trim::Padding },
+                };
+                let format = StringFormat::Padded(width, pad, trim);
+                return Ok(ParserExpr::Str(format, TypeItem(ty.clone())));
+            }
+            MetaAttrType::LengthPrefix => {
+                let len_ty = syn::parse2::<Type>(meta.arg().unwrap().clone())?;
+                let expr = get_type_parser(&len_ty, meta_list, config)?;
+                let format = StringFormat::LengthPrefixed(Box::new(expr), len_ty.to_token_stream());
+                return Ok(ParserExpr::Str(format, TypeItem(ty.clone())));
+            }
+            MetaAttrType::Varint => {
+                let wrapper = get_varint_wrapper(meta)?;
+                let wrapped_ty = syn::parse2::<Type>(quote! { #wrapper<#ty> })?;
+                let expr = ParserExpr::CallParse(TypeItem(wrapped_ty));
+                return Ok(ParserExpr::Wrapped(Box::new(expr), wrapper));
+            }
+            MetaAttrType::Uint24 | MetaAttrType::Int24 | MetaAttrType::Uint48 => {
+                let wrapper = match meta.attr_type {
+                    MetaAttrType::Uint24 => quote! { nom_derive::numbers::U24 },
+                    MetaAttrType::Int24 => quote! { nom_derive::numbers::I24 },
+                    _ => quote! { nom_derive::numbers::U48 },
+                };
+                let wrapped_ty = syn::parse2::<Type>(quote! { #wrapper<#ty> })?;
+                let expr = get_endian_parser(&wrapped_ty, meta_list, config)?;
+                return Ok(ParserExpr::Wrapped(Box::new(expr), wrapper));
+            }
+            MetaAttrType::Flags => {
+                let policy = get_flags_policy(meta)?;
+                let bits_ty: Type = parse_quote! { <#ty as nom_derive::flags::Flags>::Bits };
+                let expr = match &config.bits_field {
+                    Some(b) => ParserExpr::Bits(b.clone(), TypeItem(bits_ty)),
+                    None => get_endian_parser(&bits_ty, meta_list, config)?,
+                };
+                return Ok(ParserExpr::Flags(
+                    Box::new(expr),
+                    policy,
+                    TypeItem(ty.clone()),
+                ));
+            }
+            MetaAttrType::Peek => {
+                let expr = get_parser(ident, ty, sub_meta_list, meta_list, config)?;
+                return Ok(ParserExpr::Peek(Box::new(expr)));
+            }
             MetaAttrType::Debug => {
                 let expr = get_parser(ident, ty, sub_meta_list, meta_list, config)?;
                 let ident = match ident {
@@ -193,36 +480,92 @@ fn get_parser(
                 };
                 return Ok(ParserExpr::DbgDmp(Box::new(expr), ident.clone()));
             }
+            MetaAttrType::AtOffset => {
+                // null pointers are allowed for Option<T>
+                let optional = matches!(get_type_first_ident(ty), Ok(s) if s == "Option");
+                let sub_ty = if optional {
+                    let sub = get_item_subtype_parser(ty, "Option", "AtOffset")?;
+                    syn::parse2::<Type>(sub)?
+                } else {
+                    ty.clone()
+                };
+                let expr = get_parser(ident, &sub_ty, sub_meta_list, meta_list, config)?;
+                let offset = meta.arg().unwrap().clone();
+                let base = match meta_list
+                    .iter()
+                    .find(|m| m.is_type(MetaAttrType::OffsetBase))
+                {
+                    Some(m) => m.arg().unwrap().clone(),
+                    None => {
+                        let orig_input = Ident::new(config.orig_input_name(), meta.span());
+                        quote! { #orig_input }
+                    }
+                };
+                return Ok(ParserExpr::AtOffset(Box::new(expr), offset, base, optional));
+            }
             MetaAttrType::Cond => {
-                // try to infer subparser
-                // check type is Option<T>, and extract T
-                let sub = get_item_subtype_parser(ty, "Option", "Cond")?;
-                let sub_ty = syn::parse2::<Type>(sub)?;
+                // infer subparser from the type of items
+                let sub_ty = get_container_item_type(ty, meta, meta_list)?;
                 let expr = get_parser(ident, &sub_ty, sub_meta_list, meta_list, config)?;
                 let ts = meta.arg().unwrap();
-                return Ok(ParserExpr::Cond(Box::new(expr), ts.clone()));
+                return Ok(ParserExpr::Cond(
+                    Box::new(expr),
+                    ts.clone(),
+                    TypeItem(ty.clone()),
+                ));
             }
             MetaAttrType::Count => {
-                // try to infer subparser
-                // check type is Vec<T>, and extract T
-                let sub = get_item_subtype_parser(ty, "Vec", "Count")?;
-                let sub_ty = syn::parse2::<Type>(sub)?;
+                // infer subparser from the type of items
+                let sub_ty = get_container_item_type(ty, meta, meta_list)?;
                 let expr = get_parser(ident, &sub_ty, sub_meta_list, meta_list, config)?;
                 let ts = meta.arg().unwrap();
-                return Ok(ParserExpr::Count(Box::new(expr), ts.clone()));
+                return Ok(ParserExpr::Count(
+                    Box::new(expr),
+                    ts.clone(),
+                    TypeItem(ty.clone()),
+                ));
             }
             MetaAttrType::Into => {
                 let expr = get_parser(ident, ty, sub_meta_list, meta_list, config)?;
                 return Ok(ParserExpr::Into(Box::new(expr)));
             }
             MetaAttrType::LengthCount => {
-                // try to infer subparser
-                // check type is Vec<T>, and extract T
-                let sub = get_item_subtype_parser(ty, "Vec", "LengthCount")?;
-                let sub_ty = syn::parse2::<Type>(sub)?;
+                // infer subparser from the type of items
+                let sub_ty = get_container_item_type(ty, meta, meta_list)?;
                 let expr = get_parser(ident, &sub_ty, sub_meta_list, meta_list, config)?;
                 let ts = meta.arg().unwrap();
-                return Ok(ParserExpr::LengthCount(Box::new(expr), ts.clone()));
+                return Ok(ParserExpr::LengthCount(
+                    Box::new(expr),
+                    ts.clone(),
+                    TypeItem(ty.clone()),
+                ));
+            }
+            MetaAttrType::RepeatUntil | MetaAttrType::RepeatUntilTag => {
+                // check type is Vec<T>, and extract T (unless specified with `Elem`)
+                let sub_ty = match get_elem_type(meta_list)? {
+                    Some(elem) => elem,
+                    None => {
+                        let attr = meta.attr_type.to_string();
+                        let sub = get_item_subtype_parser(ty, "Vec", &attr)?;
+                        syn::parse2::<Type>(sub)?
+                    }
+                };
+                let expr = get_parser(ident, &sub_ty, sub_meta_list, meta_list, config)?;
+                let ts = meta.arg().unwrap().clone();
+                let (terminator, default_mode) = if meta.attr_type == MetaAttrType::RepeatUntil {
+                    let t = RepeatTerminator::Predicate(ts, sub_ty.to_token_stream());
+                    (t, TerminatorMode::Include)
+                } else {
+                    (RepeatTerminator::Tag(ts), TerminatorMode::Consume)
+                };
+                let mode = match meta_list
+                    .iter()
+                    .find(|m| m.is_type(MetaAttrType::Terminator))
+                {
+                    Some(m) => get_terminator_mode(m, &terminator)?,
+                    None => default_mode,
+                };
+                return Ok(ParserExpr::RepeatUntil(Box::new(expr), terminator, mode));
             }
             MetaAttrType::Map => {
                 let expr = get_parser(ident, ty, sub_meta_list, meta_list, config)?;
@@ -262,19 +605,52 @@ fn get_parser(
             _ => (),
         }
     }
+    // bit fields
+    if let Some(b) = &config.bits_field {
+        return Ok(ParserExpr::Bits(b.clone(), TypeItem(ty.clone())));
+    }
     // else try primitive types knowledge
     get_type_parser(ty, meta_list, config)
 }
 
 fn get_field_parser(field: &Field, meta_list: &[MetaAttr], config: &Config) -> Result<ParserExpr> {
     // eprintln!("field: {:?}", field);
-    get_parser(
+    // SerializeWith and Size wrap the parser, and can appear anywhere in the attribute list
+    let (ser_with, meta_list): (Vec<_>, Vec<_>) = meta_list
+        .iter()
+        .cloned()
+        .partition(|m| m.attr_type == MetaAttrType::SerializeWith);
+    let (size, meta_list): (Vec<_>, Vec<_>) = meta_list
+        .into_iter()
+        .partition(|m| matches!(m.attr_type, MetaAttrType::Size | MetaAttrType::StrictSize));
+    let expr = get_parser(
         field.ident.as_ref(),
         &field.ty,
-        meta_list,
-        meta_list,
+        &meta_list,
+        &meta_list,
         config,
-    )
+    )?;
+    let strict = size.iter().find(|m| m.is_type(MetaAttrType::StrictSize));
+    let expr = match (size.iter().find(|m| m.is_type(MetaAttrType::Size)), strict) {
+        (Some(m), _) => {
+            let n = get_expr_arg(m)?;
+            ParserExpr::Size(Box::new(expr), n, strict.is_some())
+        }
+        (None, Some(m)) => {
+            return Err(Error::new(
+                m.span(),
+                "Nom-derive: StrictSize requires the Size attribute",
+            ));
+        }
+        (None, None) => expr,
+    };
+    match ser_with.last() {
+        Some(m) => Ok(ParserExpr::SerializeWith(
+            Box::new(expr),
+            m.arg().unwrap().clone(),
+        )),
+        None => Ok(expr),
+    }
 }
 
 fn quote_align(align: &TokenStream, config: &Config) -> TokenStream {
@@ -282,7 +658,7 @@ fn quote_align(align: &TokenStream, config: &Config) -> TokenStream {
     let orig_input = syn::Ident::new(config.orig_input_name(), align.span());
     quote! {
         let (#input, _) = {
-            let offset = #input.as_ptr() as usize - #orig_input.as_ptr() as usize;
+            let offset = nom_derive::input_position(&#orig_input, &#input);
             let align = #align as usize;
             let align = ((align - (offset % align)) % align);
             nom::bytes::streaming::take(align)(#input)
@@ -296,8 +672,7 @@ fn quote_move(offset: &TokenStream, config: &Config) -> TokenStream {
     let orig_input = syn::Ident::new(config.orig_input_name(), offset.span());
     quote! {
         let #input = {
-            let start = #orig_input.as_ptr() as usize;
-            let pos = #input.as_ptr() as usize - start;
+            let pos = nom_derive::input_position(&#orig_input, &#input);
             let offset = #offset as isize;
             let offset_u = offset.abs() as usize;
             let new_offset = if offset < 0 {
@@ -306,12 +681,12 @@ fn quote_move(offset: &TokenStream, config: &Config) -> TokenStream {
                 }
                 pos - offset_u
             } else {
-                if pos + offset_u > #orig_input.len() {
-                    return Err(nom::Err::Incomplete(nom::Needed::new(offset_u)));
-                }
                 pos + offset_u
             };
-            &#orig_input[new_offset..]
+            match nom_derive::input_at(&#orig_input, new_offset) {
+                Some(new_input) => new_input,
+                None => return Err(nom::Err::Incomplete(nom::Needed::new(offset_u))),
+            }
         };
     }
 }
@@ -323,10 +698,10 @@ fn quote_move_abs(offset: &TokenStream, config: &Config) -> TokenStream {
     quote! {
         let #input = {
             let offset = #offset as usize;
-            if offset > #orig_input.len() {
-                return Err(nom::Err::Incomplete(nom::Needed::new(offset)));
+            match nom_derive::input_at(&#orig_input, offset) {
+                Some(new_input) => new_input,
+                None => return Err(nom::Err::Incomplete(nom::Needed::new(offset))),
             }
-            &#orig_input[offset..]
         };
     }
 }
@@ -401,7 +776,7 @@ pub(crate) fn get_pre_post_exec(
             }
             MetaAttrType::Exact => {
                 let input = syn::Ident::new(config.input_name(), m.span());
-                let cond = quote! { !#input.is_empty() };
+                let cond = quote! { nom::Input::input_len(&#input) != 0 };
                 let qq = quote_error_if(&cond, config);
                 tk_post.extend(qq);
             }
@@ -427,6 +802,492 @@ pub(crate) fn get_pre_post_exec(
     (pre, post)
 }
 
+/// Find fields with a `LengthOf` or `CountOf` attribute, and resolve their target
+fn get_computed_fields(
+    f: &Fields,
+    meta_lists: &[Vec<MetaAttr>],
+) -> Result<Vec<Option<ComputedField>>> {
+    let mut computed_fields = Vec::new();
+    for (idx, (field, meta_list)) in f.iter().zip(meta_lists).enumerate() {
+        let mut computed = None;
+        for m in meta_list {
+            if m.attr_type != MetaAttrType::LengthOf && m.attr_type != MetaAttrType::CountOf {
+                continue;
+            }
+            if computed.is_some() {
+                return Err(Error::new(
+                    m.span(),
+                    "Nom-derive: LengthOf and CountOf can only be used once per field",
+                ));
+            }
+            let target = syn::parse2::<Ident>(m.arg().unwrap().clone())?;
+            let target_index = f
+                .iter()
+                .position(|other| other.ident.as_ref() == Some(&target))
+                .ok_or_else(|| {
+                    Error::new(
+                        target.span(),
+                        format!("Nom-derive: unknown field in {}", m.attr_type),
+                    )
+                })?;
+            let ident = match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{}", idx),
+            };
+            computed = Some(ComputedField {
+                attr_type: m.attr_type,
+                field: ident,
+                ty: field.ty.clone(),
+                target,
+                target_index,
+            });
+        }
+        computed_fields.push(computed);
+    }
+    Ok(computed_fields)
+}
+
+/// Checks depending on several fields, executed once all the required fields have been parsed
+struct FieldsChecks {
+    /// Fields for which the input position must be recorded (before and after parsing)
+    marked: Vec<bool>,
+    /// Code to run after parsing each field
+    checks: Vec<TokenStream>,
+}
+
+/// A bound of a byte range, relative to the start of the struct
+enum RangeBound {
+    Offset(TokenStream),
+    FieldStart(usize),
+    FieldEnd(usize),
+}
+
+fn get_field_ident(f: &Fields, idx: usize) -> Ident {
+    match f.iter().nth(idx).and_then(|field| field.ident.as_ref()) {
+        Some(ident) => ident.clone(),
+        None => format_ident!("_{}", idx),
+    }
+}
+
+fn get_range_bound_offset(bound: &RangeBound, f: &Fields, config: &Config) -> TokenStream {
+    let orig_input = syn::Ident::new(config.orig_input_name(), Span::call_site());
+    match bound {
+        RangeBound::Offset(ts) => quote! { (#ts) as usize },
+        RangeBound::FieldStart(idx) => {
+            let start = format_ident!("__start_{}", get_field_ident(f, *idx));
+            quote! { nom_derive::input_position(&#orig_input, &#start) }
+        }
+        RangeBound::FieldEnd(idx) => {
+            let end = format_ident!("__end_{}", get_field_ident(f, *idx));
+            quote! { nom_derive::input_position(&#orig_input, &#end) }
+        }
+    }
+}
+
+/// Parse the arguments of the `Checksum` attribute: algorithm, and optional range
+fn get_checksum_check(
+    f: &Fields,
+    idx: usize,
+    m: &MetaAttr,
+    checks: &mut FieldsChecks,
+    config: &Config,
+) -> Result<()> {
+    let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+    let args = parser.parse2(m.arg().unwrap().clone())?;
+    let algo = match args.first() {
+        Some(Expr::Path(p)) if p.path.is_ident("crc32") => quote! { nom_derive::checksum::crc32 },
+        Some(Expr::Path(p)) if p.path.is_ident("adler32") => {
+            quote! { nom_derive::checksum::adler32 }
+        }
+        Some(Expr::Path(p)) if p.path.is_ident("internet") => {
+            quote! { nom_derive::checksum::internet }
+        }
+        Some(e) => e.to_token_stream(),
+        None => {
+            return Err(Error::new(
+                m.span(),
+                "Nom-derive: Checksum requires an algorithm",
+            ))
+        }
+    };
+    let field_index = |e: &Expr| match e {
+        Expr::Path(p) => p
+            .path
+            .get_ident()
+            .and_then(|id| f.iter().position(|field| field.ident.as_ref() == Some(id))),
+        _ => None,
+    };
+    let (start, end) = match args.iter().nth(1) {
+        None => (
+            RangeBound::Offset(quote! { 0 }),
+            RangeBound::FieldStart(idx),
+        ),
+        Some(Expr::Range(r)) => {
+            let start = match r.start.as_deref() {
+                None => RangeBound::Offset(quote! { 0 }),
+                Some(e) => match field_index(e) {
+                    Some(i) => RangeBound::FieldStart(i),
+                    None => RangeBound::Offset(e.to_token_stream()),
+                },
+            };
+            let closed = matches!(r.limits, RangeLimits::Closed(_));
+            let end = match r.end.as_deref() {
+                None => RangeBound::FieldStart(idx),
+                Some(e) => match (field_index(e), closed) {
+                    (Some(i), false) => RangeBound::FieldStart(i),
+                    (Some(i), true) => RangeBound::FieldEnd(i),
+                    (None, false) => RangeBound::Offset(e.to_token_stream()),
+                    (None, true) => RangeBound::Offset(quote! { #e + 1 }),
+                },
+            };
+            (start, end)
+        }
+        Some(e) => {
+            return Err(Error::new(
+                e.span(),
+                "Nom-derive: Checksum range must be a range of fields or offsets (for ex. `a..=c` or `0..12`)",
+            ))
+        }
+    };
+    // the check is done after parsing the checksum and all the fields of the range
+    let mut last = idx;
+    for bound in &[&start, &end] {
+        match bound {
+            RangeBound::FieldStart(i) | RangeBound::FieldEnd(i) => {
+                checks.marked[*i] = true;
+                last = last.max(*i);
+            }
+            RangeBound::Offset(_) => (),
+        }
+    }
+    let orig_input = syn::Ident::new(config.orig_input_name(), Span::call_site());
+    let field = get_field_ident(f, idx);
+    let start = get_range_bound_offset(&start, f, config);
+    let end = get_range_bound_offset(&end, f, config);
+    let cond = quote! {
+        nom_derive::input_range(&#orig_input, #start, #end)
+            .map_or(true, |__data| #algo(nom::AsBytes::as_bytes(&__data)) != #field)
+    };
+    checks.checks[last].extend(quote_error_if(&cond, config));
+    Ok(())
+}
+
+/// Prepare the code checking values depending on other fields (`LengthOf`, `CountOf`,
+/// `Checksum`)
+fn get_fields_checks(
+    f: &Fields,
+    meta_lists: &[Vec<MetaAttr>],
+    computed_fields: &[Option<ComputedField>],
+    config: &Config,
+) -> Result<FieldsChecks> {
+    let mut checks = FieldsChecks {
+        marked: vec![false; meta_lists.len()],
+        checks: vec![TokenStream::new(); meta_lists.len()],
+    };
+    for (idx, computed) in computed_fields.iter().enumerate() {
+        let c = match computed {
+            Some(c) => c,
+            None => continue,
+        };
+        let target = &c.target;
+        let actual = if c.attr_type == MetaAttrType::LengthOf {
+            // record input position around the target parser
+            checks.marked[c.target_index] = true;
+            let start = format_ident!("__start_{}", target);
+            let end = format_ident!("__end_{}", target);
+            quote! { nom::Input::input_len(&#start) - nom::Input::input_len(&#end) }
+        } else {
+            quote! { #target.len() }
+        };
+        let field = &c.field;
+        let cond = quote! {
+            <usize as core::convert::TryFrom<_>>::try_from(#field).ok() != Some(#actual)
+        };
+        // check once both the computed field and its target have been parsed
+        let last = idx.max(c.target_index);
+        checks.checks[last].extend(quote_error_if(&cond, config));
+    }
+    for (idx, meta_list) in meta_lists.iter().enumerate() {
+        for m in meta_list {
+            if m.attr_type == MetaAttrType::Checksum {
+                get_checksum_check(f, idx, m, &mut checks, config)?;
+            }
+        }
+    }
+    Ok(checks)
+}
+
+/// Add code recording the input position and checks for field `idx`
+fn add_fields_checks(
+    idx: usize,
+    f: &Fields,
+    pre: Option<TokenStream>,
+    post: Option<TokenStream>,
+    checks: &FieldsChecks,
+    config: &Config,
+) -> (Option<TokenStream>, Option<TokenStream>) {
+    let input = syn::Ident::new(config.input_name(), Span::call_site());
+    let mut tk_pre = pre.unwrap_or_default();
+    let mut tk_post = TokenStream::new();
+    if checks.marked[idx] {
+        let ident = get_field_ident(f, idx);
+        let start = format_ident!("__start_{}", ident);
+        let end = format_ident!("__end_{}", ident);
+        let input = config.input_copy(&input);
+        tk_pre.extend(quote! { let #start = #input; });
+        tk_post.extend(quote! { let #end = #input; });
+    }
+    if let Some(post) = post {
+        tk_post.extend(post);
+    }
+    tk_post.extend(checks.checks[idx].clone());
+    let pre = if tk_pre.is_empty() {
+        None
+    } else {
+        Some(tk_pre)
+    };
+    let post = if tk_post.is_empty() {
+        None
+    } else {
+        Some(tk_post)
+    };
+    (pre, post)
+}
+
+/// Add the requirements on the generic input type, for attributes not using the `Parse` trait
+pub(crate) fn add_input_bounds(meta_list: &[MetaAttr], config: &mut Config) {
+    let input_type = config.input_type();
+    for m in meta_list {
+        match m.attr_type {
+            MetaAttrType::Checksum | MetaAttrType::Encoding => {
+                config.add_input_bound(parse_quote! { #input_type: nom::AsBytes });
+            }
+            MetaAttrType::Tag => {
+                config.add_input_bound(
+                    parse_quote! { #input_type: for<'__t> nom::Compare<&'__t [u8]> },
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Get the parsers for the top-level `Tag` and `Magic` attributes, and the values to serialize
+///
+/// These values are checked before the first field, and are not stored.
+pub(crate) fn get_magic_parsers(
+    meta_list: &[MetaAttr],
+    config: &Config,
+) -> Result<Vec<(ParserExpr, TokenStream)>> {
+    let mut parsers = Vec::new();
+    for m in meta_list {
+        match m.attr_type {
+            MetaAttrType::Tag => {
+                // the value is not used to serialize a tag
+                parsers.push((ParserExpr::Tag(m.arg().unwrap().clone()), quote! { &() }));
+            }
+            MetaAttrType::Magic => {
+                let lit = match syn::parse2::<LitInt>(m.arg().unwrap().clone()) {
+                    Ok(lit) if !lit.suffix().is_empty() => lit,
+                    _ => {
+                        return Err(Error::new(
+                            m.span(),
+                            "Nom-derive: Magic must be an integer literal with a type suffix (for ex. 0xA1B2C3D4u32)",
+                        ));
+                    }
+                };
+                let ty = syn::parse_str::<Type>(lit.suffix())?;
+                let expr = get_endian_parser(&ty, &[], config)?;
+                let ident = Ident::new("__magic", m.span());
+                let cond = quote! { *__magic == #lit };
+                let expr = ParserExpr::Verify(Box::new(expr), ident, cond);
+                parsers.push((expr, quote! { &#lit }));
+            }
+            _ => (),
+        }
+    }
+    Ok(parsers)
+}
+
+/// Generate the code checking the top-level `Tag` and `Magic` values
+pub(crate) fn quote_magic(
+    magic: &[(ParserExpr, TokenStream)],
+    endianness: ParserEndianness,
+    config: &Config,
+) -> TokenStream {
+    let input = syn::Ident::new(config.input_name(), Span::call_site());
+    let parsers = magic.iter().map(|(p, _)| p.with_endianness(endianness));
+    quote! {
+        #( let (#input, _) = nom::Parser::parse(&mut #parsers, #input)?; )*
+    }
+}
+
+/// Record generic type parameters used as collections, with the type of their items
+///
+/// These parameters are required to implement `FromParsedIter` instead of `Parse`.
+fn add_collection_params(field: &Field, meta_list: &[MetaAttr], config: &mut Config) -> Result<()> {
+    let elem = match get_elem_type(meta_list)? {
+        Some(elem) => elem,
+        None => return Ok(()),
+    };
+    if let Type::Path(typepath) = &field.ty {
+        if let Some(ident) = typepath.path.get_ident() {
+            config.collection_params.push((ident.clone(), elem));
+        }
+    }
+    Ok(())
+}
+
+/// A group of consecutive bit fields, stored in a whole number of bytes
+struct BitsGroup {
+    /// Index of the first field of the group
+    first: usize,
+    data: Ident,
+    len_bytes: usize,
+    fields: Vec<(Ident, BitsField)>,
+}
+
+/// Size in bits of known types
+fn get_type_bits_width(ty: &Type) -> Option<usize> {
+    match get_type_first_ident(ty).ok()?.as_ref() {
+        "bool" => Some(1),
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        _ => None,
+    }
+}
+
+/// Return true if the field does not consume input
+fn is_field_virtual(field: &Field, meta_list: &[MetaAttr]) -> bool {
+    meta_list
+        .iter()
+        .any(|m| m.attr_type == MetaAttrType::Value || m.attr_type == MetaAttrType::Ignore)
+        || matches!(get_type_first_ident(&field.ty), Ok(s) if s == "PhantomData")
+}
+
+/// Get the width of a bit field, or `None` if the field is not a bit field
+fn get_bits_width(field: &Field, meta_list: &[MetaAttr], config: &Config) -> Result<Option<usize>> {
+    let meta = meta_list.iter().find(|m| m.attr_type == MetaAttrType::Bits);
+    let (width, span) = match meta {
+        Some(m) => {
+            let arg = m.arg().ok_or_else(|| {
+                Error::new(m.span(), "Nom-derive: Bits requires a width for fields")
+            })?;
+            let lit = syn::parse2::<LitInt>(arg.clone()).map_err(|_| {
+                Error::new(
+                    arg.span(),
+                    "Nom-derive: the width of bit fields must be an integer literal",
+                )
+            })?;
+            (lit.base10_parse::<usize>()?, m.span())
+        }
+        None if config.bits && !is_field_virtual(field, meta_list) => {
+            let width = get_type_bits_width(&field.ty).ok_or_else(|| {
+                Error::new(
+                    field.ty.span(),
+                    "Nom-derive: cannot infer the width of bit field, use the Bits attribute",
+                )
+            })?;
+            (width, field.ty.span())
+        }
+        None => return Ok(None),
+    };
+    let max = get_type_bits_width(&field.ty).unwrap_or(64);
+    if width == 0 || width > max {
+        return Err(Error::new(
+            span,
+            format!(
+                "Nom-derive: invalid width for bit field (must be 1 to {})",
+                max
+            ),
+        ));
+    }
+    Ok(Some(width))
+}
+
+/// Split bit fields in groups, and compute their positions
+fn get_bits_groups(
+    f: &Fields,
+    meta_lists: &[Vec<MetaAttr>],
+    config: &Config,
+) -> Result<Vec<BitsGroup>> {
+    let mut groups: Vec<BitsGroup> = Vec::new();
+    let mut current: Option<(BitsGroup, usize, bool)> = None;
+    let mut last_span = Span::call_site();
+    let close = |group: Option<(BitsGroup, usize, bool)>,
+                 span: Span|
+     -> Result<Option<BitsGroup>> {
+        match group {
+            Some((mut group, bits, _)) => {
+                if bits % 8 != 0 {
+                    return Err(Error::new(
+                        span,
+                        format!(
+                            "Nom-derive: bit fields must be byte-aligned at group boundaries (group has {} bits)",
+                            bits
+                        ),
+                    ));
+                }
+                group.len_bytes = bits / 8;
+                Ok(Some(group))
+            }
+            None => Ok(None),
+        }
+    };
+    for (idx, (field, meta_list)) in f.iter().zip(meta_lists).enumerate() {
+        match get_bits_width(field, meta_list, config)? {
+            Some(width) => {
+                let order = meta_list
+                    .iter()
+                    .find(|m| m.attr_type == MetaAttrType::BitOrder);
+                let (group, bits, lsb0) = match current.as_mut() {
+                    Some(current) => {
+                        // the order is set by the first field of the group
+                        if let Some(m) = order {
+                            if get_bit_order_lsb0(m)? != current.2 {
+                                return Err(Error::new(
+                                    m.span(),
+                                    "Nom-derive: BitOrder conflicts with the order of the bit group (set it on the first field of the group)",
+                                ));
+                            }
+                        }
+                        current
+                    }
+                    None => {
+                        let lsb0 = match order {
+                            Some(m) => get_bit_order_lsb0(m)?,
+                            None => config.bits_lsb0,
+                        };
+                        let group = BitsGroup {
+                            first: idx,
+                            data: format_ident!("__bits_{}", get_field_ident(f, idx)),
+                            len_bytes: 0,
+                            fields: Vec::new(),
+                        };
+                        current.get_or_insert((group, 0, lsb0))
+                    }
+                };
+                let b = BitsField {
+                    data: group.data.clone(),
+                    offset: *bits,
+                    width,
+                    lsb0: *lsb0,
+                    flags: meta_list.iter().any(|m| m.is_type(MetaAttrType::Flags)),
+                };
+                group.fields.push((get_field_ident(f, idx), b));
+                *bits += width;
+                last_span = field.span();
+            }
+            None if is_field_virtual(field, meta_list) => (),
+            None => groups.extend(close(current.take(), last_span)?),
+        }
+    }
+    groups.extend(close(current.take(), last_span)?);
+    Ok(groups)
+}
+
 pub(crate) fn parse_fields(f: &Fields, config: &mut Config) -> Result<StructParserTree> {
     let mut parsers = vec![];
     let mut empty = false;
@@ -443,15 +1304,30 @@ pub(crate) fn parse_fields(f: &Fields, config: &mut Config) -> Result<StructPars
             // so the caller must check and add attributes
         }
     }
-    for (idx, field) in f.iter().enumerate() {
+    let field_idents: Vec<_> = f.iter().filter_map(|field| field.ident.clone()).collect();
+    let meta_lists = f
+        .iter()
+        .map(|field| meta::parse_nom_attribute(&field.attrs))
+        .collect::<Result<Vec<_>>>()?;
+    let computed_fields = get_computed_fields(f, &meta_lists)?;
+    let checks = get_fields_checks(f, &meta_lists, &computed_fields, config)?;
+    let bits_groups = get_bits_groups(f, &meta_lists, config)?;
+    for ((idx, field), meta_list) in f.iter().enumerate().zip(meta_lists) {
         let ident_str = if let Some(s) = field.ident.as_ref() {
             s.to_string()
         } else {
             format!("_{}", idx)
         };
-        let meta_list = meta::parse_nom_attribute(&field.attrs)?;
         // eprintln!("meta_list: {:?}", meta_list);
+        config.bits_field = bits_groups
+            .iter()
+            .flat_map(|g| &g.fields)
+            .find(|(ident, _)| *ident == get_field_ident(f, idx))
+            .map(|(_, b)| b.clone());
         let mut p = get_field_parser(field, &meta_list, config)?;
+        config.bits_field = None;
+        add_input_bounds(&meta_list, config);
+        add_collection_params(field, &meta_list, config)?;
 
         if config.complete {
             p = p.complete();
@@ -471,10 +1347,44 @@ pub(crate) fn parse_fields(f: &Fields, config: &mut Config) -> Result<StructPars
             p = ParserExpr::DbgDmp(Box::new(p), ident.clone());
         }
 
+        if let Some(name) = &config.context {
+            // name fields of tuple structs using their index
+            let field_name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => idx.to_string(),
+            };
+            p = ParserExpr::Context(
+                Box::new(p.with_indices()),
+                format!("{}.{}", name, field_name),
+            );
+        }
+
         // add pre and post code (also takes care of alignment)
         let (pre, post) = get_pre_post_exec(&meta_list, config);
+        let (mut pre, post) = add_fields_checks(idx, f, pre, post, &checks, config);
+        let bits_group = bits_groups.iter().find(|g| g.first == idx);
+        if let Some(group) = bits_group {
+            // read all bytes of the group before the first field
+            let input = syn::Ident::new(config.input_name(), Span::call_site());
+            let (data, len) = (&group.data, group.len_bytes);
+            pre.get_or_insert_with(TokenStream::new).extend(quote! {
+                let (#input, #data) = nom::bytes::streaming::take(#len)(#input)?;
+                let #data: [u8; #len] = nom_derive::bits::group_bytes(#data);
+            });
+        }
         let item = ParserTreeItem::new(field.ident.clone(), p);
-        let sp = StructParser::new(ident_str, item, pre, post);
+        let mut sp = StructParser::new(ident_str, item, pre, post);
+        sp.computed = computed_fields[idx].clone();
+        if config.serialize {
+            let (mut ser_pre, ser_post) = get_serialize_pre_post(&meta_list, &field_idents)?;
+            if let Some(group) = bits_group {
+                ser_pre
+                    .get_or_insert_with(TokenStream::new)
+                    .extend(gen_bits_group_serializer(group.len_bytes, &group.fields));
+            }
+            sp.ser_pre = ser_pre;
+            sp.ser_post = ser_post;
+        }
         parsers.push(sp);
     }
     Ok(StructParserTree {
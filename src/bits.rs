@@ -0,0 +1,166 @@
+//! Helpers for bit fields, used by the `Bits` attribute
+//!
+//! See the [docs](crate::docs::Nom#bit-fields) for details.
+
+use nom::Input;
+
+/// Order of bits in a byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The first bit is the most significant bit of the byte (network order)
+    Msb0,
+    /// The first bit is the least significant bit of the byte
+    Lsb0,
+}
+
+/// Copy the bytes of a group of bit fields from the input
+///
+/// If `data` is shorter than `N` bytes, the remaining bytes are set to 0.
+pub fn group_bytes<I: Input<Item = u8>, const N: usize>(data: I) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for (b, v) in bytes.iter_mut().zip(data.iter_elements()) {
+        *b = v;
+    }
+    bytes
+}
+
+/// Read `width` bits (at most 64) from `data`, starting at bit `offset`
+///
+/// With `BitOrder::Msb0`, the first bit read is the most significant bit of the result. With
+/// `BitOrder::Lsb0`, it is the least significant bit.
+///
+/// Panics if `data` is too small.
+pub fn extract_bits(data: &[u8], offset: usize, width: usize, order: BitOrder) -> u64 {
+    debug_assert!(width <= 64);
+    let mut v = 0u64;
+    for k in 0..width {
+        let b = offset + k;
+        let byte = data[b / 8];
+        match order {
+            BitOrder::Msb0 => {
+                let bit = (byte >> (7 - b % 8)) & 1;
+                v = (v << 1) | bit as u64;
+            }
+            BitOrder::Lsb0 => {
+                let bit = (byte >> (b % 8)) & 1;
+                v |= (bit as u64) << k;
+            }
+        }
+    }
+    v
+}
+
+/// Write the `width` lower bits of `value` to `data`, starting at bit `offset`
+///
+/// This is the inverse of [`extract_bits`].
+///
+/// Panics if `data` is too small.
+pub fn insert_bits(data: &mut [u8], offset: usize, width: usize, value: u64, order: BitOrder) {
+    debug_assert!(width <= 64);
+    for k in 0..width {
+        let b = offset + k;
+        let (bit, shift) = match order {
+            BitOrder::Msb0 => ((value >> (width - 1 - k)) & 1, 7 - b % 8),
+            BitOrder::Lsb0 => ((value >> k) & 1, b % 8),
+        };
+        data[b / 8] = (data[b / 8] & !(1 << shift)) | ((bit as u8) << shift);
+    }
+}
+
+/// Types which can be stored in bit fields
+pub trait BitField: Sized {
+    /// Build value from the `width` lower bits of `v`
+    fn from_bits(v: u64, width: usize) -> Self;
+
+    /// Return the bits of the value
+    fn to_bits(&self) -> u64;
+}
+
+impl BitField for bool {
+    fn from_bits(v: u64, _width: usize) -> Self {
+        v != 0
+    }
+
+    fn to_bits(&self) -> u64 {
+        *self as u64
+    }
+}
+
+macro_rules! impl_bitfield_unsigned {
+    ( $ty:ty ) => {
+        impl BitField for $ty {
+            fn from_bits(v: u64, _width: usize) -> Self {
+                v as $ty
+            }
+
+            fn to_bits(&self) -> u64 {
+                *self as u64
+            }
+        }
+    };
+}
+
+macro_rules! impl_bitfield_signed {
+    ( $ty:ty, $uty:ty ) => {
+        impl BitField for $ty {
+            fn from_bits(v: u64, width: usize) -> Self {
+                // sign extension
+                let shift = 64 - width;
+                (((v << shift) as i64) >> shift) as $ty
+            }
+
+            fn to_bits(&self) -> u64 {
+                *self as $uty as u64
+            }
+        }
+    };
+}
+
+impl_bitfield_unsigned!(u8);
+impl_bitfield_unsigned!(u16);
+impl_bitfield_unsigned!(u32);
+impl_bitfield_unsigned!(u64);
+
+impl_bitfield_signed!(i8, u8);
+impl_bitfield_signed!(i16, u16);
+impl_bitfield_signed!(i32, u32);
+impl_bitfield_signed!(i64, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_bits() {
+        let data = [0x45, 0xa1];
+        assert_eq!(extract_bits(&data, 0, 4, BitOrder::Msb0), 4);
+        assert_eq!(extract_bits(&data, 4, 4, BitOrder::Msb0), 5);
+        assert_eq!(extract_bits(&data, 4, 12, BitOrder::Msb0), 0x5a1);
+        assert_eq!(extract_bits(&data, 0, 4, BitOrder::Lsb0), 5);
+        assert_eq!(extract_bits(&data, 4, 4, BitOrder::Lsb0), 4);
+        assert_eq!(extract_bits(&data, 4, 12, BitOrder::Lsb0), 0xa14);
+        assert_eq!(extract_bits(&data, 0, 16, BitOrder::Msb0), 0x45a1);
+    }
+
+    #[test]
+    fn test_insert_bits() {
+        for &order in &[BitOrder::Msb0, BitOrder::Lsb0] {
+            let mut data = [0u8; 2];
+            insert_bits(&mut data, 0, 3, 5, order);
+            insert_bits(&mut data, 3, 9, 0x155, order);
+            insert_bits(&mut data, 12, 4, 0xf, order);
+            assert_eq!(extract_bits(&data, 0, 3, order), 5);
+            assert_eq!(extract_bits(&data, 3, 9, order), 0x155);
+            assert_eq!(extract_bits(&data, 12, 4, order), 0xf);
+        }
+    }
+
+    #[test]
+    fn test_bitfield_signed() {
+        assert_eq!(i8::from_bits(0xf, 4), -1);
+        assert_eq!(i8::from_bits(0x7, 4), 7);
+        assert_eq!(i16::from_bits(0x800, 12), -2048);
+        assert_eq!((-2i8).to_bits() & 0xf, 0xe);
+        assert!(bool::from_bits(1, 1));
+    }
+}
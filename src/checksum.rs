@@ -0,0 +1,87 @@
+//! Checksum algorithms, used by the `Checksum` attribute
+//!
+//! See the [docs](crate::docs::Nom#checksum) for details.
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// Compute the CRC-32 (ISO-HDLC, as used by Ethernet, zlib or PNG) of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    let crc = data.iter().fold(0xffff_ffff_u32, |crc, &b| {
+        CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+/// Compute the Adler-32 checksum (RFC 1950) of `data`
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // process by chunks, to reduce the number of modulo operations without overflowing
+    for chunk in data.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+/// Compute the Internet checksum (RFC 1071) of `data`
+///
+/// `data` is read as big-endian 16-bit words (padded with a zero byte if the length is odd).
+pub fn internet(data: &[u8]) -> u16 {
+    let sum = data.chunks(2).fold(0u32, |sum, w| {
+        let word = match w {
+            [a, b] => u16::from_be_bytes([*a, *b]),
+            [a] => u16::from_be_bytes([*a, 0]),
+            _ => unreachable!(),
+        };
+        // fold the carry at each step, so the sum always fits in 16 bits
+        let sum = sum + word as u32;
+        (sum & 0xffff) + (sum >> 16)
+    });
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&[0xff; 10000]), 0xb623_eb2b);
+        // IPv4 header, with checksum field set to 0
+        let hdr =
+            b"\x45\x00\x00\x73\x00\x00\x40\x00\x40\x11\x00\x00\xc0\xa8\x00\x01\xc0\xa8\x00\xc7";
+        assert_eq!(internet(hdr), 0xb861);
+        assert_eq!(internet(b"\x01"), 0xfeff);
+        // large inputs: the sum of 100000 words of 0xffff does not fit in a u32
+        assert_eq!(internet(&[0xff; 200_000]), 0);
+        assert_eq!(internet(&[0x01, 0x00].repeat(100_000)), 0x5e79);
+    }
+}
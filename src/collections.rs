@@ -0,0 +1,398 @@
+//! Collections of parsed items, used by the `Count`, `LengthCount` and `Cond` attributes
+//!
+//! Fields using these attributes can have any type implementing [`FromParsedIter`]: this
+//! includes `Vec`, `VecDeque`, `Box<[T]>`, `Option`, `BTreeMap`, `HashMap` (with the `std`
+//! feature), and `SmallVec`, `heapless::Vec` or `ArrayVec` (with the `smallvec`, `heapless` or
+//! `arrayvec` features). These types also implement [`SerializeIter`], to serialize the items.
+//!
+//! See the [docs](crate::docs::Nom#collections) for details.
+
+use crate::serialize::SerializeError;
+use nom::error::{ContextError, ErrorKind, ParseError};
+use nom::{Err, IResult, Parser, ToUsize};
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, LinkedList, VecDeque},
+    vec::Vec,
+};
+
+/// Containers which can be built from parsed items
+///
+/// For maps, items are `(key, value)` tuples.
+pub trait FromParsedIter: Sized {
+    /// The type of items
+    type Item;
+
+    /// Build the container from all the items of `iter`
+    ///
+    /// Returns `None` if the items do not fit in the container.
+    fn from_parsed_iter<It: Iterator<Item = Self::Item>>(iter: It) -> Option<Self>;
+}
+
+impl<T> FromParsedIter for Option<T> {
+    type Item = T;
+
+    fn from_parsed_iter<It: Iterator<Item = T>>(mut iter: It) -> Option<Self> {
+        let item = iter.next();
+        match iter.next() {
+            Some(_) => None,
+            None => Some(item),
+        }
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "smallvec"))]
+macro_rules! impl_from_parsed_iter_collect {
+    ( $( $ty:ty, $item:ty, [ $($gen:tt)* ] ),* $(,)? ) => {
+        $(
+            impl< $($gen)* > FromParsedIter for $ty {
+                type Item = $item;
+
+                fn from_parsed_iter<It: Iterator<Item = $item>>(iter: It) -> Option<Self> {
+                    Some(iter.collect())
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_from_parsed_iter_collect!(
+    Vec<T>, T, [T],
+    VecDeque<T>, T, [T],
+    LinkedList<T>, T, [T],
+    Box<[T]>, T, [T],
+    BTreeSet<T>, T, [T: Ord],
+    BTreeMap<K, V>, (K, V), [K: Ord, V],
+);
+
+#[cfg(feature = "std")]
+impl_from_parsed_iter_collect!(
+    std::collections::HashMap<K, V, S>, (K, V),
+        [K: Eq + core::hash::Hash, V, S: core::hash::BuildHasher + Default],
+    std::collections::HashSet<T, S>, T,
+        [T: Eq + core::hash::Hash, S: core::hash::BuildHasher + Default],
+);
+
+#[cfg(feature = "smallvec")]
+impl_from_parsed_iter_collect!(smallvec::SmallVec<A>, A::Item, [A: smallvec::Array]);
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> FromParsedIter for heapless::Vec<T, N> {
+    type Item = T;
+
+    fn from_parsed_iter<It: Iterator<Item = T>>(iter: It) -> Option<Self> {
+        let mut v = heapless::Vec::new();
+        for item in iter {
+            v.push(item).ok()?;
+        }
+        Some(v)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> FromParsedIter for arrayvec::ArrayVec<T, CAP> {
+    type Item = T;
+
+    fn from_parsed_iter<It: Iterator<Item = T>>(iter: It) -> Option<Self> {
+        let mut v = arrayvec::ArrayVec::new();
+        for item in iter {
+            v.try_push(item).ok()?;
+        }
+        Some(v)
+    }
+}
+
+/// Containers whose items can be serialized
+///
+/// This is the counterpart of [`FromParsedIter`], used to serialize `Count`, `LengthCount` and
+/// `Cond` fields. For maps, items are `(&key, &value)` tuples.
+pub trait SerializeIter {
+    /// The type of items given to [`try_for_each_item`](SerializeIter::try_for_each_item)
+    type ItemRef<'a>
+    where
+        Self: 'a;
+
+    /// Return the number of items
+    fn item_count(&self) -> usize;
+
+    /// Call `f` on each item, in order, and stop at the first error
+    fn try_for_each_item<'a, F>(&'a self, f: F) -> Result<(), SerializeError>
+    where
+        F: FnMut(&Self::ItemRef<'a>) -> Result<(), SerializeError>;
+}
+
+impl<T> SerializeIter for Option<T> {
+    type ItemRef<'a>
+        = T
+    where
+        T: 'a;
+
+    fn item_count(&self) -> usize {
+        usize::from(self.is_some())
+    }
+
+    fn try_for_each_item<'a, F>(&'a self, f: F) -> Result<(), SerializeError>
+    where
+        F: FnMut(&Self::ItemRef<'a>) -> Result<(), SerializeError>,
+    {
+        self.iter().try_for_each(f)
+    }
+}
+
+#[cfg(any(
+    feature = "alloc",
+    feature = "smallvec",
+    feature = "heapless",
+    feature = "arrayvec"
+))]
+macro_rules! impl_serialize_iter {
+    ( $( $ty:ty, $item:ty, [ $($gen:tt)* ] ),* $(,)? ) => {
+        $(
+            impl< $($gen)* > SerializeIter for $ty {
+                type ItemRef<'a> = $item where Self: 'a;
+
+                fn item_count(&self) -> usize {
+                    self.len()
+                }
+
+                fn try_for_each_item<'a, F>(&'a self, f: F) -> Result<(), SerializeError>
+                where
+                    F: FnMut(&Self::ItemRef<'a>) -> Result<(), SerializeError>,
+                {
+                    self.iter().try_for_each(f)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_serialize_iter_map {
+    ( $( $ty:ty, [ $($gen:tt)* ] ),* $(,)? ) => {
+        $(
+            impl< $($gen)* > SerializeIter for $ty {
+                type ItemRef<'a> = (&'a K, &'a V) where Self: 'a;
+
+                fn item_count(&self) -> usize {
+                    self.len()
+                }
+
+                fn try_for_each_item<'a, F>(&'a self, mut f: F) -> Result<(), SerializeError>
+                where
+                    F: FnMut(&Self::ItemRef<'a>) -> Result<(), SerializeError>,
+                {
+                    self.iter().try_for_each(|(k, v)| f(&(k, v)))
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_serialize_iter!(
+    Vec<T>,
+    T,
+    [T],
+    VecDeque<T>,
+    T,
+    [T],
+    LinkedList<T>,
+    T,
+    [T],
+    Box<[T]>,
+    T,
+    [T],
+    BTreeSet<T>,
+    T,
+    [T],
+);
+
+#[cfg(feature = "alloc")]
+impl_serialize_iter_map!(BTreeMap<K, V>, [K, V]);
+
+#[cfg(feature = "std")]
+impl_serialize_iter!(std::collections::HashSet<T, S>, T, [T, S]);
+
+#[cfg(feature = "std")]
+impl_serialize_iter_map!(std::collections::HashMap<K, V, S>, [K, V, S]);
+
+#[cfg(feature = "smallvec")]
+impl_serialize_iter!(smallvec::SmallVec<A>, A::Item, [A: smallvec::Array]);
+
+#[cfg(feature = "heapless")]
+impl_serialize_iter!(heapless::Vec<T, N>, T, [T, const N: usize]);
+
+#[cfg(feature = "arrayvec")]
+impl_serialize_iter!(arrayvec::ArrayVec<T, CAP>, T, [T, const CAP: usize]);
+
+/// Run the parser `n` times, and collect the results
+///
+/// Like `nom::multi::count`, but builds any container implementing [`FromParsedIter`]. If the
+/// items do not fit in the container, a `TooLarge` error is returned.
+pub fn count<C, I, E, F>(mut f: F, n: usize) -> impl FnMut(I) -> IResult<I, C, E>
+where
+    C: FromParsedIter,
+    I: Clone,
+    E: ParseError<I>,
+    F: Parser<I, Output = C::Item, Error = E>,
+{
+    move |i: I| collect_n(&mut f, i, n)
+}
+
+/// Get the number of items using the parser `f`, then run the parser `g` this number of times
+///
+/// Like `nom::multi::length_count`, but builds any container implementing [`FromParsedIter`].
+pub fn length_count<C, I, E, N, F, G>(mut f: F, mut g: G) -> impl FnMut(I) -> IResult<I, C, E>
+where
+    C: FromParsedIter,
+    I: Clone,
+    E: ParseError<I>,
+    N: ToUsize,
+    F: Parser<I, Output = N, Error = E>,
+    G: Parser<I, Output = C::Item, Error = E>,
+{
+    move |i: I| {
+        let (i, n) = f.parse(i)?;
+        collect_n(&mut g, i, n.to_usize())
+    }
+}
+
+fn collect_n<C, I, E, F>(f: &mut F, i: I, n: usize) -> IResult<I, C, E>
+where
+    C: FromParsedIter,
+    I: Clone,
+    E: ParseError<I>,
+    F: Parser<I, Output = C::Item, Error = E>,
+{
+    let mut input = i.clone();
+    let mut error = None;
+    let iter = (0..n).map_while(|_| match f.parse(input.clone()) {
+        Ok((rem, o)) => {
+            input = rem;
+            Some(o)
+        }
+        Err(e) => {
+            error = Some(e);
+            None
+        }
+    });
+    let res = C::from_parsed_iter(iter);
+    match error {
+        Some(Err::Error(e)) => Err(Err::Error(E::append(i, ErrorKind::Count, e))),
+        Some(e) => Err(e),
+        None => match res {
+            Some(c) => Ok((input, c)),
+            None => Err(Err::Error(E::from_error_kind(i, ErrorKind::TooLarge))),
+        },
+    }
+}
+
+/// Maximum index recorded by [`indexed`]
+const MAX_INDEX: usize = 255;
+
+/// Contexts for element indices: `[0]` to `[255]`, each in a slot of 5 bytes
+static INDEX_CONTEXTS: [u8; 5 * (MAX_INDEX + 1)] = index_contexts();
+
+const fn index_contexts() -> [u8; 5 * (MAX_INDEX + 1)] {
+    let mut buf = [0u8; 5 * (MAX_INDEX + 1)];
+    let mut idx = 0;
+    while idx <= MAX_INDEX {
+        let mut pos = idx * 5;
+        buf[pos] = b'[';
+        pos += 1;
+        if idx >= 100 {
+            buf[pos] = b'0' + (idx / 100) as u8;
+            pos += 1;
+        }
+        if idx >= 10 {
+            buf[pos] = b'0' + (idx / 10 % 10) as u8;
+            pos += 1;
+        }
+        buf[pos] = b'0' + (idx % 10) as u8;
+        buf[pos + 1] = b']';
+        idx += 1;
+    }
+    buf
+}
+
+/// Return the context for the element at index `idx` (for ex. `[3]`), or `[..]` if the index
+/// is larger than 255
+pub fn index_context(idx: usize) -> &'static str {
+    if idx > MAX_INDEX {
+        return "[..]";
+    }
+    let len = match idx {
+        0..=9 => 3,
+        10..=99 => 4,
+        _ => 5,
+    };
+    core::str::from_utf8(&INDEX_CONTEXTS[idx * 5..idx * 5 + len]).unwrap_or("[..]")
+}
+
+/// Errors recording the index of the element of a collection which failed to parse
+///
+/// This is required by the `Context` attribute, with `GenericErrors`. The default implementation
+/// adds the index as a context using [`index_context`], so indices larger than 255 are recorded
+/// as `[..]`. Error types can record any index by implementing
+/// [`add_index`](IndexContextError::add_index).
+pub trait IndexContextError<I>: ContextError<I> {
+    /// Add the index `idx` of the element which failed to parse to `other`
+    fn add_index(input: I, idx: usize, other: Self) -> Self {
+        Self::add_context(input, index_context(idx), other)
+    }
+}
+
+impl<I> IndexContextError<I> for nom::error::Error<I> {}
+impl<I> IndexContextError<I> for (I, ErrorKind) {}
+impl<I> IndexContextError<I> for () {}
+
+/// Wrap the parser of the elements of a collection, to add the index of the element to errors
+///
+/// The index is the number of times the parser was called, and is added using
+/// [`IndexContextError::add_index`].
+pub fn indexed<I, O, E, F>(mut f: F) -> impl FnMut(I) -> IResult<I, O, E>
+where
+    I: Clone,
+    E: IndexContextError<I>,
+    F: Parser<I, Output = O, Error = E>,
+{
+    let mut idx = 0;
+    move |i: I| {
+        let res = f
+            .parse(i.clone())
+            .map_err(|e| e.map(|e| E::add_index(i, idx, e)));
+        idx += 1;
+        res
+    }
+}
+
+/// Run the parser if the condition is true, and build the container from the optional result
+///
+/// Like `nom::combinator::cond`, but builds any container implementing [`FromParsedIter`] (for
+/// ex. `Option<T>`, or an empty `Vec<T>` if the condition is false).
+pub fn cond<C, I, E, F>(b: bool, mut f: F) -> impl FnMut(I) -> IResult<I, C, E>
+where
+    C: FromParsedIter,
+    I: Clone,
+    E: ParseError<I>,
+    F: Parser<I, Output = C::Item, Error = E>,
+{
+    move |i: I| {
+        let (rem, o) = if b {
+            let (rem, o) = f.parse(i.clone())?;
+            (rem, Some(o))
+        } else {
+            (i.clone(), None)
+        };
+        match C::from_parsed_iter(o.into_iter()) {
+            Some(c) => Ok((rem, c)),
+            None => Err(Err::Error(E::from_error_kind(i, ErrorKind::TooLarge))),
+        }
+    }
+}
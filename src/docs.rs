@@ -32,7 +32,10 @@
 /// - [Deriving parsers for `Struct`](#deriving-parsers-for-struct)
 /// - [Deriving parsers for `Enum`](#deriving-parsers-for-enum)
 /// - [Generic Errors](#generic-errors)
+/// - [Generic Input](#generic-input)
+/// - [Text input](#text-input)
 /// - [Generic Type Parameters](#generic-type-parameters)
+/// - [Serialization](#serialization)
 ///
 /// # Attributes
 ///
@@ -55,35 +58,68 @@
 /// |-----------|------------------|------------
 /// | [AlignAfter](#alignment-and-padding) | fields | skip bytes until aligned to a multiple of the provided value, after parsing value
 /// | [AlignBefore](#alignment-and-padding) | fields | skip bytes until aligned to a multiple of the provided value, before parsing value
+/// | [AtOffset](#atoffset) | fields | Parse value at an offset of the input, without moving the current position (alias: `Pointer`)
 /// | [BigEndian](#byteorder) | all | Set the endianness to big endian
-/// | [Cond](#conditional-values) | fields | Used on an `Option<T>` to read a value of type `T` only if the condition is met
+/// | [BitOrder](#bit-fields) | all | Set the order of bits in bytes for bit fields
+/// | [Bits](#bit-fields) | all | Read field from a number of bits, or read all fields as bit fields
+/// | [Checksum](#checksum) | fields | Verify the value is the checksum of a range of bytes
+/// | [Cond](#conditional-values) | fields | Used on an `Option<T>` (or another collection) to read a value of type `T` only if the condition is met
 /// | [Complete](#complete) | all | Transforms Incomplete into Error
+/// | [Context](#error-context) | top-level | Add the path of the field (`Struct.field`) to errors
 /// | [Count](#count) | fields | Set the expected number of items to parse
+/// | [CountOf](#lengthof-and-countof) | fields | The field is the number of elements of another field
 /// | [Debug](#debug) | all | Print error message and input if parser fails (at runtime)
 /// | [DebugDerive](#debugderive) | top-level | Print the generated code to stderr during build
 /// | [Default](#default) | fields | Do not parse, set a field to the default value for the type
+/// | [Elem](#collections) | fields | Specify the type of items for `Count`, `LengthCount`, `Cond` or `RepeatUntil`
+/// | [Encoding](#string-encodings) | fields | Decode a `String` using UTF-16, Latin-1 or Windows-1252
 /// | [ErrorIf](#verifications) | fields | Before parsing, check condition is true and return an error if false.
 /// | [Exact](#exact) | top-level | Check that input was entirely consumed by parser
+/// | [Flags](#bitflags) | fields | Read a `bitflags` type (`"truncate"`, `"retain"` or `"strict"`)
 /// | [GenericErrors](#generic-errors) | top-level | Change function signature to accept generic type parameter for error
+/// | [GenericInput](#generic-input) | top-level | Change function signature to accept generic type parameter for input
 /// | [If](#conditional-values) | fields | Similar to `Cond`
+/// | [Hex](#text-input) | fields | Read an integer in hexadecimal text form
 /// | [Ignore](#default) | fields | An alias for `default`
 /// | [InputName](#input-name) | top-level | Change the internal name of input
+/// | [Int24](#odd-width-and-exotic-numbers) | fields | Read a signed integer from 3 bytes
 /// | [Into](#into) | fields | Automatically converts the child parser's result to another type
 /// | [LengthCount](#lengthcount) | fields | Specify a parser to get the number of items, and parse the expected number of items
+/// | [LengthOf](#lengthof-and-countof) | fields | The field is the length in bytes of another field
+/// | [LengthPrefix](#string-fields) | fields | Read a string prefixed by its length, using the provided type
 /// | [LittleEndian](#byteorder) | all | Set the endianness to little endian
+/// | [Magic](#tag) | top-level | Check a constant integer (for ex. `0xA1B2C3D4u32`) before the first field
 /// | [Map](#map) | fields | Parse field, then apply a function
 /// | [Move](#alignment-and-padding) | fields | add the specified offset to current position, before parsing
 /// | [MoveAbs](#alignment-and-padding) | fields | go to the specified absoluted position, before parsing
+/// | [OffsetBase](#atoffset) | fields | Set the input used as base for `AtOffset`
+/// | [NulTerminated](#string-fields) | fields | Read a string terminated by a NUL byte
+/// | [PadByte](#string-fields) | fields | Set the padding byte for `Padded` (default: 0)
+/// | [Padded](#string-fields) | fields | Read a string stored in a fixed number of bytes, with padding
 /// | [Parse](#custom-parsers) | fields | Use a custom parser function for reading from a file
+/// | [Peek](#peek) | fields | Parse value without consuming input
 /// | [PreExec](#preexec) | all | Execute Rust code before parsing field or struct
 /// | [PostExec](#postexec) | all | Execute Rust code after parsing field or struct
+/// | [RepeatUntil](#repeatuntil-and-repeatuntiltag) | fields | Parse items until an item matches a predicate
+/// | [RepeatUntilTag](#repeatuntil-and-repeatuntiltag) | fields | Parse items until a constant pattern is found
 /// | [Selector](#deriving-parsers-for-enum) | all | Used to specify the value matching an enum variant
+/// | [Serialize](#serialization) | top-level | Also derive the `Serialize` trait (inverse of the parser)
+/// | [SerializeWith](#serialization) | fields | Use a custom function to serialize a field
 /// | [SetEndian](#byteorder) | all | Dynamically set the endianness
+/// | [Size](#size) | fields | Parse field from a window of `n` bytes, and skip the bytes left in the window
 /// | [SkipAfter](#alignment-and-padding) | fields | skip the specified number of bytes, after parsing
 /// | [SkipBefore](#alignment-and-padding) | fields | skip the specified number of bytes, before parsing
-/// | [Tag](#tag) | fields | Parse a constant pattern
+/// | [StrInput](#text-input) | top-level | Change function signature to parse `&str` instead of `&[u8]`
+/// | [StrictSize](#size) | fields | With `Size`, return an error if bytes are left in the window
+/// | [Tag](#tag) | all | Parse a constant pattern (at top-level: checked before the first field, and not stored)
 /// | [Take](#take) | fields | Take `n` bytes of input
+/// | [Terminator](#repeatuntil-and-repeatuntiltag) | fields | Set if the terminator of `RepeatUntil` is included, consumed, or kept in input
+/// | [Trim](#string-fields) | fields | Set the trim policy for `Padded` (`"keep"`, `"padding"` or `"nul"`)
+/// | [Uint24](#odd-width-and-exotic-numbers) | fields | Read an unsigned integer from 3 bytes
+/// | [Uint48](#odd-width-and-exotic-numbers) | fields | Read an unsigned integer from 6 bytes
+/// | [Unknown](#default-case) | fields | Catch-all enum variant, storing the unknown value
 /// | [Value](#value) | fields | Store result of evaluated expression in field
+/// | [Varint](#variable-length-integers) | fields | Read an integer using a variable-length encoding
 /// | [Verify](#verifications) | fields | After parsing, check that condition is true and return an error if false.
 ///
 /// See below for examples.
@@ -271,6 +307,11 @@
 /// If the parser cannot be inferred, a default function will be called. It is also possible to
 /// override this using the `Parse` attribute.
 ///
+/// Qualified paths like `std::vec::Vec<T>`, `core::marker::PhantomData<T>` or `crate::types::Foo`
+/// are also supported. Paths in `std`, `core` or `alloc` are identified by their last segment,
+/// while other qualified paths are always user types (so `mymod::Option<T>` is not handled as
+/// `Option<T>`).
+///
 /// Following sections give more details.
 ///
 /// ## Option types
@@ -313,12 +354,97 @@
 /// # }
 /// ```
 ///
+/// ## Booleans, characters and network addresses
+///
+/// Parsers are also provided for some common value types:
+/// - `bool` is read from a byte, and only `0` and `1` are accepted. To accept any non-zero value
+///   as `true`, use `#[nom(Parse = "nom_derive::lenient_bool")]`
+/// - `char` is read from a `u32` code point
+/// - `NonZeroU8`, `NonZeroI16`, etc. are read like the underlying integer type, and zero is
+///   rejected
+/// - `Ipv4Addr` and `Ipv6Addr` are read from 4 or 16 bytes in network order, and `SocketAddrV4`
+///   from an `Ipv4Addr` followed by a `u16` port. These types come from `core::net`, and are also
+///   available without the `std` feature
+///
+/// Invalid values are rejected with an error of kind `Verify`. For `char` and `NonZero` types,
+/// the error is built using `FromExternalError` (so with the `GenericErrors` attribute, the error
+/// type must implement `FromExternalError<I, CharTryFromError>` or
+/// `FromExternalError<I, TryFromIntError>`).
+///
+/// ```rust
+/// # use nom_derive::*;
+/// # use std::net::Ipv4Addr;
+/// # use std::num::NonZeroU16;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S {
+///   enabled: bool,
+///   id: NonZeroU16,
+///   addr: Ipv4Addr,
+/// }
+///
+/// let input = b"\x01\x00\x2a\x7f\x00\x00\x01";
+/// let res = S::parse(input);
+/// # let id = NonZeroU16::new(42).unwrap();
+/// assert_eq!(res, Ok((&input[7..],S{enabled: true, id, addr: Ipv4Addr::LOCALHOST})));
+/// ```
+///
+/// ## Tuples
+///
+/// Tuples (up to 12 elements) are parsed by parsing all elements in order, using the endianness
+/// of the field. They can also be used as items of collections, for ex. with `Count`:
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(LittleEndian)]
+/// struct S {
+///   a: (u8, u16, u16),
+///   #[nom(Count="a.0")]
+///   b: Vec<(u8, u16)>,
+/// }
+///
+/// let input = b"\x01\x02\x00\x03\x00\x04\x05\x00";
+/// let res = S::parse(input);
+/// assert_eq!(res, Ok((&input[8..],S{a:(1, 2, 3), b:vec![(4, 5)]})));
+/// # }
+/// ```
+///
+/// ## Smart pointers and recursive types
+///
+/// With the `alloc` feature, `Box<T>`, `Rc<T>` and `Arc<T>` are parsed using the parser of `T`.
+/// `Box<[T]>` is parsed like `Vec<T>`, and `Cow<'a, [u8]>` borrows all remaining bytes.
+///
+/// Boxes can be used to define recursive types (for ex. expression trees or nested containers):
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct Node {
+///   value: u8,
+///   has_next: u8,
+///   #[nom(Cond="has_next != 0")]
+///   next: Option<Box<Node>>,
+/// }
+///
+/// let input = b"\x01\x01\x02\x00";
+/// let (_, node) = Node::parse(input).expect("parsing failed");
+/// assert_eq!(node.next.map(|n| n.value), Some(2));
+/// # }
+/// ```
+///
 /// ## Count
 ///
 /// The `Count(n)` attribute can be used to specify the number of items to parse.
 ///
 /// Notes:
-///   - the subparser is inferred as usual (item type must be `Vec< ... >`)
+///   - the subparser is inferred as usual, from the item type of the collection (see
+///     [Collections](#collections))
 ///   - the number of items (`n`) can be any expression, and will be cast to `usize`
 ///
 /// For ex:
@@ -346,7 +472,8 @@
 /// use this number to parse an expected number of items.
 ///
 /// Notes:
-///   - the subparser is inferred as usual (item type must be `Vec< ... >`)
+///   - the subparser is inferred as usual, from the item type of the collection (see
+///     [Collections](#collections))
 ///   - the length parser must return a number
 ///
 /// For ex:
@@ -368,6 +495,112 @@
 /// # }
 /// ```
 ///
+/// ## Collections
+///
+/// Fields with the `Count`, `LengthCount` or `Cond` attributes can use any type implementing
+/// the [`FromParsedIter`](crate::FromParsedIter) trait. It is implemented for `Option`, `Vec`,
+/// `VecDeque`, `LinkedList`, `Box<[T]>`, `BTreeSet` and `BTreeMap` (with the `alloc` feature),
+/// `HashMap` and `HashSet` (with the `std` feature), and for `smallvec::SmallVec`,
+/// `heapless::Vec` and `arrayvec::ArrayVec` (with the `smallvec`, `heapless` and `arrayvec`
+/// features).
+///
+/// The item parser is inferred from `<Type as FromParsedIter>::Item` (for ex. `u16` for
+/// `VecDeque<u16>`, or `T` for `SmallVec<[T; 4]>`), so it also works for user types and type
+/// aliases. For maps, items are `(key, value)` tuples.
+///
+/// If the parsed items do not fit in the collection (for ex. a fixed-capacity vector, or more
+/// than one item in an `Option`), the parser returns an error of kind `TooLarge`.
+///
+/// When [serializing](#serialization), the items are read using the
+/// [`SerializeIter`](crate::SerializeIter) trait, which is implemented for the same types.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// # use std::collections::VecDeque;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S {
+///   a: u8,
+///   #[nom(Count="a")]
+///   b: VecDeque<u16>,
+///   #[nom(Count="a")]
+///   c: Box<[u8]>,
+/// }
+/// #
+/// # let input = b"\x01\x12\x34\x56";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[4..],S{a:1, b:VecDeque::from(vec![0x1234]), c:Box::new([0x56])})));
+/// # }
+/// ```
+///
+/// The `Elem` attribute specifies the type of items explicitly, when it cannot be inferred from
+/// the type of the field: for ex. for a type alias used with `RepeatUntil`, or a generic type
+/// parameter. A generic parameter used this way is required to implement
+/// `FromParsedIter<Item = T>` instead of `Parse`.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S<C> {
+///   n: u8,
+///   #[nom(Count="n", Elem="u16")]
+///   items: C,
+/// }
+/// #
+/// # let input = b"\x01\x12\x34";
+/// # let res = <S<Vec<u16>>>::parse(input);
+/// # assert_eq!(res, Ok((&input[3..],S{n:1, items:vec![0x1234]})));
+/// # }
+/// ```
+///
+/// ## RepeatUntil and RepeatUntilTag
+///
+/// The `RepeatUntil` and `RepeatUntilTag` attributes parse items of a `Vec<T>` until a terminator
+/// is found, using `nom::multi::many_till`:
+///  - `RepeatUntil` takes a predicate (a closure or function taking a `&T`). The terminator is
+///    the first item matching the predicate.
+///  - `RepeatUntilTag` takes a constant pattern. Before each item, the input is checked for the
+///    pattern.
+///
+/// The `Terminator` attribute specifies what to do with the terminator:
+///  - `"include"`: consume the terminator item, and add it to the `Vec` (default for
+///    `RepeatUntil`, not supported for `RepeatUntilTag`)
+///  - `"consume"`: consume the terminator, but do not store it (default for `RepeatUntilTag`)
+///  - `"keep"`: leave the terminator in input, so the next fields can parse it
+///
+/// If the input ends before the terminator, an `Incomplete` error is returned (or an error, if
+/// the `Complete` attribute is used).
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct Opt{
+///     pub kind: u8,
+/// }
+///
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(RepeatUntil = "|o| o.kind == 0", Terminator = "consume")]
+///     pub options: Vec<Opt>,
+///     #[nom(RepeatUntilTag(b"\r\n"))]
+///     pub data: Vec<u8>,
+/// }
+/// #
+/// # let input = b"\x01\x02\x00ab\r\n";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[7..],S{options:vec![Opt{kind:1}, Opt{kind:2}], data:b"ab".to_vec()})));
+/// # }
+/// ```
+///
 /// ## Tag
 ///
 /// The `Tag(value)` attribute is used to parse a constant value (or "magic").
@@ -390,6 +623,39 @@
 /// # assert_eq!(res, Ok((&input[7..],S{tag: b"TAG", a:1, b:0x1234})));
 /// ```
 ///
+/// `Tag` can also be used as a top-level attribute, to check the value before the first field
+/// without storing it. The top-level `Magic` attribute is similar, for an integer: the type is
+/// given by the suffix of the literal (for ex. `0xA1B2C3D4u32`), and the value is read using
+/// the endianness of the object. A wrong value returns an error of kind `Tag` or `Verify`.
+///
+/// When [serializing](#serialization), these values are written before the first field.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(Tag(b"\x89PNG"))]
+/// struct Png {
+///   version: u8,
+/// }
+///
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(Magic = 0xA1B2C3D4u32, LittleEndian)]
+/// struct Pcap {
+///   major: u16,
+///   minor: u16,
+/// }
+/// #
+/// # let input = b"\x89PNG\x01";
+/// # let res = Png::parse(input);
+/// # assert_eq!(res, Ok((&input[5..],Png{version: 1})));
+/// # let input = b"\xd4\xc3\xb2\xa1\x02\x00\x04\x00";
+/// # let res = Pcap::parse(input);
+/// # assert_eq!(res, Ok((&input[8..],Pcap{major: 2, minor: 4})));
+/// ```
+///
 /// ## Take
 ///
 /// The `Take="n"` attribute can be used to take `n` bytes of input.
@@ -414,6 +680,270 @@
 /// # assert_eq!(res, Ok((&input[3..],S{a:1, b:&[0x12]})));
 /// ```
 ///
+/// ## Size
+///
+/// The `Size="n"` attribute parses the field from a window of exactly `n` bytes of input, then
+/// advances the input by `n` bytes. This is useful for TLV records, where the value may not use
+/// all bytes, or ends with a list of items:
+///   - `Vec` fields (and other parsers stopping at the end of input) stop at the end of the window
+///   - bytes left in the window are skipped. With the `StrictSize` attribute, an `Eof` error is
+///     returned instead
+///
+/// The size (`n`) can be any expression, and will be cast to `usize`. When
+/// [serializing](#serialization), the field is padded with zeros to `n` bytes, and
+/// `ValueOutOfRange` is returned if the field is too large (or does not fill the window exactly,
+/// with `StrictSize`).
+///
+/// For ex:
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct Body {
+///   version: u8,
+///   items: Vec<u16>,
+/// }
+///
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S {
+///   len: u8,
+///   #[nom(Size="len - 1")]
+///   body: Body,
+///   tail: u8,
+/// }
+/// #
+/// # let input = b"\x04\x01\x12\x34\xff";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[5..],S{len: 4, body: Body{version: 1, items: vec![0x1234]}, tail: 0xff})));
+/// # }
+/// ```
+///
+/// ## LengthOf and CountOf
+///
+/// The `LengthOf(field)` and `CountOf(field)` attributes mark a field as computed from another
+/// field: the length in bytes of the serialized field, or the number of elements of the field
+/// (which must have a `len()` method).
+///
+/// When parsing, the value is checked once both fields have been parsed, and a `Verify` error
+/// is returned if it does not match. When [serializing](#serialization), the value is computed
+/// and written automatically, ignoring the value stored in the struct. The length of a field is
+/// patched after writing the target field, so the length field can appear before the target.
+///
+/// For ex:
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(Serialize)]
+/// struct S<'a> {
+///   #[nom(CountOf(v))]
+///   n: u8,
+///   #[nom(LengthOf(data))]
+///   len: u16,
+///   #[nom(Count="n")]
+///   v: Vec<u16>,
+///   #[nom(Take="len")]
+///   data: &'a [u8],
+/// }
+/// #
+/// # let input = b"\x01\x00\x02\x12\x34ab";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[7..],S{n: 1, len: 2, v: vec![0x1234], data: b"ab"})));
+///
+/// let s = S { n: 0, len: 0, v: vec![0x1234], data: b"ab" };
+/// let mut out = Vec::new();
+/// s.serialize(&mut out).expect("serialization failed");
+/// assert_eq!(out, b"\x01\x00\x02\x12\x34ab");
+/// # }
+/// ```
+///
+/// ## Length-prefixed values
+///
+/// The [`LengthValue<L, T>`](crate::LengthValue) type reads a length of type `L`, then parses a
+/// value of type `T` from exactly `length` bytes (like [`LengthData`](crate::LengthData), which
+/// only returns the raw bytes). Both the length and the value use the endianness of the field.
+///
+/// Bytes left in the window after parsing `T` are skipped. To reject them instead (with an
+/// `Eof` error), use [`StrictLengthValue<L, T>`](crate::StrictLengthValue).
+///
+/// When [serializing](#serialization), the length is computed from the serialized value.
+///
+/// For ex:
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct Header {
+///   version: u8,
+/// }
+///
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S {
+///   header: LengthValue<u8, Header>,
+///   items: StrictLengthValue<u16, Vec<u16>>,
+/// }
+/// #
+/// # let input = b"\x02\x01\xff\x00\x02\x12\x34";
+/// # let res = S::parse(input);
+/// # let expected = S{header: LengthValue::new(Header{version: 1}), items: LengthValue::new(vec![0x1234])};
+/// # assert_eq!(res, Ok((&input[7..], expected)));
+/// # }
+/// ```
+///
+/// ## String fields
+///
+/// By default, `String` is read using a big-endian `u32` length prefix. The following attributes
+/// can be used for other formats, with fields of type `&str`, `String`, `&CStr`, `&[u8]` or
+/// `Vec<u8>` (see [`StringType`](crate::strings::StringType)):
+///  - `NulTerminated`: the string is terminated by a NUL byte, which is consumed
+///  - `Padded = n`: the string is stored in exactly `n` bytes, padded using `PadByte` (default:
+///    0). The `Trim` attribute specifies how the value is extracted: `"padding"` removes the
+///    trailing padding bytes (default), `"nul"` stops at the first NUL byte (C-style), and
+///    `"keep"` keeps all bytes
+///  - `LengthPrefix = "type"`: the string is prefixed by its length in bytes, read using the
+///    provided type (the endianness of the field is used)
+///
+/// Strings are checked to be valid UTF-8 when required by the type. With `GenericErrors`, the
+/// error type must implement `FromExternalError<&[u8], Utf8Error>`. A `&CStr` value ends at the
+/// first NUL byte, which must be found in the field (whatever the `Trim` policy).
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S<'a>{
+///     #[nom(NulTerminated)]
+///     pub name: &'a str,
+///     #[nom(Padded = 6, PadByte = 0x20)]
+///     pub label: String,
+///     #[nom(LengthPrefix = "u8")]
+///     pub pascal: String,
+/// }
+/// #
+/// # let input = b"abc\x00de    \x02fg";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[13..],S{name:"abc", label:"de".to_string(), pascal:"fg".to_string()})));
+/// # }
+/// ```
+///
+/// ## String encodings
+///
+/// The `Encoding` attribute decodes a `String` field stored in another encoding than UTF-8:
+///  - `utf16le`, `utf16be`: UTF-16, little or big-endian
+///  - `utf16`: UTF-16, using the endianness of the field (including `SetEndian`)
+///  - `latin1`: ISO-8859-1
+///  - `windows-1252` (or `cp1252`)
+///
+/// The length of the string must be given using one of the following attributes:
+///  - `Take = n`: the length in bytes
+///  - `Count = n`: the length in code units (for ex. 2 bytes per unit for UTF-16)
+///  - `NulTerminated`: the string is terminated by a NUL code unit, which is consumed
+///  - `LengthPrefix = "type"`: the string is prefixed by its length in bytes
+///
+/// Decoding errors (for ex. an unpaired UTF-16 surrogate) are reported using `MapRes`. With
+/// `GenericErrors`, the error type must implement
+/// `FromExternalError<&[u8], EncodingError>` (see [`EncodingError`](crate::strings::EncodingError)).
+/// When serializing, characters that cannot be represented return a `ValueOutOfRange` error.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(LittleEndian)]
+/// struct UsbStringDescriptor{
+///     pub b_length: u8,
+///     pub b_descriptor_type: u8,
+///     #[nom(Encoding = "utf16", Take = "b_length - 2")]
+///     pub string: String,
+/// }
+/// #
+/// # let input = b"\x06\x03h\x00i\x00";
+/// # let res = UsbStringDescriptor::parse(input);
+/// # assert_eq!(res, Ok((&input[6..],UsbStringDescriptor{b_length:6, b_descriptor_type:3, string:"hi".to_string()})));
+/// # }
+/// ```
+///
+/// ## Variable-length integers
+///
+/// The `Varint` attribute reads an integer using a variable-length encoding. The field type can
+/// be any integer type: if the decoded value does not fit, a `TooLarge` error is returned.
+///
+/// Supported encodings are:
+///  - `leb128`: unsigned LEB128, see [`Leb128`](crate::Leb128)
+///  - `sleb128`: signed LEB128, see [`Sleb128`](crate::Sleb128)
+///  - `zigzag`: zig-zag encoding and LEB128 (protobuf `sint`), see [`ZigZag`](crate::ZigZag)
+///  - `quic`: QUIC variable-length integers, see [`QuicVarInt`](crate::QuicVarInt)
+///  - `ber`: ASN.1 BER definite length, see [`BerLength`](crate::BerLength)
+///
+/// The wrapper types implement `Parse` (and `ToUsize` for unsigned encodings), so they can also
+/// be used directly, for example in `LengthCount` or `LengthData`.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(Varint = "leb128")]
+///     pub len: u32,
+///     #[nom(Count = "len")]
+///     pub v: Vec<u8>,
+///     #[nom(LengthCount = "<QuicVarInt<u8>>::parse")]
+///     pub w: Vec<u8>,
+/// }
+/// #
+/// # let input = b"\x01\xaa\x01\xbb";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[4..],S{len:1, v:vec![0xaa], w:vec![0xbb]})));
+/// # }
+/// ```
+///
+/// ## Odd-width and exotic numbers
+///
+/// The `Uint24`, `Int24` and `Uint48` attributes read an integer from 3 or 6 bytes, using the
+/// endianness of the field, and store it in a standard integer type. If the value does not fit,
+/// a `TooLarge` error is returned.
+///
+/// The [`numbers`](crate::numbers) module also provides types implementing `Parse` and
+/// `Serialize`:
+///  - [`U24`](crate::U24), [`I24`](crate::I24) and [`U48`](crate::U48): 24-bit and 48-bit
+///    integers (they also implement `ToUsize`, so they can be used in `LengthData`; negative
+///    `I24` values give a length of 0)
+///  - [`F16`](crate::F16) and [`BF16`](crate::BF16): half-precision and `bfloat16` floats
+///  - [`Bcd<N>`](crate::Bcd): packed BCD number stored in `N` bytes
+///  - [`Fixed<T, FRAC>`](crate::Fixed): fixed-point number with `FRAC` fractional bits
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(Uint24)]
+///     pub len: u32,
+///     #[nom(Int24, LittleEndian)]
+///     pub delta: i32,
+///     pub date: Bcd<2>,
+/// }
+/// #
+/// # let input = b"\x00\x01\x00\xff\xff\xff\x10\x17";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[8..],S{len:256, delta:-1, date:Bcd(1017)})));
+/// ```
+///
 /// ## Default parsing function
 ///
 /// If a field with type `T` is not a primitive or known type, the generated parser is
@@ -659,12 +1189,37 @@
 /// # assert_eq!(res, Ok((&input[2..],S{a:1,b:"0".to_string()})));
 /// ```
 ///
+/// ## Peek
+///
+/// The `Peek` attribute parses the field without consuming input: the value is stored in the
+/// field, but the input position is not modified, so the following fields read the same bytes
+/// again.
+///
+/// This is useful to look at a version or type byte that is also part of the next field.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(Peek)]
+///     pub version: u8,
+///     pub header: u16,
+/// }
+/// #
+/// # let input = b"\x01\x23";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[2..],S{version:1, header:0x0123})));
+/// ```
+///
 /// ## Conditional Values
 ///
 /// The `Cond` custom attribute allows for specifying a condition.
 /// The generated parser will use the `cond!` combinator, which calls the
 /// child parser only if the condition is met.
-/// The type with this attribute must be an `Option` type.
+/// The type with this attribute is usually an `Option` type, but can be any
+/// [collection](#collections) (for ex. an empty `Vec` if the condition is not met).
 ///
 /// ```rust
 /// # use nom_derive::*;
@@ -776,6 +1331,51 @@
 /// # assert!(res.is_err());
 /// ```
 ///
+/// ## Checksum
+///
+/// The `Checksum(algorithm, range)` attribute verifies that the value of the field is the
+/// checksum of a range of bytes of the struct. If the value does not match, a `Verify` error is
+/// returned.
+///
+/// The algorithm can be one of the built-in functions from the [checksum](crate::checksum)
+/// module (`crc32`, `adler32`, `internet`), or any function taking a `&[u8]` and returning a
+/// value of the same type as the field.
+///
+/// The range is optional, and can be given using fields or offsets:
+///   - if the range is not given, it covers all bytes from the start of the struct, up to the
+///     checksum field (excluded)
+///   - `a..c` covers all bytes from the start of field `a` to the start of field `c`
+///   - `a..=c` covers all bytes from the start of field `a` to the end of field `c`
+///   - `4..12` covers bytes 4 to 11, offsets being relative to the start of the struct
+///   - bounds can be mixed or omitted: `2..=c`, `a..` (up to the checksum field)
+///
+/// The verification is done once the checksum and all the fields of the range have been parsed,
+/// so the checksum can be stored before or after the data.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     pub a: u16,
+///     pub b: u16,
+///     #[nom(Checksum(crc32))] // covers a and b
+///     pub crc: u32,
+///     #[nom(Checksum(internet, a..=b))]
+///     pub checksum: u16,
+/// }
+/// #
+/// # let input = b"\x00\x01\x00\x02\xce\x88\xd4\x07\xff\xfc";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[10..], S{a: 1, b: 2, crc: 0xce88d407, checksum: 0xfffc})));
+/// # let res = S::parse(b"\x00\x01\x00\x03\xce\x88\xd4\x07\xff\xfc");
+/// # assert!(res.is_err());
+/// ```
+///
+/// Note: checksums are not computed when [serializing](#serialization), the value of the field
+/// is written as-is.
+///
 /// ## PreExec
 ///
 /// The `PreExec` custom attribute executes the provided code before parsing
@@ -893,6 +1493,117 @@
 /// # assert_eq!(res, Ok((&input[5..],S{a:1, b:2})));
 /// ```
 ///
+/// ## AtOffset
+///
+/// The `AtOffset` attribute (or its alias `Pointer`) parses the value at the specified offset,
+/// instead of the current position. The offset is relative to the start of the original parser
+/// input, and the current position is not modified: parsing continues after the previous field.
+///
+/// If the field has type `Option<T>`, an offset of 0 is considered as a null pointer, and the
+/// value is `None`. Otherwise, a value of type `T` is parsed at the offset.
+///
+/// The base of the offset can be changed using `OffsetBase`, for example to use an input
+/// passed using `ExtraArgs`.
+///
+/// If the offset is beyond the end of the base input, an `Eof` error is raised.
+///
+/// Expected value: a valid Rust value (immediate value, or expression)
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     pub offset: u8,
+///     #[nom(AtOffset = "offset")]
+///     pub a: u16,
+///     #[nom(Pointer = "0")]
+///     pub b: Option<u8>,
+///     pub c: u8,
+/// }
+/// #
+/// # let input = b"\x03\x00\xff\x12\x34";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[2..],S{offset:3, a:0x1234, b:None, c:0})));
+/// ```
+///
+/// ## Bit fields
+///
+/// The `Bits = n` attribute reads the field from the next `n` bits of input, instead of a number
+/// of bytes. Consecutive bit fields are grouped, and the group is read at once: the total number
+/// of bits in a group must be a multiple of 8, so the input is always byte-aligned when the next
+/// (non-bit) field is parsed. An unaligned group is rejected at compile time.
+///
+/// Bit fields can have type `bool` (1 bit), or any integer type, as long as `n` is not larger
+/// than the type width. Signed integers are sign-extended.
+///
+/// If `Bits` is used as a top-level attribute (without value), all fields of the struct are bit
+/// fields. The width of each field is the width of its type, unless specified.
+///
+/// Bits are read starting from the most significant bit of each byte (network order). This can
+/// be changed using `BitOrder = "lsb"` (as a top-level attribute, or on the first field of a
+/// group). A different order on a later field of the same group is an error.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(Bits = 4)]
+///     pub version: u8,
+///     #[nom(Bits = 4)]
+///     pub ihl: u8,
+///     #[nom(Bits = 3)]
+///     pub flags: u8,
+///     #[nom(Bits = 13)]
+///     pub fragment_offset: u16,
+///     pub ttl: u8,
+/// }
+/// #
+/// # let input = b"\x45\x40\x01\x40";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[4..],S{version:4, ihl:5, flags:2, fragment_offset:1, ttl:64})));
+/// ```
+///
+/// ## Bitflags
+///
+/// With the `bitflags` feature, the `Flags` attribute reads a type generated by the
+/// [`bitflags`](https://docs.rs/bitflags) crate. The underlying integer is read using the
+/// endianness of the field, and the value is built according to the policy given as argument:
+///  - `"truncate"` (default): unknown bits are removed
+///  - `"retain"`: unknown bits are kept
+///  - `"strict"`: unknown bits are rejected, with a `MapOpt` error
+///
+/// `Flags` can be combined with `Bits` to read the flags from a bit field.
+///
+/// ```rust
+/// # #[cfg(feature = "bitflags")] {
+/// # use nom_derive::*;
+/// use bitflags::bitflags;
+///
+/// bitflags! {
+///     # #[derive(Debug,PartialEq)] // for assert_eq!
+///     pub struct TcpFlags: u8 {
+///         const SYN = 0x02;
+///         const ACK = 0x10;
+///     }
+/// }
+///
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// struct S{
+///     #[nom(Flags = "strict")]
+///     pub flags: TcpFlags,
+/// }
+/// #
+/// # let input = b"\x12";
+/// # let res = S::parse(input);
+/// # assert_eq!(res, Ok((&input[1..],S{flags: TcpFlags::SYN | TcpFlags::ACK})));
+/// # }
+/// ```
+///
 /// # Deriving parsers for `Enum`
 ///
 /// The `Nom` attribute can also used to generate parser for `Enum` types.
@@ -1007,6 +1718,27 @@
 /// If the `_` selector is not the last variant, the generated code will use it
 /// as the last match to avoid unreachable code.
 ///
+/// To preserve unknown values, the default variant can be marked with the `Unknown` attribute
+/// (the `_` selector is then implied). The first field of the variant stores the value of the
+/// selector, and the optional second field (`&[u8]` or `Vec<u8>`) stores the remaining bytes of
+/// input. When serializing, only the remaining bytes are written.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(Selector="u8")]
+/// pub enum U2<'a>{
+///     #[nom(Selector="0")] Field1(u32),
+///     #[nom(Unknown)] Other(u8, &'a [u8]),
+/// }
+/// #
+/// # let input = b"\x00\x00\x00\x02";
+/// # let res = U2::parse(input, 123);
+/// # assert_eq!(res, Ok((&input[4..],U2::Other(123, input))));
+/// ```
+///
 /// ## Special case: specifying parsers for fields
 ///
 /// Sometimes, an unnamed field requires a custom parser. In that case, the
@@ -1097,6 +1829,29 @@
 ///
 /// For ex, `U3::parse(b"\x02")` will return `Ok((&b""[..],U3::B))`.
 ///
+/// By default, an error is returned if the value does not match any variant. To accept unknown
+/// values, one variant with a single field of type `ty` can be marked with the `Unknown`
+/// attribute: it will store the value. In that case, the values of the other variants are read
+/// from their discriminants.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[repr(u8)]
+/// #[derive(Eq,Nom)]
+/// pub enum Code{
+///     A = 1,
+///     B = 2,
+///     #[nom(Unknown)]
+///     Other(u8),
+/// }
+/// #
+/// # let empty : &[u8] = b"";
+/// # assert_eq!(Code::parse(b"\x02"), Ok((empty,Code::B)));
+/// # assert_eq!(Code::parse(b"\x07"), Ok((empty,Code::Other(7))));
+/// ```
+///
 /// ## Input Name
 ///
 /// Internally, the parser will use a variable to follow the input.
@@ -1258,6 +2013,165 @@
 /// - The error type must implement `nom::error::ParseError<&[u8]>`
 /// - All subparsers must return compatible error types
 ///
+/// ## Error context
+///
+/// The `Context` top-level attribute wraps each field parser using `nom::error::context`, with the
+/// name `Struct.field` (`Enum::Variant.field` for enum variants, and the index of the field for
+/// tuple structs). Contexts of nested structs also using this attribute are stacked, so an error
+/// type recording contexts (like `VerboseError`) gives the full path of the field that failed.
+///
+/// For `Count` and `LengthCount` fields, the index of the element that failed is also added
+/// (for ex. `Header.options`, `[3]`, `TcpOption.len`).
+///
+/// With `GenericErrors`, the error type must also implement `nom::error::ContextError` and
+/// [`IndexContextError`](crate::collections::IndexContextError). The default implementation of
+/// `IndexContextError` adds the index as a context, but since contexts must be static strings,
+/// indices larger than 255 are recorded as `[..]`: implement its `add_index` method to record
+/// any index. The default error type ignores contexts.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// # use nom::error::{ContextError, ErrorKind, ParseError};
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # struct PathError(Vec<&'static str>);
+/// # impl<I> ParseError<I> for PathError {
+/// #     fn from_error_kind(_: I, _: ErrorKind) -> Self { PathError(vec![]) }
+/// #     fn append(_: I, _: ErrorKind, other: Self) -> Self { other }
+/// # }
+/// # impl<I> ContextError<I> for PathError {
+/// #     fn add_context(_: I, ctx: &'static str, mut other: Self) -> Self {
+/// #         other.0.insert(0, ctx);
+/// #         other
+/// #     }
+/// # }
+/// # impl<I> collections::IndexContextError<I> for PathError {}
+/// #[derive(Nom)]
+/// #[nom(Context, GenericErrors)]
+/// pub struct Header {
+///     #[nom(Verify = "*len > 0")]
+///     pub len: u8,
+/// }
+///
+/// #[derive(Nom)]
+/// #[nom(Context, GenericErrors)]
+/// pub struct Packet {
+///     pub header: Header,
+/// }
+/// #
+/// # let res: nom::IResult<_, _, PathError> = Packet::parse(b"\x00");
+/// # assert_eq!(res.err(), Some(nom::Err::Error(PathError(vec!["Packet.header", "Header.len"]))));
+/// ```
+///
+/// # Generic Input
+///
+/// By default, the derived parsers only accept `&[u8]` as input.
+///
+/// The `GenericInput` attribute changes the generated functions to have a generic type parameter
+/// for the input type, bounded by [InputSlice](crate::InputSlice) (`nom::Input<Item = u8>`).
+/// This allows using any `nom` input made of bytes, for example `nom_locate::LocatedSpan<&[u8]>`,
+/// or a custom input type:
+///
+/// ```rust
+/// # use nom_derive::*;
+/// # use nom::IResult;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(GenericInput)]
+/// pub struct S {
+///     pub a: u32,
+///     #[nom(AlignBefore(8))]
+///     pub b: u16,
+/// }
+/// #
+/// # let input = b"\x00\x00\x00\x01\x00\x00\x00\x00\x12\x34";
+/// # let res: IResult<&[u8], S> = S::parse(input);
+/// # assert_eq!(res, Ok((&input[10..], S{a: 1, b: 0x1234})));
+/// ```
+/// will generate the following code signature (simplified):
+/// ```rust,ignore
+/// impl <NomInput> Parse <NomInput, nom::error::Error<NomInput>> for S
+/// where
+///     NomInput: InputSlice
+/// {
+///     fn parse_be(orig_i : NomInput) -> IResult <NomInput, Self>
+///     {
+///         ...
+///     }
+/// }
+/// ```
+///
+/// Alignment, `Move` and `MoveAbs` compute positions using the length of the input, so they work
+/// for all input types.
+///
+/// This attribute has the following requirements:
+/// - All subparsers must be generic over the input type. For example, `&[u8]` fields (using
+///   `Take` or `Tag`), `String` fields and the `Debug` attribute cannot be used.
+/// - The `Checksum` and `Encoding` attributes require the input type to implement `nom::AsBytes`
+///
+/// This attribute can be combined with `GenericErrors`.
+///
+/// # Text input
+///
+/// The `StrInput` attribute changes the generated functions to parse a `&str` input, instead of
+/// `&[u8]`. This is useful for text protocols (commands, headers, etc.).
+///
+/// When parsing text, primitive integer types are read in decimal form (with an optional sign for
+/// signed types), and the endianness is ignored. The `Hex` attribute reads an integer in
+/// hexadecimal form instead. `Tag` can be used with string literals. Other parsers, for ex. from
+/// `nom::character`, can be used with the `Parse` attribute.
+///
+/// Like other parsers, number parsers are streaming: a number at the end of input returns
+/// `Incomplete`, since more digits could follow.
+///
+/// Number parsers are also available in the [text](crate::text) module. They also accept binary
+/// inputs, so `Hex` can be used for ASCII numbers in binary protocols.
+///
+/// ```rust
+/// # use nom_derive::*;
+/// # use nom::character::streaming::alpha1;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(StrInput)]
+/// struct Header<'a> {
+///     #[nom(Parse = "alpha1")]
+///     pub key: &'a str,
+///     #[nom(Tag("="))]
+///     pub eq: &'a str,
+///     pub value: u32,
+///     #[nom(Tag(","))]
+///     pub sep: &'a str,
+///     #[nom(Hex)]
+///     pub flags: u16,
+///     #[nom(Tag("\n"))]
+///     pub eol: &'a str,
+/// }
+/// #
+/// # let res = Header::parse("Size=1500,a0f\n");
+/// # assert_eq!(res, Ok(("", Header{key: "Size", eq: "=", value: 1500, sep: ",", flags: 0xa0f, eol: "\n"})));
+/// ```
+///
+/// Enums with a `&str` selector can match on string keywords:
+///
+/// ```rust
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug,PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(StrInput, Selector = "&str")]
+/// pub enum Command<'a> {
+///     #[nom(Selector("SIZE"))]
+///     Size(#[nom(Tag(" "))] &'a str, u64),
+///     #[nom(Selector("QUIT"))]
+///     Quit,
+/// }
+/// #
+/// # let res = Command::parse(" 42\n", "SIZE");
+/// # assert_eq!(res, Ok(("\n", Command::Size(" ", 42))));
+/// ```
+///
 /// # Generic Type Parameters
 ///
 /// `nom-derive` supports generic type parameters in the `struct` or `enum` definition.
@@ -1278,5 +2192,82 @@
 /// ```
 ///
 /// Generic type parameters can also be used with generic errors.
+///
+/// # Serialization
+///
+/// The `Serialize` top-level attribute makes the generator also implement the
+/// [Serialize](crate::Serialize) trait, which writes the object back to bytes. The generated
+/// serializer is the inverse of the parser: it follows the same attributes, so that parsing the
+/// serialized object gives back the same object.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// # use nom_derive::*;
+/// #
+/// # #[derive(Debug, PartialEq)] // for assert_eq!
+/// #[derive(Nom)]
+/// #[nom(Serialize)]
+/// struct S<'a> {
+///     #[nom(Tag(b"S"))]
+///     tag: &'a [u8],
+///     a: u16,
+///     #[nom(LittleEndian, AlignAfter(4))]
+///     b: u16,
+///     #[nom(LengthCount = "nom::number::complete::be_u8")]
+///     v: Vec<u16>,
+/// }
+///
+/// # let input = b"S\x00\x01\x02\x00\x00\x00\x00\x02\x00\x03\x00\x04";
+/// let s = S { tag: b"S", a: 1, b: 2, v: vec![3, 4] };
+/// let mut out = Vec::new();
+/// s.serialize(&mut out).expect("serialization failed");
+/// # assert_eq!(out, input);
+/// # assert_eq!(S::parse(&out), Ok((&b""[..], s)));
+/// # }
+/// ```
+///
+/// Values are written to a [SerializeOutput](crate::SerializeOutput): `Vec<u8>` (if the `alloc`
+/// feature is enabled), or [SliceOutput](crate::SliceOutput) to write into a `&mut [u8]`.
+///
+/// The endianness of fields is handled the same way as for parsers: the `serialize` method writes
+/// big-endian values, unless an endianness is specified for the object or the field.
+///
+/// Attributes are converted as follows:
+/// - `Tag` writes the tag, `Take` writes the bytes of the field
+/// - `Count` writes every item, and `Cond` writes the value if it is `Some`. The count or condition
+///   itself is not written, it is usually stored in another field. It is evaluated using the values
+///   of the other fields, and `ValueOutOfRange` is returned if the number of items does not match
+///   (unless it uses a `LengthOf` or `CountOf` field, or variables which are not fields, like
+///   `ExtraArgs`)
+/// - `LengthCount` writes the number of items then every item. The length parser must be a `nom`
+///   number parser (for ex. `be_u16`), so the length type can be inferred
+/// - `AlignBefore`, `AlignAfter`, `SkipBefore` and `SkipAfter` write padding (zero bytes)
+/// - `Value` and `Default` fields are not written
+/// - `LengthOf` and `CountOf` fields are computed from their target field
+/// - the selector of an enum is not written (it must be written by the caller, usually as a field
+///   of the parent struct), only the fields of the variant are written. For fieldless enums, the
+///   `repr` value is written.
+///
+/// Some attributes cannot be inverted: `Parse`, `Map`, `Into`, `Move` and `MoveAbs` will cause a
+/// compilation error. For fields using custom parsers, the `SerializeWith` attribute gives the
+/// function to use to serialize the field:
+///
+/// ```rust
+/// # use nom_derive::*;
+/// # use nom::number::complete::be_u8;
+/// #
+/// fn ser_flag<O: SerializeOutput + ?Sized>(v: &bool, out: &mut O) -> Result<(), SerializeError> {
+///     out.write_bytes(&[*v as u8])
+/// }
+///
+/// #[derive(Nom)]
+/// #[nom(Serialize)]
+/// struct S {
+///     #[nom(Parse = "nom::combinator::map(be_u8, |x| x != 0)", SerializeWith = "ser_flag")]
+///     flag: bool,
+/// }
+/// ```
+///
+/// The function is called with a reference to the field value, and the output.
 #[allow(non_snake_case)]
 pub mod Nom {}
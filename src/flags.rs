@@ -0,0 +1,5 @@
+//! Support for `bitflags` types, used by the `Flags` attribute (requires the `bitflags` feature)
+//!
+//! See the [docs](crate::docs::Nom#bitflags) for details.
+
+pub use bitflags::Flags;
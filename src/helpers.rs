@@ -1,7 +1,11 @@
+use crate::serialize::*;
 use crate::traits::*;
+use core::convert::{TryFrom, TryInto};
 use core::marker::PhantomData;
-use nom::error::ParseError;
-use nom::{IResult, ToUsize};
+use nom::bytes::streaming::take;
+use nom::error::{ErrorKind, ParseError};
+use nom::number::streaming::be_u8;
+use nom::{IResult, Input, Parser, ToUsize};
 
 #[derive(Debug, PartialEq)]
 pub struct LengthData<L, D> {
@@ -18,7 +22,7 @@ impl<L, D> LengthData<L, D> {
 
 impl<L, I, E> Parse<I, E> for LengthData<L, I>
 where
-    I: Clone + PartialEq + InputSlice,
+    I: Clone + PartialEq + ParseInput,
     E: ParseError<I>,
     L: Parse<I, E> + ToUsize,
 {
@@ -39,11 +43,492 @@ where
     }
 }
 
+impl<L, D> Serialize for LengthData<L, D>
+where
+    L: Serialize + TryFrom<usize>,
+    D: AsRef<[u8]>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize_be(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let data = self.data.as_ref();
+        let length = L::try_from(data.len()).map_err(|_| SerializeError::ValueOutOfRange)?;
+        length.serialize_be(out)?;
+        out.write_bytes(data)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let data = self.data.as_ref();
+        let length = L::try_from(data.len()).map_err(|_| SerializeError::ValueOutOfRange)?;
+        length.serialize_le(out)?;
+        out.write_bytes(data)
+    }
+}
+
 pub type LengthDataU8<'a> = LengthData<u8, &'a [u8]>;
 pub type LengthDataU16<'a> = LengthData<u16, &'a [u8]>;
 pub type LengthDataU32<'a> = LengthData<u32, &'a [u8]>;
 pub type LengthDataU64<'a> = LengthData<u64, &'a [u8]>;
 
+/// A value of type `T`, parsed from a window of input prefixed by its length (of type `L`)
+///
+/// The length prefix is read using the requested endianness, then `T` is parsed from exactly
+/// `length` bytes (using the same endianness). Bytes left in the window are skipped, unless
+/// `STRICT` is `true`: in that case, leftover bytes are rejected with an `Eof` error (see
+/// [`StrictLengthValue`]).
+///
+/// Since the window is complete, `Incomplete` errors from `T` are converted to errors.
+#[derive(Debug, PartialEq)]
+pub struct LengthValue<L, T, const STRICT: bool = false> {
+    l: PhantomData<L>,
+    pub value: T,
+}
+
+/// A [`LengthValue`] rejecting leftover bytes in the window
+pub type StrictLengthValue<L, T> = LengthValue<L, T, true>;
+
+/// Apply a parser to a window of exactly `size` bytes of input, and advance the input by `size`
+/// bytes
+///
+/// Since the window is complete, `Incomplete` errors from the parser are converted to errors
+/// (and `Vec` fields stop at the end of the window). Bytes left in the window are skipped, or
+/// rejected with an `Eof` error if `strict` is `true`.
+///
+/// This is the parser used by the `Size` attribute.
+pub fn sized<I, O, E, F>(
+    size: usize,
+    strict: bool,
+    mut parser: F,
+) -> impl FnMut(I) -> IResult<I, O, E>
+where
+    I: ParseInput,
+    E: ParseError<I>,
+    F: Parser<I, Output = O, Error = E>,
+{
+    move |i: I| {
+        let (rem, window) = take(size)(i)?;
+        let (leftover, value) = match parser.parse(window) {
+            Ok(res) => res,
+            Err(nom::Err::Incomplete(_)) => {
+                return Err(nom::Err::Error(E::from_error_kind(
+                    rem,
+                    ErrorKind::Complete,
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+        if strict && leftover.input_len() > 0 {
+            return Err(nom::Err::Error(E::from_error_kind(
+                leftover,
+                ErrorKind::Eof,
+            )));
+        }
+        Ok((rem, value))
+    }
+}
+
+/// Conversion of the value of a `Size` expression to `usize`
+///
+/// This is used by derived serializers, where fields are bound by reference.
+#[doc(hidden)]
+pub trait ToSize {
+    fn to_size(&self) -> usize;
+}
+
+impl<T: ToSize + ?Sized> ToSize for &T {
+    #[inline]
+    fn to_size(&self) -> usize {
+        (**self).to_size()
+    }
+}
+
+macro_rules! impl_to_size {
+    ( $($ty:ty),* ) => {
+        $(
+            impl ToSize for $ty {
+                #[inline]
+                fn to_size(&self) -> usize {
+                    *self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_to_size!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<L, T, const STRICT: bool> LengthValue<L, T, STRICT> {
+    pub const fn new(value: T) -> Self {
+        let l = PhantomData;
+        LengthValue { l, value }
+    }
+
+    fn parse_window<I, E, F>(i: I, length: L, parser: F) -> IResult<I, Self, E>
+    where
+        I: ParseInput,
+        E: ParseError<I>,
+        L: ToUsize,
+        F: FnMut(I) -> IResult<I, T, E>,
+    {
+        let (rem, value) = sized(length.to_usize(), STRICT, parser)(i)?;
+        Ok((rem, LengthValue::new(value)))
+    }
+}
+
+impl<L, T, I, E, const STRICT: bool> Parse<I, E> for LengthValue<L, T, STRICT>
+where
+    I: ParseInput,
+    E: ParseError<I>,
+    L: Parse<I, E> + ToUsize,
+    T: Parse<I, E>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, length) = L::parse(i)?;
+        Self::parse_window(rem, length, T::parse)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (rem, length) = L::parse_be(i)?;
+        Self::parse_window(rem, length, T::parse_be)
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (rem, length) = L::parse_le(i)?;
+        Self::parse_window(rem, length, T::parse_le)
+    }
+}
+
+impl<L, T, const STRICT: bool> LengthValue<L, T, STRICT>
+where
+    L: Serialize + TryFrom<usize>,
+    T: Serialize,
+{
+    /// Write a placeholder length, then the value, and patch the length
+    ///
+    /// The length type must have a fixed size (the encoded placeholder and real length must have
+    /// the same size), otherwise `ValueOutOfRange` is returned.
+    fn serialize_window<W, F, G>(
+        &self,
+        out: &mut W,
+        ser_l: F,
+        ser_t: G,
+    ) -> Result<(), SerializeError>
+    where
+        W: SerializeOutput + ?Sized,
+        F: Fn(&L, &mut SliceOutput) -> Result<(), SerializeError>,
+        G: FnOnce(&T, &mut W) -> Result<(), SerializeError>,
+    {
+        let to_length = |len: usize| L::try_from(len).map_err(|_| SerializeError::ValueOutOfRange);
+        let mut buf = [0u8; 16];
+        let mut prefix = SliceOutput::new(&mut buf);
+        ser_l(&to_length(0)?, &mut prefix)?;
+        let pos = out.position();
+        out.write_bytes(prefix.written())?;
+        let start = out.position();
+        ser_t(&self.value, out)?;
+        let length = out.position() - start;
+        let mut buf = [0u8; 16];
+        let mut prefix = SliceOutput::new(&mut buf);
+        ser_l(&to_length(length)?, &mut prefix)?;
+        if prefix.written().len() != start - pos {
+            return Err(SerializeError::ValueOutOfRange);
+        }
+        out.patch(pos, prefix.written())
+    }
+}
+
+impl<L, T, const STRICT: bool> Serialize for LengthValue<L, T, STRICT>
+where
+    L: Serialize + TryFrom<usize>,
+    T: Serialize,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize_window(out, |l, o| l.serialize(o), |t, o| t.serialize(o))
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize_window(out, |l, o| l.serialize_be(o), |t, o| t.serialize_be(o))
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize_window(out, |l, o| l.serialize_le(o), |t, o| t.serialize_le(o))
+    }
+}
+
+/// Unsigned integer, encoded using LEB128 (variable length, 7 bits per byte, least significant
+/// group first)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Leb128<T>(pub T);
+
+/// Signed integer, encoded using signed LEB128
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sleb128<T>(pub T);
+
+/// Signed integer, encoded using zig-zag and LEB128 (protobuf `sint32`/`sint64`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZigZag<T>(pub T);
+
+/// Unsigned integer, encoded using QUIC variable-length integers (RFC 9000, up to 62 bits)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuicVarInt<T>(pub T);
+
+/// Length, encoded using ASN.1 BER definite form (short or long form)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BerLength<T>(pub T);
+
+#[inline]
+fn too_large<I, E: ParseError<I>>(i: I) -> nom::Err<E> {
+    nom::Err::Error(E::from_error_kind(i, ErrorKind::TooLarge))
+}
+
+fn parse_leb128_raw<I, E>(i: I) -> IResult<I, u128, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    let mut value = 0u128;
+    let mut shift = 0;
+    let mut rem = i.clone();
+    loop {
+        let (r, b) = be_u8(rem)?;
+        let low = u128::from(b & 0x7f);
+        if shift >= 128 || (low << shift) >> shift != low {
+            return Err(too_large(i));
+        }
+        value |= low << shift;
+        shift += 7;
+        rem = r;
+        if b & 0x80 == 0 {
+            return Ok((rem, value));
+        }
+    }
+}
+
+fn parse_sleb128_raw<I, E>(i: I) -> IResult<I, i128, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    let mut value = 0i128;
+    let mut shift = 0;
+    let mut rem = i.clone();
+    loop {
+        let (r, b) = be_u8(rem)?;
+        let low = b & 0x7f;
+        if shift >= 128 || (shift == 126 && !matches!((low << 1) as i8 >> 1, -2..=1)) {
+            return Err(too_large(i));
+        }
+        value |= i128::from(low) << shift;
+        shift += 7;
+        rem = r;
+        if b & 0x80 == 0 {
+            if shift < 128 && b & 0x40 != 0 {
+                // sign extension
+                value |= -1i128 << shift;
+            }
+            return Ok((rem, value));
+        }
+    }
+}
+
+fn parse_be_uint_raw<I, E>(i: I, first: u64, len: usize) -> IResult<I, u64, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    let (rem, data) = take(len)(i)?;
+    let value = data
+        .iter_elements()
+        .fold(first, |acc, b| (acc << 8) | u64::from(b));
+    Ok((rem, value))
+}
+
+impl<T, I, E> Parse<I, E> for Leb128<T>
+where
+    T: TryFrom<u128>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = parse_leb128_raw(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, Leb128(v)))
+    }
+}
+
+impl<T, I, E> Parse<I, E> for Sleb128<T>
+where
+    T: TryFrom<i128>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = parse_sleb128_raw(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, Sleb128(v)))
+    }
+}
+
+impl<T, I, E> Parse<I, E> for ZigZag<T>
+where
+    T: TryFrom<i128>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = parse_leb128_raw(i.clone())?;
+        let v = ((v >> 1) as i128) ^ -((v & 1) as i128);
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, ZigZag(v)))
+    }
+}
+
+impl<T, I, E> Parse<I, E> for QuicVarInt<T>
+where
+    T: TryFrom<u64>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, b) = be_u8(i.clone())?;
+        let len = 1usize << (b >> 6);
+        let (rem, v) = parse_be_uint_raw(rem, u64::from(b & 0x3f), len - 1)?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, QuicVarInt(v)))
+    }
+}
+
+impl<T, I, E> Parse<I, E> for BerLength<T>
+where
+    T: TryFrom<u64>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, b) = be_u8(i.clone())?;
+        let (rem, v) = match b {
+            0..=0x7f => (rem, u64::from(b)),
+            // indefinite length, and reserved value
+            0x80 | 0xff => return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Verify))),
+            _ if b & 0x7f > 8 => return Err(too_large(i)),
+            _ => parse_be_uint_raw(rem, 0, usize::from(b & 0x7f))?,
+        };
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, BerLength(v)))
+    }
+}
+
+fn write_leb128<W: SerializeOutput + ?Sized>(
+    mut v: u128,
+    out: &mut W,
+) -> Result<(), SerializeError> {
+    loop {
+        let b = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            return out.write_bytes(&[b]);
+        }
+        out.write_bytes(&[b | 0x80])?;
+    }
+}
+
+impl<T> Serialize for Leb128<T>
+where
+    T: Copy + TryInto<u128>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let v = self
+            .0
+            .try_into()
+            .map_err(|_| SerializeError::ValueOutOfRange)?;
+        write_leb128(v, out)
+    }
+}
+
+impl<T> Serialize for Sleb128<T>
+where
+    T: Copy + TryInto<i128>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let mut v: i128 = self
+            .0
+            .try_into()
+            .map_err(|_| SerializeError::ValueOutOfRange)?;
+        loop {
+            let b = (v & 0x7f) as u8;
+            v >>= 7;
+            if (v == 0 && b & 0x40 == 0) || (v == -1 && b & 0x40 != 0) {
+                return out.write_bytes(&[b]);
+            }
+            out.write_bytes(&[b | 0x80])?;
+        }
+    }
+}
+
+impl<T> Serialize for ZigZag<T>
+where
+    T: Copy + TryInto<i128>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let v: i128 = self
+            .0
+            .try_into()
+            .map_err(|_| SerializeError::ValueOutOfRange)?;
+        write_leb128(((v << 1) ^ (v >> 127)) as u128, out)
+    }
+}
+
+impl<T> Serialize for QuicVarInt<T>
+where
+    T: Copy + TryInto<u64>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let v: u64 = self
+            .0
+            .try_into()
+            .map_err(|_| SerializeError::ValueOutOfRange)?;
+        match v {
+            0..=0x3f => out.write_bytes(&[v as u8]),
+            0x40..=0x3fff => out.write_bytes(&(v as u16 | 0x4000).to_be_bytes()),
+            0x4000..=0x3fff_ffff => out.write_bytes(&(v as u32 | 0x8000_0000).to_be_bytes()),
+            0x4000_0000..=0x3fff_ffff_ffff_ffff => {
+                out.write_bytes(&(v | 0xc000_0000_0000_0000).to_be_bytes())
+            }
+            _ => Err(SerializeError::ValueOutOfRange),
+        }
+    }
+}
+
+impl<T> Serialize for BerLength<T>
+where
+    T: Copy + TryInto<u64>,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let v: u64 = self
+            .0
+            .try_into()
+            .map_err(|_| SerializeError::ValueOutOfRange)?;
+        if v < 0x80 {
+            return out.write_bytes(&[v as u8]);
+        }
+        let bytes = v.to_be_bytes();
+        let skip = (v.leading_zeros() / 8) as usize;
+        out.write_bytes(&[0x80 | (8 - skip) as u8])?;
+        out.write_bytes(&bytes[skip..])
+    }
+}
+
+macro_rules! impl_varint_to_usize {
+    ( $($ty:ident),* ) => {
+        $(
+            impl<T: ToUsize> ToUsize for $ty<T> {
+                #[inline]
+                fn to_usize(&self) -> usize {
+                    self.0.to_usize()
+                }
+            }
+        )*
+    };
+}
+
+impl_varint_to_usize!(Leb128, QuicVarInt, BerLength);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +557,89 @@ mod tests {
             (b"" as &[u8], LengthData::new(b"ab" as &[u8]))
         );
     }
+
+    #[test]
+    fn test_serialize_length_data() {
+        let mut buf = [0u8; 4];
+        let mut out = SliceOutput::new(&mut buf);
+        LengthDataU8::new(b"abc").serialize(&mut out).unwrap();
+        assert_eq!(out.written(), b"\x03abc");
+        let mut out = SliceOutput::new(&mut buf);
+        let res = LengthDataU16::new(b"abc").serialize_le(&mut out);
+        assert_eq!(res, Err(SerializeError::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_parse_leb128() {
+        let res: IResult<_, _, Error<&[u8]>> = <Leb128<u32>>::parse(&b"\xe5\x8e\x26\x00"[..]);
+        assert_eq!(res, Ok((&b"\x00"[..], Leb128(624_485))));
+        let res: IResult<_, _, Error<&[u8]>> = <Leb128<u8>>::parse(&b"\x80\x02"[..]);
+        assert_eq!(
+            res,
+            Err(nom::Err::Error(Error::new(
+                &b"\x80\x02"[..],
+                ErrorKind::TooLarge
+            )))
+        );
+        let res: IResult<_, Leb128<u64>, Error<&[u8]>> = <Leb128<u64>>::parse(&b"\xff\xff"[..]);
+        assert_eq!(res, Err(nom::Err::Incomplete(nom::Needed::new(1))));
+        let input = [0xffu8; 20];
+        let res: IResult<_, _, Error<&[u8]>> = <Leb128<u128>>::parse(&input[..]);
+        assert!(matches!(res, Err(nom::Err::Error(_))));
+    }
+
+    #[test]
+    fn test_parse_sleb128_zigzag() {
+        let res: IResult<_, _, Error<&[u8]>> = <Sleb128<i32>>::parse(&b"\xc0\xbb\x78"[..]);
+        assert_eq!(res, Ok((&b""[..], Sleb128(-123_456))));
+        let res: IResult<_, _, Error<&[u8]>> = <Sleb128<i8>>::parse(&b"\x80\x7f"[..]);
+        assert_eq!(res, Ok((&b""[..], Sleb128(-128))));
+        let res: IResult<_, Sleb128<i8>, Error<&[u8]>> = <Sleb128<i8>>::parse(&b"\xff\x7e"[..]);
+        assert!(res.is_err());
+        let res: IResult<_, _, Error<&[u8]>> = <ZigZag<i64>>::parse(&b"\x03"[..]);
+        assert_eq!(res, Ok((&b""[..], ZigZag(-2))));
+        let res: IResult<_, _, Error<&[u8]>> = <ZigZag<i32>>::parse(&b"\xfe\xff\xff\xff\x0f"[..]);
+        assert_eq!(res, Ok((&b""[..], ZigZag(i32::MAX))));
+    }
+
+    #[test]
+    fn test_parse_quic_ber() {
+        let res: IResult<_, _, Error<&[u8]>> =
+            <QuicVarInt<u64>>::parse(&b"\xc2\x19\x7c\x5e\xff\x14\xe8\x8c"[..]);
+        assert_eq!(res, Ok((&b""[..], QuicVarInt(151_288_809_941_952_652))));
+        let res: IResult<_, _, Error<&[u8]>> = <QuicVarInt<u16>>::parse(&b"\x7b\xbd"[..]);
+        assert_eq!(res, Ok((&b""[..], QuicVarInt(15_293))));
+        let res: IResult<_, QuicVarInt<u8>, Error<&[u8]>> = <QuicVarInt<u8>>::parse(&b"\x9d"[..]);
+        assert_eq!(res, Err(nom::Err::Incomplete(nom::Needed::new(3))));
+        let res: IResult<_, _, Error<&[u8]>> = <BerLength<u32>>::parse(&b"\x82\x01\x00"[..]);
+        assert_eq!(res, Ok((&b""[..], BerLength(256))));
+        let res: IResult<_, _, Error<&[u8]>> = <BerLength<u32>>::parse(&b"\x7f"[..]);
+        assert_eq!(res, Ok((&b""[..], BerLength(127))));
+        let res: IResult<_, BerLength<u32>, Error<&[u8]>> = <BerLength<u32>>::parse(&b"\x80"[..]);
+        assert!(res.is_err());
+        let res: IResult<_, BerLength<u16>, Error<&[u8]>> =
+            <BerLength<u16>>::parse(&b"\x83\x01\x00\x00"[..]);
+        assert!(res.is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_serialize_varints() {
+        fn ser<T: Serialize>(v: T) -> Vec<u8> {
+            let mut out = Vec::new();
+            v.serialize(&mut out).unwrap();
+            out
+        }
+        assert_eq!(ser(Leb128(624_485u32)), b"\xe5\x8e\x26");
+        assert_eq!(ser(Sleb128(-123_456i32)), b"\xc0\xbb\x78");
+        assert_eq!(ser(Sleb128(63i8)), b"\x3f");
+        assert_eq!(ser(Sleb128(64i8)), b"\xc0\x00");
+        assert_eq!(ser(ZigZag(-2i64)), b"\x03");
+        assert_eq!(ser(QuicVarInt(15_293u16)), b"\x7b\xbd");
+        assert_eq!(ser(QuicVarInt(494_878_333u32)), b"\x9d\x7f\x3e\x7d");
+        assert_eq!(ser(BerLength(256usize)), b"\x82\x01\x00");
+        let mut out = Vec::new();
+        let res = QuicVarInt(u64::MAX).serialize(&mut out);
+        assert_eq!(res, Err(SerializeError::ValueOutOfRange));
+    }
 }
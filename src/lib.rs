@@ -108,11 +108,23 @@
 
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 
+pub mod bits;
+pub mod checksum;
+pub mod collections;
 pub mod docs;
+#[cfg(feature = "bitflags")]
+pub mod flags;
 mod helpers;
+pub mod numbers;
+mod serialize;
+pub mod strings;
+pub mod text;
 mod traits;
 
+pub use collections::{FromParsedIter, SerializeIter};
 pub use helpers::*;
+pub use numbers::{Bcd, Fixed, BF16, F16, I24, U24, U48};
+pub use serialize::*;
 pub use traits::*;
 
 pub use nom;
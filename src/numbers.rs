@@ -0,0 +1,544 @@
+//! Numeric types with an unusual encoding: 24-bit and 48-bit integers, half-precision floats,
+//! packed BCD and fixed-point values
+//!
+//! All types implement [`Parse`] and [`Serialize`], using the requested endianness. The integer
+//! types are also used by the `Uint24`, `Int24` and `Uint48` attributes, to read a field of a
+//! standard integer type (for ex. `u32`) from an odd number of bytes.
+//!
+//! See the [docs](crate::docs::Nom#odd-width-and-exotic-numbers) for details.
+
+use crate::serialize::{Serialize, SerializeError, SerializeOutput};
+use crate::traits::{InputSlice, Parse, ParseInput};
+use core::convert::{TryFrom, TryInto};
+use nom::bytes::streaming::take;
+use nom::error::{ErrorKind, ParseError};
+use nom::number::streaming::{be_i24, be_u16, be_u24, le_i24, le_u16, le_u24};
+use nom::{Err, IResult, Input, ToUsize};
+
+/// Unsigned 24-bit integer (3 bytes), stored in a `T` (`u32` by default)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U24<T = u32>(pub T);
+
+/// Signed 24-bit integer (3 bytes), stored in a `T` (`i32` by default)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct I24<T = i32>(pub T);
+
+/// Unsigned 48-bit integer (6 bytes), stored in a `T` (`u64` by default)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U48<T = u64>(pub T);
+
+/// IEEE 754 half-precision float (2 bytes), stored as a `f32`
+///
+/// Serializing rounds the value to the nearest half-precision float.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct F16(pub f32);
+
+/// Brain floating point (`bfloat16`, the 16 most significant bits of a `f32`), stored as a `f32`
+///
+/// Serializing rounds the value to the nearest `bfloat16`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct BF16(pub f32);
+
+/// Packed BCD (binary-coded decimal) number, with 2 digits per byte, stored in `N` bytes
+///
+/// In big-endian, the most significant byte comes first (the usual encoding). In little-endian,
+/// bytes are in reverse order. Invalid digits are rejected with a `Verify` error.
+///
+/// A `u64` has at most 20 digits, so serializing fails with `ValueOutOfRange` if `N` is larger
+/// than 10, or if the value does not fit in `N` bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bcd<const N: usize>(pub u64);
+
+/// Fixed-point number in Q format: a raw integer `T`, with `FRAC` fractional bits
+///
+/// For ex. `Fixed<i32, 16>` is a Q16.16 value, and `Fixed<u16, 8>` an unsigned Q8.8 value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<T, const FRAC: u32>(pub T);
+
+#[inline]
+fn too_large<I, E: ParseError<I>>(i: I) -> Err<E> {
+    Err::Error(E::from_error_kind(i, ErrorKind::TooLarge))
+}
+
+impl<T, I, E> Parse<I, E> for U24<T>
+where
+    T: TryFrom<u32>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = be_u24(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, U24(v)))
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = le_u24(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, U24(v)))
+    }
+}
+
+impl<T, I, E> Parse<I, E> for I24<T>
+where
+    T: TryFrom<i32>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = be_i24(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, I24(v)))
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = le_i24(i.clone())?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, I24(v)))
+    }
+}
+
+/// Read `len` bytes as an unsigned integer
+fn parse_uint_raw<I, E>(i: I, len: usize, big_endian: bool) -> IResult<I, u64, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    let (rem, data) = take(len)(i)?;
+    let value = if big_endian {
+        data.iter_elements()
+            .fold(0, |acc, b| (acc << 8) | u64::from(b))
+    } else {
+        data.iter_elements()
+            .enumerate()
+            .fold(0, |acc, (n, b)| acc | (u64::from(b) << (8 * n)))
+    };
+    Ok((rem, value))
+}
+
+impl<T, I, E> Parse<I, E> for U48<T>
+where
+    T: TryFrom<u64>,
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = parse_uint_raw(i.clone(), 6, true)?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, U48(v)))
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = parse_uint_raw(i.clone(), 6, false)?;
+        let v = T::try_from(v).map_err(|_| too_large(i))?;
+        Ok((rem, U48(v)))
+    }
+}
+
+/// Write the `len` least significant bytes of `v`
+fn write_uint_raw<W: SerializeOutput + ?Sized>(
+    v: u64,
+    len: usize,
+    big_endian: bool,
+    out: &mut W,
+) -> Result<(), SerializeError> {
+    if big_endian {
+        out.write_bytes(&v.to_be_bytes()[8 - len..])
+    } else {
+        out.write_bytes(&v.to_le_bytes()[..len])
+    }
+}
+
+impl<T> U24<T>
+where
+    T: Copy + TryInto<u32>,
+{
+    fn to_raw(self) -> Result<u64, SerializeError> {
+        match self.0.try_into() {
+            Ok(v) if v < (1 << 24) => Ok(u64::from(v)),
+            _ => Err(SerializeError::ValueOutOfRange),
+        }
+    }
+}
+
+impl<T> I24<T>
+where
+    T: Copy + TryInto<i32>,
+{
+    fn to_raw(self) -> Result<u64, SerializeError> {
+        match self.0.try_into() {
+            Ok(v) if (-(1 << 23)..(1 << 23)).contains(&v) => Ok(u64::from(v as u32)),
+            _ => Err(SerializeError::ValueOutOfRange),
+        }
+    }
+}
+
+impl<T> U48<T>
+where
+    T: Copy + TryInto<u64>,
+{
+    fn to_raw(self) -> Result<u64, SerializeError> {
+        match self.0.try_into() {
+            Ok(v) if v < (1 << 48) => Ok(v),
+            _ => Err(SerializeError::ValueOutOfRange),
+        }
+    }
+}
+
+macro_rules! impl_int_serialize {
+    ( $ty:ident, $into:ty, $len:expr ) => {
+        impl<T> Serialize for $ty<T>
+        where
+            T: Copy + TryInto<$into>,
+        {
+            fn serialize<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                self.serialize_be(out)
+            }
+            fn serialize_be<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                write_uint_raw(self.to_raw()?, $len, true, out)
+            }
+            fn serialize_le<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                write_uint_raw(self.to_raw()?, $len, false, out)
+            }
+        }
+    };
+}
+
+impl_int_serialize!(U24, u32, 3);
+impl_int_serialize!(I24, i32, 3);
+impl_int_serialize!(U48, u64, 6);
+
+impl<T: ToUsize> ToUsize for U24<T> {
+    #[inline]
+    fn to_usize(&self) -> usize {
+        self.0.to_usize()
+    }
+}
+
+/// Negative values are clamped to 0
+impl<T: Copy + Into<i64>> ToUsize for I24<T> {
+    #[inline]
+    fn to_usize(&self) -> usize {
+        self.0.into().max(0) as usize
+    }
+}
+
+impl<T: ToUsize> ToUsize for U48<T> {
+    #[inline]
+    fn to_usize(&self) -> usize {
+        self.0.to_usize()
+    }
+}
+
+impl F16 {
+    /// Build a value from its binary representation
+    pub fn from_bits(bits: u16) -> Self {
+        let sign = u32::from(bits & 0x8000) << 16;
+        let exp = u32::from((bits >> 10) & 0x1f);
+        let man = u32::from(bits & 0x3ff);
+        let v = match exp {
+            0 => {
+                // zero or subnormal: man * 2^-24
+                let v = man as f32 / (1 << 24) as f32;
+                return F16(if sign != 0 { -v } else { v });
+            }
+            0x1f => f32::from_bits(sign | 0x7f80_0000 | (man << 13)),
+            _ => f32::from_bits(sign | ((exp + 112) << 23) | (man << 13)),
+        };
+        F16(v)
+    }
+
+    /// Return the binary representation, rounding to the nearest half-precision float
+    pub fn to_bits(self) -> u16 {
+        let x = self.0.to_bits();
+        let sign = ((x >> 16) & 0x8000) as u16;
+        let exp = ((x >> 23) & 0xff) as i32;
+        let man = x & 0x7f_ffff;
+        if exp == 0xff {
+            // infinity or NaN (keeping NaN quiet)
+            let nan = if man != 0 {
+                0x200 | (man >> 13) as u16
+            } else {
+                0
+            };
+            return sign | 0x7c00 | nan;
+        }
+        let e = exp - 127 + 15;
+        if e >= 0x1f {
+            return sign | 0x7c00;
+        }
+        let (value, shift) = if e <= 0 {
+            if e < -10 {
+                return sign;
+            }
+            // subnormal: add the implicit bit
+            (man | 0x80_0000, (14 - e) as u32)
+        } else {
+            (((e as u32) << 23) | man, 13)
+        };
+        let mut h = value >> shift;
+        let rem = value & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        if rem > halfway || (rem == halfway && h & 1 == 1) {
+            // may carry into the exponent, which is the correct result
+            h += 1;
+        }
+        sign | h as u16
+    }
+}
+
+impl BF16 {
+    /// Build a value from its binary representation
+    pub fn from_bits(bits: u16) -> Self {
+        BF16(f32::from_bits(u32::from(bits) << 16))
+    }
+
+    /// Return the binary representation, rounding to the nearest `bfloat16`
+    pub fn to_bits(self) -> u16 {
+        let x = self.0.to_bits();
+        if self.0.is_nan() {
+            return ((x >> 16) | 0x40) as u16;
+        }
+        let rounded = x.wrapping_add(0x7fff + ((x >> 16) & 1));
+        (rounded >> 16) as u16
+    }
+}
+
+macro_rules! impl_float16 {
+    ( $ty:ident ) => {
+        impl<I, E> Parse<I, E> for $ty
+        where
+            I: InputSlice,
+            E: ParseError<I>,
+        {
+            fn parse(i: I) -> IResult<I, Self, E> {
+                Self::parse_be(i)
+            }
+            fn parse_be(i: I) -> IResult<I, Self, E> {
+                let (rem, bits) = be_u16(i)?;
+                Ok((rem, $ty::from_bits(bits)))
+            }
+            fn parse_le(i: I) -> IResult<I, Self, E> {
+                let (rem, bits) = le_u16(i)?;
+                Ok((rem, $ty::from_bits(bits)))
+            }
+        }
+
+        impl Serialize for $ty {
+            fn serialize<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                self.to_bits().serialize(out)
+            }
+            fn serialize_be<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                self.to_bits().serialize_be(out)
+            }
+            fn serialize_le<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                self.to_bits().serialize_le(out)
+            }
+        }
+    };
+}
+
+impl_float16!(F16);
+impl_float16!(BF16);
+
+impl<const N: usize> Bcd<N> {
+    fn parse_digits<I, E>(i: I, big_endian: bool) -> IResult<I, Self, E>
+    where
+        I: Input<Item = u8>,
+        E: ParseError<I>,
+    {
+        let (rem, data) = take(N)(i.clone())?;
+        let mut value = 0u64;
+        for n in 0..N {
+            let idx = if big_endian { n } else { N - 1 - n };
+            let b = data.iter_elements().nth(idx).unwrap_or(0);
+            let (hi, lo) = (b >> 4, b & 0xf);
+            if hi > 9 || lo > 9 {
+                return Err(Err::Error(E::from_error_kind(i, ErrorKind::Verify)));
+            }
+            value = value
+                .checked_mul(100)
+                .and_then(|v| v.checked_add(u64::from(hi * 10 + lo)))
+                .ok_or_else(|| too_large(i.clone()))?;
+        }
+        Ok((rem, Bcd(value)))
+    }
+
+    fn write_digits<W: SerializeOutput + ?Sized>(
+        &self,
+        big_endian: bool,
+        out: &mut W,
+    ) -> Result<(), SerializeError> {
+        // u64::MAX has 20 digits, which fit in 10 bytes
+        if N > 10 {
+            return Err(SerializeError::ValueOutOfRange);
+        }
+        let mut v = self.0;
+        let mut digits = [0u8; 10];
+        // least significant byte first
+        for n in 0..N {
+            let b = ((((v / 10) % 10) << 4) | (v % 10)) as u8;
+            v /= 100;
+            let idx = if big_endian { N - 1 - n } else { n };
+            digits[idx] = b;
+        }
+        if v != 0 {
+            return Err(SerializeError::ValueOutOfRange);
+        }
+        out.write_bytes(&digits[..N])
+    }
+}
+
+impl<I, E, const N: usize> Parse<I, E> for Bcd<N>
+where
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        Self::parse_digits(i, true)
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        Self::parse_digits(i, false)
+    }
+}
+
+impl<const N: usize> Serialize for Bcd<N> {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.write_digits(true, out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.write_digits(true, out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.write_digits(false, out)
+    }
+}
+
+impl<T, const FRAC: u32> Fixed<T, FRAC>
+where
+    T: Copy + Into<f64>,
+{
+    /// Return the value as a `f64`
+    pub fn to_f64(self) -> f64 {
+        self.0.into() / (1u64 << FRAC) as f64
+    }
+}
+
+impl<T, I, E, const FRAC: u32> Parse<I, E> for Fixed<T, FRAC>
+where
+    T: Parse<I, E>,
+    I: ParseInput,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = T::parse(i)?;
+        Ok((rem, Fixed(v)))
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = T::parse_be(i)?;
+        Ok((rem, Fixed(v)))
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (rem, v) = T::parse_le(i)?;
+        Ok((rem, Fixed(v)))
+    }
+}
+
+impl<T: Serialize, const FRAC: u32> Serialize for Fixed<T, FRAC> {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.0.serialize(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.0.serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.0.serialize_le(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f16_bits() {
+        for (bits, v) in [
+            (0x3c00, 1.0),
+            (0xc000, -2.0),
+            (0x7bff, 65504.0),
+            (0x0001, 5.960_464_5e-8),
+            (0x3555, 0.333_251_95),
+            (0x7c00, f32::INFINITY),
+        ] {
+            assert_eq!(F16::from_bits(bits), F16(v));
+            assert_eq!(F16(v).to_bits(), bits);
+        }
+        // rounding and overflow
+        assert_eq!(F16(1.0 / 3.0).to_bits(), 0x3555);
+        assert_eq!(F16(1e6).to_bits(), 0x7c00);
+        assert!(F16::from_bits(0x7e00).0.is_nan());
+    }
+
+    #[test]
+    fn test_bf16_bits() {
+        assert_eq!(BF16::from_bits(0x3f80), BF16(1.0));
+        assert_eq!(BF16(1.0).to_bits(), 0x3f80);
+        assert_eq!(BF16(1.0 / 3.0).to_bits(), 0x3eab);
+    }
+
+    #[test]
+    fn test_to_usize() {
+        assert_eq!(U24(0x12_3456u32).to_usize(), 0x12_3456);
+        assert_eq!(I24(0x1234).to_usize(), 0x1234);
+        assert_eq!(I24(-1).to_usize(), 0);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_bcd() {
+        use nom::error::Error;
+
+        let res: IResult<_, Bcd<2>, Error<_>> = Bcd::parse_le(&b"\x34\x12"[..]);
+        assert_eq!(res, Ok((&b""[..], Bcd(1234))));
+        let mut out = Vec::new();
+        Bcd::<2>(1234).serialize_le(&mut out).unwrap();
+        assert_eq!(out, b"\x34\x12");
+        assert_eq!(
+            Bcd::<2>(12345).serialize(&mut out),
+            Err(SerializeError::ValueOutOfRange)
+        );
+        let input = b"\x18\x44\x67\x44\x07\x37\x09\x55\x16\x15";
+        let res: IResult<_, Bcd<10>, Error<_>> = Bcd::parse_be(&input[..]);
+        assert_eq!(res, Ok((&b""[..], Bcd(u64::MAX))));
+        let mut out = Vec::new();
+        Bcd::<10>(u64::MAX).serialize(&mut out).unwrap();
+        assert_eq!(out, input);
+    }
+}
@@ -0,0 +1,493 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+    NonZeroU32, NonZeroU64, NonZeroU8,
+};
+
+#[rustversion::since(1.77)]
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+
+#[rustversion::before(1.77)]
+#[cfg(feature = "std")]
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use core::convert::TryFrom;
+
+/// Errors returned by serializers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializeError {
+    /// The output buffer is too small to hold the serialized object
+    BufferTooSmall,
+    /// A value cannot be represented in the serialized format (for ex. a length that does not fit
+    /// in its prefix)
+    ValueOutOfRange,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::BufferTooSmall => f.write_str("output buffer is too small"),
+            SerializeError::ValueOutOfRange => f.write_str("value out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SerializeError {}
+
+/// Output for serializers
+///
+/// Implementations are provided for `Vec<u8>` (if the `alloc` feature is enabled) and for
+/// [`SliceOutput`], which writes into a `&mut [u8]`.
+pub trait SerializeOutput {
+    /// Append `data` to the output
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), SerializeError>;
+
+    /// Return the number of bytes written to the output
+    fn position(&self) -> usize;
+
+    /// Overwrite bytes already written, starting at position `pos`
+    ///
+    /// This is used to fill fields which value is known only after serializing other fields (for
+    /// ex. a length).
+    fn patch(&mut self, pos: usize, data: &[u8]) -> Result<(), SerializeError>;
+
+    /// Append `count` copies of `byte` to the output
+    fn write_fill(&mut self, byte: u8, count: usize) -> Result<(), SerializeError> {
+        for _ in 0..count {
+            self.write_bytes(&[byte])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl SerializeOutput for Vec<u8> {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), SerializeError> {
+        self.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.len()
+    }
+
+    fn patch(&mut self, pos: usize, data: &[u8]) -> Result<(), SerializeError> {
+        let end = pos + data.len();
+        if end > self.len() {
+            return Err(SerializeError::BufferTooSmall);
+        }
+        self[pos..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn write_fill(&mut self, byte: u8, count: usize) -> Result<(), SerializeError> {
+        self.resize(self.len() + count, byte);
+        Ok(())
+    }
+}
+
+/// A [`SerializeOutput`] writing into a fixed-size buffer
+///
+/// ```rust
+/// use nom_derive::{Serialize, SliceOutput};
+///
+/// let mut buf = [0u8; 4];
+/// let mut out = SliceOutput::new(&mut buf);
+/// 0x1234_u16.serialize_be(&mut out).expect("serialization failed");
+/// assert_eq!(out.written(), &[0x12, 0x34]);
+/// ```
+#[derive(Debug)]
+pub struct SliceOutput<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceOutput<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceOutput { buf, pos: 0 }
+    }
+
+    /// Return the bytes written so far
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
+}
+
+impl SerializeOutput for SliceOutput<'_> {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), SerializeError> {
+        let end = self.pos + data.len();
+        if end > self.buf.len() {
+            return Err(SerializeError::BufferTooSmall);
+        }
+        self.buf[self.pos..end].copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn patch(&mut self, pos: usize, data: &[u8]) -> Result<(), SerializeError> {
+        let end = pos + data.len();
+        if end > self.pos {
+            return Err(SerializeError::BufferTooSmall);
+        }
+        self.buf[pos..end].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Common trait for all serializers in nom-derive
+///
+/// This trait is the inverse of [`Parse`](crate::Parse): serializing an object and parsing the
+/// result must give back the same object. Implementations are provided for common and primitive
+/// types, and can be derived using the `Serialize` top-level attribute of the `Nom` derive
+/// (see the [docs](crate::docs::Nom#serialization)).
+///
+/// The only required method is `serialize`, but it is advised to implement the `serialize_be` and
+/// `serialize_le` methods. Derived code will call one of these methods, depending on the field
+/// endianness.
+///
+/// # Example
+///
+/// A possible implementation for the type `u32` is:
+/// ```rust,ignore
+/// impl Serialize for u32 {
+///     fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+///         self.serialize_be(out) // default to big-endian
+///     }
+///     fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+///         out.write_bytes(&self.to_be_bytes())
+///     }
+///     fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+///         out.write_bytes(&self.to_le_bytes())
+///     }
+/// }
+/// ```
+pub trait Serialize {
+    /// Serialize object, not knowing the endianness
+    ///
+    /// Default implementations for common types are big-endian.
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError>;
+
+    /// Serialize object as Big-Endian
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize(out)
+    }
+
+    /// Serialize object as Little-Endian
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize(out)
+    }
+}
+
+macro_rules! impl_primitive_serialize {
+    ( $ty:ty ) => {
+        impl Serialize for $ty {
+            fn serialize<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                self.serialize_be(out)
+            }
+            fn serialize_be<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                out.write_bytes(&self.to_be_bytes())
+            }
+            fn serialize_le<W: SerializeOutput + ?Sized>(
+                &self,
+                out: &mut W,
+            ) -> Result<(), SerializeError> {
+                out.write_bytes(&self.to_le_bytes())
+            }
+        }
+    };
+}
+
+impl_primitive_serialize!(i8);
+impl_primitive_serialize!(i16);
+impl_primitive_serialize!(i32);
+impl_primitive_serialize!(i64);
+impl_primitive_serialize!(i128);
+
+impl_primitive_serialize!(u8);
+impl_primitive_serialize!(u16);
+impl_primitive_serialize!(u32);
+impl_primitive_serialize!(u64);
+impl_primitive_serialize!(u128);
+
+impl_primitive_serialize!(f32);
+impl_primitive_serialize!(f64);
+
+impl Serialize for bool {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        u8::from(*self).serialize(out)
+    }
+}
+
+impl Serialize for char {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        u32::from(*self).serialize(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        u32::from(*self).serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        u32::from(*self).serialize_le(out)
+    }
+}
+
+macro_rules! impl_nonzero_serialize {
+    ( $( $nz:ty ),* ) => {
+        $(
+            impl Serialize for $nz {
+                fn serialize<W: SerializeOutput + ?Sized>(
+                    &self,
+                    out: &mut W,
+                ) -> Result<(), SerializeError> {
+                    self.get().serialize(out)
+                }
+                fn serialize_be<W: SerializeOutput + ?Sized>(
+                    &self,
+                    out: &mut W,
+                ) -> Result<(), SerializeError> {
+                    self.get().serialize_be(out)
+                }
+                fn serialize_le<W: SerializeOutput + ?Sized>(
+                    &self,
+                    out: &mut W,
+                ) -> Result<(), SerializeError> {
+                    self.get().serialize_le(out)
+                }
+            }
+        )*
+    };
+}
+
+impl_nonzero_serialize!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128);
+impl_nonzero_serialize!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128);
+
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl Serialize for Ipv4Addr {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        out.write_bytes(&self.octets())
+    }
+}
+
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl Serialize for Ipv6Addr {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        out.write_bytes(&self.octets())
+    }
+}
+
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl Serialize for SocketAddrV4 {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.serialize_be(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.ip().serialize(out)?;
+        self.port().serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.ip().serialize(out)?;
+        self.port().serialize_le(out)
+    }
+}
+
+impl<T> Serialize for &T
+where
+    T: Serialize + ?Sized,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        (*self).serialize(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        (*self).serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        (*self).serialize_le(out)
+    }
+}
+
+impl<T> Serialize for PhantomData<T> {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, _out: &mut W) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Serialize for String {
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        let sz = u32::try_from(self.len()).map_err(|_| SerializeError::ValueOutOfRange)?;
+        sz.serialize(out)?;
+        out.write_bytes(self.as_bytes())
+    }
+}
+
+impl<T> Serialize for Option<T>
+where
+    T: Serialize,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        match self {
+            Some(t) => t.serialize(out),
+            None => Ok(()),
+        }
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        match self {
+            Some(t) => t.serialize_be(out),
+            None => Ok(()),
+        }
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        match self {
+            Some(t) => t.serialize_le(out),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Serialize for [T]
+where
+    T: Serialize,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.iter().try_for_each(|t| t.serialize(out))
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.iter().try_for_each(|t| t.serialize_be(out))
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.iter().try_for_each(|t| t.serialize_le(out))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Serialize for Vec<T>
+where
+    T: Serialize,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.as_slice().serialize(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.as_slice().serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self.as_slice().serialize_le(out)
+    }
+}
+
+impl<T, const N: usize> Serialize for [T; N]
+where
+    T: Serialize,
+{
+    fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self[..].serialize(out)
+    }
+    fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self[..].serialize_be(out)
+    }
+    fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+        self[..].serialize_le(out)
+    }
+}
+
+/// Implement `Serialize` for tuples, by writing all elements in order
+macro_rules! impl_tuple_serialize {
+    ( $( $ty:ident $var:ident ),+ ) => {
+        impl<$($ty,)+> Serialize for ($($ty,)+)
+        where
+            $( $ty: Serialize, )+
+        {
+            fn serialize<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+                let ($($var,)+) = self;
+                $( $var.serialize(out)?; )+
+                Ok(())
+            }
+            fn serialize_be<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+                let ($($var,)+) = self;
+                $( $var.serialize_be(out)?; )+
+                Ok(())
+            }
+            fn serialize_le<W: SerializeOutput + ?Sized>(&self, out: &mut W) -> Result<(), SerializeError> {
+                let ($($var,)+) = self;
+                $( $var.serialize_le(out)?; )+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_tuple_serialize!(T1 t1);
+impl_tuple_serialize!(T1 t1, T2 t2);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10, T11 t11);
+impl_tuple_serialize!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10, T11 t11, T12 t12);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_slice_output() {
+        let mut buf = [0u8; 6];
+        let mut out = SliceOutput::new(&mut buf);
+        0x0102_u16.serialize_be(&mut out).unwrap();
+        0x0304_u16.serialize_le(&mut out).unwrap();
+        assert_eq!(out.position(), 4);
+        assert_eq!(out.written(), &[1, 2, 4, 3]);
+        assert_eq!(
+            0x0506_0708_u32.serialize(&mut out),
+            Err(SerializeError::BufferTooSmall)
+        );
+        out.patch(1, &[5, 6]).unwrap();
+        assert_eq!(out.written(), &[1, 5, 6, 3]);
+        assert_eq!(out.patch(3, &[0, 0]), Err(SerializeError::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_serialize_tuple_array() {
+        let mut buf = [0u8; 8];
+        let mut out = SliceOutput::new(&mut buf);
+        (1u8, [2u16, 3]).serialize_le(&mut out).unwrap();
+        assert_eq!(out.written(), &[1, 2, 0, 3, 0]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod tests_alloc {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_serialize_vec_string() {
+        let mut out = Vec::new();
+        vec![Some(1u8), None, Some(2)].serialize(&mut out).unwrap();
+        String::from("ab").serialize_le(&mut out).unwrap();
+        assert_eq!(out, b"\x01\x02\x00\x00\x00\x02ab");
+    }
+}
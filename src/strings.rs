@@ -0,0 +1,478 @@
+//! Parsers and serializers for string fields: NUL-terminated strings, fixed-width padded strings,
+//! strings with a length prefix, and non-UTF-8 encodings
+//!
+//! These functions are used by the `NulTerminated`, `Padded` and `LengthPrefix` attributes, and
+//! accept any type implementing [`StringType`] (for ex. `&str`, `String`, `&CStr` or `&[u8]`).
+//! The `Encoding` attribute uses [`Encoding`] to decode strings to `String`.
+//!
+//! See the [docs](crate::docs::Nom#string-fields) for details.
+
+use crate::serialize::{SerializeError, SerializeOutput};
+use crate::traits::InputSlice;
+use core::char::DecodeUtf16Error;
+use core::ffi::CStr;
+use core::fmt;
+use core::str::Utf8Error;
+use nom::bytes::streaming::take;
+use nom::error::{ErrorKind, FromExternalError, ParseError};
+use nom::{AsBytes, Err, IResult, Needed, Parser, ToUsize};
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+
+/// Errors when building a string field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringError {
+    /// The string is not valid UTF-8
+    Utf8(Utf8Error),
+    /// The string is not followed by a NUL byte
+    MissingNul,
+}
+
+/// Types which can be used for string fields
+pub trait StringType<'a>: Sized {
+    /// Build a value from the first `len` bytes of `input`
+    ///
+    /// The string may be followed by a NUL byte (which is not part of the string).
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError>;
+
+    /// Get the bytes of the string (without NUL terminator)
+    fn string_bytes(&self) -> &[u8];
+}
+
+impl<'a> StringType<'a> for &'a [u8] {
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError> {
+        Ok(&input[..len])
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<'a> StringType<'a> for &'a str {
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError> {
+        core::str::from_utf8(&input[..len]).map_err(StringError::Utf8)
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// The string ends at the first NUL byte of `input`, which is required (a `CStr` cannot contain
+/// NUL bytes). For padded strings, the NUL byte must be found in the field.
+impl<'a> StringType<'a> for &'a CStr {
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError> {
+        let end = match input.get(len) {
+            Some(0) => len,
+            _ => input
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or(StringError::MissingNul)?,
+        };
+        CStr::from_bytes_with_nul(&input[..=end]).map_err(|_| StringError::MissingNul)
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        self.to_bytes()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> StringType<'a> for String {
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError> {
+        <&str>::from_string_bytes(input, len).map(|s| s.to_owned())
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> StringType<'a> for Vec<u8> {
+    fn from_string_bytes(input: &'a [u8], len: usize) -> Result<Self, StringError> {
+        Ok(input[..len].to_vec())
+    }
+
+    fn string_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+fn build_string<'a, 'b, T, E>(i: &'a [u8], data: &'b [u8], len: usize) -> Result<T, Err<E>>
+where
+    T: StringType<'b>,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error>,
+{
+    T::from_string_bytes(data, len).map_err(|e| match e {
+        StringError::Utf8(e) => Err::Error(E::from_external_error(i, ErrorKind::MapRes, e)),
+        StringError::MissingNul => Err::Error(E::from_error_kind(i, ErrorKind::Verify)),
+    })
+}
+
+/// Trim policy for fixed-width padded strings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trim {
+    /// Keep all bytes, including padding
+    Keep,
+    /// Remove trailing padding bytes
+    Padding,
+    /// Stop at the first NUL byte (C-style), ignoring the remaining bytes
+    Nul,
+}
+
+/// Parse a string terminated by a NUL byte
+///
+/// The NUL byte is consumed, but not part of the string. If no NUL byte is found, `Incomplete`
+/// is returned.
+pub fn nul_terminated<'a, 'b, T, E>(i: &'a [u8]) -> IResult<&'a [u8], T, E>
+where
+    'a: 'b,
+    T: StringType<'b>,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error>,
+{
+    match i.iter().position(|&b| b == 0) {
+        Some(len) => {
+            let s = build_string(i, i, len)?;
+            Ok((&i[len + 1..], s))
+        }
+        None => Err(Err::Incomplete(Needed::new(1))),
+    }
+}
+
+/// Parse a string stored in exactly `width` bytes, padded with `pad`
+pub fn padded<'a, 'b, T, E>(
+    width: usize,
+    pad: u8,
+    trim: Trim,
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], T, E>
+where
+    'a: 'b,
+    T: StringType<'b>,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error>,
+{
+    move |i: &'a [u8]| {
+        let (rem, data) = take(width).parse(i)?;
+        let len = match trim {
+            Trim::Keep => width,
+            Trim::Padding => data.iter().rposition(|&b| b != pad).map_or(0, |p| p + 1),
+            Trim::Nul => data.iter().position(|&b| b == 0).unwrap_or(width),
+        };
+        let s = build_string(i, data, len)?;
+        Ok((rem, s))
+    }
+}
+
+/// Parse a string prefixed by its length (in bytes), read using the `length` parser
+pub fn length_prefixed<'a, 'b, T, E, F>(
+    mut length: F,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>
+where
+    'a: 'b,
+    T: StringType<'b>,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error>,
+    F: Parser<&'a [u8], Error = E>,
+    F::Output: ToUsize,
+{
+    move |i: &'a [u8]| {
+        let (rem, len) = length.parse(i)?;
+        let (rem, data) = take(len.to_usize()).parse(rem)?;
+        let s = build_string(i, data, data.len())?;
+        Ok((rem, s))
+    }
+}
+
+/// Write a string, followed by a NUL byte
+///
+/// Returns an error if the string contains a NUL byte.
+pub fn write_nul_terminated<'a, T, W>(s: &T, out: &mut W) -> Result<(), SerializeError>
+where
+    T: StringType<'a>,
+    W: SerializeOutput + ?Sized,
+{
+    let data = s.string_bytes();
+    if data.contains(&0) {
+        return Err(SerializeError::ValueOutOfRange);
+    }
+    out.write_bytes(data)?;
+    out.write_bytes(&[0])
+}
+
+/// Write a string using exactly `width` bytes, padded with `pad`
+///
+/// Returns an error if the string is larger than `width`.
+pub fn write_padded<'a, T, W>(
+    s: &T,
+    width: usize,
+    pad: u8,
+    out: &mut W,
+) -> Result<(), SerializeError>
+where
+    T: StringType<'a>,
+    W: SerializeOutput + ?Sized,
+{
+    let data = s.string_bytes();
+    if data.len() > width {
+        return Err(SerializeError::ValueOutOfRange);
+    }
+    out.write_bytes(data)?;
+    for _ in data.len()..width {
+        out.write_bytes(&[pad])?;
+    }
+    Ok(())
+}
+
+/// Character encodings for string fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1
+    Latin1,
+    Windows1252,
+}
+
+/// Errors when decoding or encoding a string
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    /// Invalid UTF-16 data (unpaired surrogate)
+    Utf16(DecodeUtf16Error),
+    /// The length of the data is not a multiple of the code unit size
+    Truncated,
+    /// Byte not defined in the encoding
+    InvalidByte(u8),
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::Utf16(e) => write!(f, "invalid UTF-16 data: {}", e),
+            EncodingError::Truncated => f.write_str("truncated code unit"),
+            EncodingError::InvalidByte(b) => write!(f, "invalid byte 0x{:02x}", b),
+        }
+    }
+}
+
+/// Characters for bytes 0x80 to 0x9f in Windows-1252 (0 if not defined)
+#[cfg(feature = "alloc")]
+const WINDOWS_1252: [u16; 32] = [
+    0x20ac, 0, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021, 0x02c6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0, 0x017d, 0, 0, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014, 0x02dc,
+    0x2122, 0x0161, 0x203a, 0x0153, 0, 0x017e, 0x0178,
+];
+
+impl Encoding {
+    /// Size of a code unit, in bytes
+    pub fn unit_size(self) -> usize {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Latin1 | Encoding::Windows1252 => 1,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn decode_byte(self, b: u8) -> Result<char, EncodingError> {
+        match (self, b) {
+            (Encoding::Windows1252, 0x80..=0x9f) => match WINDOWS_1252[usize::from(b - 0x80)] {
+                0 => Err(EncodingError::InvalidByte(b)),
+                c => char::from_u32(u32::from(c)).ok_or(EncodingError::InvalidByte(b)),
+            },
+            _ => Ok(char::from(b)),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn encode_char(self, c: char) -> Option<u8> {
+        match (self, u32::from(c)) {
+            (Encoding::Windows1252, 0x80..=0x9f) => None,
+            (_, 0..=0xff) => Some(c as u8),
+            (Encoding::Windows1252, v) => WINDOWS_1252
+                .iter()
+                .position(|&x| u32::from(x) == v)
+                .map(|p| 0x80 + p as u8),
+            _ => None,
+        }
+    }
+
+    /// Decode bytes to a `String`
+    #[cfg(feature = "alloc")]
+    pub fn decode(self, data: &[u8]) -> Result<String, EncodingError> {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                if data.len() % 2 != 0 {
+                    return Err(EncodingError::Truncated);
+                }
+                let units = data.chunks_exact(2).map(|c| match self {
+                    Encoding::Utf16Le => u16::from_le_bytes([c[0], c[1]]),
+                    _ => u16::from_be_bytes([c[0], c[1]]),
+                });
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map_err(EncodingError::Utf16)
+            }
+            Encoding::Latin1 | Encoding::Windows1252 => {
+                data.iter().map(|&b| self.decode_byte(b)).collect()
+            }
+        }
+    }
+
+    /// Encode a string to bytes
+    ///
+    /// Returns an error if a character cannot be represented in the encoding.
+    #[cfg(feature = "alloc")]
+    pub fn encode(self, s: &str) -> Result<Vec<u8>, SerializeError> {
+        match self {
+            Encoding::Utf16Le => Ok(s.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Encoding::Utf16Be => Ok(s.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Encoding::Latin1 | Encoding::Windows1252 => s
+                .chars()
+                .map(|c| self.encode_char(c).ok_or(SerializeError::ValueOutOfRange))
+                .collect(),
+        }
+    }
+}
+
+/// Parse bytes terminated by a NUL code unit of `unit_size` bytes
+///
+/// The terminator is consumed, but not returned. If no terminator is found, `Incomplete` is
+/// returned.
+pub fn nul_terminated_units<I, E>(unit_size: usize) -> impl Fn(I) -> IResult<I, I, E>
+where
+    I: InputSlice + AsBytes,
+    E: ParseError<I>,
+{
+    move |i: I| {
+        let data = i.as_bytes();
+        match data
+            .chunks_exact(unit_size)
+            .position(|c| c.iter().all(|&b| b == 0))
+        {
+            Some(p) => {
+                let len = p * unit_size;
+                Ok((i.take_from(len + unit_size), i.take(len)))
+            }
+            None => Err(Err::Incomplete(Needed::new(
+                unit_size - data.len() % unit_size,
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::error::Error;
+
+    #[test]
+    fn test_nul_terminated() {
+        let res: IResult<_, &str, Error<_>> = nul_terminated(b"abc\x00def");
+        assert_eq!(res, Ok((&b"def"[..], "abc")));
+        let res: IResult<_, &CStr, Error<_>> = nul_terminated(b"abc\x00");
+        assert_eq!(res.map(|(_, s)| s.to_bytes()), Ok(&b"abc"[..]));
+        let res: IResult<_, &str, Error<_>> = nul_terminated(b"abc");
+        assert_eq!(res, Err(Err::Incomplete(Needed::new(1))));
+        #[cfg(feature = "alloc")]
+        {
+            let res: IResult<_, String, Error<_>> = nul_terminated(b"\xff\x00");
+            assert!(matches!(res, Err(Err::Error(_))));
+        }
+    }
+
+    #[test]
+    fn test_padded() {
+        let input = b"ab\x00c  x";
+        let res: IResult<_, &[u8], Error<_>> = padded(6, b' ', Trim::Padding)(input);
+        assert_eq!(res, Ok((&b"x"[..], &b"ab\x00c"[..])));
+        let res: IResult<_, &str, Error<_>> = padded(6, b' ', Trim::Nul)(input);
+        assert_eq!(res, Ok((&b"x"[..], "ab")));
+        let res: IResult<_, &CStr, Error<_>> = padded(6, b' ', Trim::Nul)(input);
+        assert!(res.is_ok());
+        let res: IResult<_, &str, Error<_>> = padded(4, 0, Trim::Keep)(input);
+        assert_eq!(res, Ok((&b"  x"[..], "ab\x00c")));
+        let res: IResult<_, &str, Error<_>> = padded(4, 0, Trim::Padding)(b"\x00\x00\x00\x00");
+        assert_eq!(res, Ok((&b""[..], "")));
+        // no NUL terminator in the field
+        let res: IResult<_, &CStr, Error<_>> = padded(2, 0, Trim::Nul)(b"ab");
+        assert!(res.is_err());
+        // NUL terminator inside the field, with other trim policies
+        let res: IResult<_, &CStr, Error<_>> = padded(4, 0, Trim::Keep)(b"ab\x00\x00");
+        assert_eq!(res.map(|(_, s)| s.to_bytes()), Ok(&b"ab"[..]));
+        let res: IResult<_, &CStr, Error<_>> = padded(4, 0, Trim::Padding)(b"ab\x00\x00");
+        assert_eq!(res.map(|(_, s)| s.to_bytes()), Ok(&b"ab"[..]));
+        let res: IResult<_, &CStr, Error<_>> = padded(4, b' ', Trim::Padding)(b"ab\x00 ");
+        assert_eq!(res.map(|(_, s)| s.to_bytes()), Ok(&b"ab"[..]));
+        let res: IResult<_, &CStr, Error<_>> = padded(4, 0, Trim::Keep)(b"abcd");
+        assert!(res.is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_write_strings() {
+        let mut out = Vec::new();
+        write_nul_terminated(&"abc", &mut out).unwrap();
+        write_padded(&"de", 4, b' ', &mut out).unwrap();
+        assert_eq!(out, b"abc\x00de  ");
+        assert_eq!(
+            write_padded(&"abcde", 4, 0, &mut out),
+            Err(SerializeError::ValueOutOfRange)
+        );
+        assert_eq!(
+            write_nul_terminated(&"a\x00b", &mut out),
+            Err(SerializeError::ValueOutOfRange)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_encodings() {
+        let data = b"a\x00\xe9\x00=\xd8\x00\xde";
+        assert_eq!(Encoding::Utf16Le.decode(data).unwrap(), "a\u{e9}\u{1f600}");
+        assert_eq!(Encoding::Utf16Le.encode("a\u{e9}\u{1f600}").unwrap(), data);
+        assert_eq!(Encoding::Utf16Be.decode(b"\x00a\x00b").unwrap(), "ab");
+        assert_eq!(
+            Encoding::Utf16Be.decode(b"\x00a\x00"),
+            Err(EncodingError::Truncated)
+        );
+        assert!(matches!(
+            Encoding::Utf16Be.decode(b"\xd8\x00\x00a"),
+            Err(EncodingError::Utf16(_))
+        ));
+        assert_eq!(
+            Encoding::Latin1.decode(b"caf\xe9\x80").unwrap(),
+            "caf\u{e9}\u{80}"
+        );
+        assert_eq!(
+            Encoding::Windows1252.decode(b"\x80 \x9f").unwrap(),
+            "\u{20ac} \u{178}"
+        );
+        assert_eq!(
+            Encoding::Windows1252.decode(b"\x81"),
+            Err(EncodingError::InvalidByte(0x81))
+        );
+        assert_eq!(
+            Encoding::Windows1252.encode("\u{20ac}\u{e9}").unwrap(),
+            b"\x80\xe9"
+        );
+        assert_eq!(
+            Encoding::Latin1.encode("\u{20ac}"),
+            Err(SerializeError::ValueOutOfRange)
+        );
+        assert_eq!(
+            Encoding::Windows1252.encode("\u{80}"),
+            Err(SerializeError::ValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_nul_terminated_units() {
+        let res: IResult<_, _, Error<_>> = nul_terminated_units(2)(&b"a\x00\x00b\x00\x00x"[..]);
+        assert_eq!(res, Ok((&b"x"[..], &b"a\x00\x00b"[..])));
+        let res: IResult<_, _, Error<_>> = nul_terminated_units(2)(&b"a\x00\x00"[..]);
+        assert_eq!(res, Err(Err::Incomplete(Needed::new(1))));
+    }
+}
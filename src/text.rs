@@ -0,0 +1,151 @@
+//! Parsers for numbers in text form, used for text inputs and by the `Hex` attribute
+//!
+//! These parsers accept any input with items convertible to characters (for ex. `&str` or
+//! `&[u8]`). Like other parsers from this crate, they are streaming: if the input ends with
+//! digits, `Incomplete` is returned, because the number could continue.
+//!
+//! See the [docs](crate::docs::Nom#text-input) for details.
+
+use nom::error::{ErrorKind, ParseError};
+use nom::{AsChar, Err, IResult, Input, Needed};
+
+/// Integer types which can be read from text
+pub trait TextInteger: Copy {
+    /// `true` if the type accepts negative values
+    const SIGNED: bool;
+    /// The value 0
+    const ZERO: Self;
+
+    /// Append a digit to the value, or return `None` if the result would overflow
+    fn push_digit(self, radix: u32, digit: u32, negative: bool) -> Option<Self>;
+}
+
+macro_rules! impl_text_integer {
+    ( $ty:ty, $signed:expr ) => {
+        impl TextInteger for $ty {
+            const SIGNED: bool = $signed;
+            const ZERO: Self = 0;
+
+            fn push_digit(self, radix: u32, digit: u32, negative: bool) -> Option<Self> {
+                let v = self.checked_mul(radix as $ty)?;
+                if negative {
+                    v.checked_sub(digit as $ty)
+                } else {
+                    v.checked_add(digit as $ty)
+                }
+            }
+        }
+    };
+}
+
+impl_text_integer!(u8, false);
+impl_text_integer!(u16, false);
+impl_text_integer!(u32, false);
+impl_text_integer!(u64, false);
+impl_text_integer!(u128, false);
+impl_text_integer!(i8, true);
+impl_text_integer!(i16, true);
+impl_text_integer!(i32, true);
+impl_text_integer!(i64, true);
+impl_text_integer!(i128, true);
+
+fn parse_radix<T, I, E>(i: I, radix: u32) -> IResult<I, T, E>
+where
+    T: TextInteger,
+    I: Input,
+    I::Item: AsChar,
+    E: ParseError<I>,
+{
+    let mut value = T::ZERO;
+    let mut negative = false;
+    let mut digits = 0;
+    for (idx, c) in i.iter_indices() {
+        let c = c.as_char();
+        if idx == 0 && T::SIGNED && (c == '-' || c == '+') {
+            negative = c == '-';
+            continue;
+        }
+        match c.to_digit(radix) {
+            Some(d) => {
+                value = value.push_digit(radix, d, negative).ok_or_else(|| {
+                    Err::Error(E::from_error_kind(i.clone(), ErrorKind::TooLarge))
+                })?;
+                digits += 1;
+            }
+            None if digits == 0 => {
+                let kind = if radix == 16 {
+                    ErrorKind::HexDigit
+                } else {
+                    ErrorKind::Digit
+                };
+                return Err(Err::Error(E::from_error_kind(i, kind)));
+            }
+            None => return Ok((i.take_from(idx), value)),
+        }
+    }
+    Err(Err::Incomplete(Needed::new(1)))
+}
+
+/// Read an integer in decimal form, with an optional sign for signed types
+///
+/// Returns a `TooLarge` error if the value does not fit in the type.
+pub fn decimal<T, I, E>(i: I) -> IResult<I, T, E>
+where
+    T: TextInteger,
+    I: Input,
+    I::Item: AsChar,
+    E: ParseError<I>,
+{
+    parse_radix(i, 10)
+}
+
+/// Read an integer in hexadecimal form (without prefix), with an optional sign for signed types
+///
+/// Returns a `TooLarge` error if the value does not fit in the type.
+pub fn hex<T, I, E>(i: I) -> IResult<I, T, E>
+where
+    T: TextInteger,
+    I: Input,
+    I::Item: AsChar,
+    E: ParseError<I>,
+{
+    parse_radix(i, 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::error::Error;
+
+    #[test]
+    fn test_decimal() {
+        let res: IResult<_, u16, Error<_>> = decimal("1234 ");
+        assert_eq!(res, Ok((" ", 1234)));
+        let res: IResult<_, i8, Error<_>> = decimal("-128,");
+        assert_eq!(res, Ok((",", -128)));
+        let res: IResult<_, u8, Error<_>> = decimal(&b"255\r\n"[..]);
+        assert_eq!(res, Ok((&b"\r\n"[..], 255)));
+        let res: IResult<_, u8, Error<_>> = decimal("256 ");
+        assert_eq!(
+            res,
+            Err(Err::Error(Error::new("256 ", ErrorKind::TooLarge)))
+        );
+        let res: IResult<_, u8, Error<_>> = decimal("-1 ");
+        assert_eq!(res, Err(Err::Error(Error::new("-1 ", ErrorKind::Digit))));
+        let res: IResult<_, u32, Error<_>> = decimal("12");
+        assert_eq!(res, Err(Err::Incomplete(Needed::new(1))));
+    }
+
+    #[test]
+    fn test_hex() {
+        let res: IResult<_, u32, Error<_>> = hex("dEadBeef;");
+        assert_eq!(res, Ok((";", 0xdead_beef)));
+        let res: IResult<_, u16, Error<_>> = hex("x12");
+        assert_eq!(res, Err(Err::Error(Error::new("x12", ErrorKind::HexDigit))));
+        let res: IResult<_, u8, Error<_>> = hex("100 ");
+        assert_eq!(
+            res,
+            Err(Err::Error(Error::new("100 ", ErrorKind::TooLarge)))
+        );
+    }
+}
@@ -1,26 +1,41 @@
-use nom::combinator::{complete, opt};
-use nom::error::{Error, ParseError};
+use core::char::CharTryFromError;
+use core::convert::TryFrom;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+    NonZeroU32, NonZeroU64, NonZeroU8, TryFromIntError,
+};
+use nom::combinator::{complete, map, opt, verify};
+use nom::error::{Error, ErrorKind, FromExternalError, ParseError};
 use nom::number::streaming::*;
-use nom::sequence::pair;
 use nom::*;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
 #[cfg(feature = "alloc")]
-use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
 
-#[cfg(feature = "alloc")]
-use core::convert::TryFrom;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
 
 #[cfg(feature = "alloc")]
 use nom::bytes::streaming::take;
 
 #[cfg(feature = "alloc")]
-use nom::combinator::map_res;
+use nom::combinator::{map_res, rest};
 
-#[cfg(feature = "alloc")]
-use nom::error::FromExternalError;
+#[rustversion::since(1.77)]
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+
+#[rustversion::before(1.77)]
+#[cfg(feature = "std")]
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
 
 #[cfg(not(feature = "alloc"))]
 use nom::multi::fill;
@@ -28,8 +43,76 @@ use nom::multi::fill;
 #[cfg(feature = "alloc")]
 use nom::multi::{many0, many_m_n};
 
-pub trait InputSlice: Input<Item = u8> {}
-impl InputSlice for &[u8] {}
+/// Binary input types: any nom input made of bytes
+///
+/// This trait is implemented for all types implementing `nom::Input<Item = u8>`, for ex. `&[u8]`
+/// or `nom_locate::LocatedSpan<&[u8]>`.
+pub trait InputSlice: ParseInput + Input<Item = u8> {}
+impl<T> InputSlice for T where T: Input<Item = u8> {}
+
+/// Input types accepted by parsers: any nom input made of bytes or characters
+///
+/// This trait is implemented for all types implementing `nom::Input`, with items of type `u8`
+/// (all [InputSlice] types) or `char` (for ex. `&str`).
+pub trait ParseInput: Input {}
+impl<T> ParseInput for T
+where
+    T: Input,
+    T::Item: InputItem,
+{
+}
+
+/// Types of the items of an input: `u8` for binary inputs, `char` for text inputs
+pub trait InputItem: Copy {}
+impl InputItem for u8 {}
+impl InputItem for char {}
+
+/// Parser for a primitive type `T`, depending on the type of the items of the input
+///
+/// For binary inputs (`u8`), numbers are read using their binary representation, using the
+/// requested endianness. For text inputs (`char`), integers are read in decimal form, and the
+/// endianness is ignored.
+pub trait PrimitiveParser<T>: InputItem {
+    /// Parse value as Big-Endian
+    fn parse_primitive_be<I, E>(i: I) -> IResult<I, T, E>
+    where
+        I: Input<Item = Self>,
+        E: ParseError<I>;
+
+    /// Parse value as Little-Endian
+    fn parse_primitive_le<I, E>(i: I) -> IResult<I, T, E>
+    where
+        I: Input<Item = Self>,
+        E: ParseError<I>;
+}
+
+/// Return the position of `i`, relative to the start of the original input `orig`
+///
+/// `i` must be a suffix of `orig`, which is always the case for the input of derived parsers.
+/// The position is computed using lengths, so this works for all input types.
+pub fn input_position<I: Input>(orig: &I, i: &I) -> usize {
+    orig.input_len() - i.input_len()
+}
+
+/// Return the original input `orig`, advanced to position `pos`
+///
+/// Return `None` if `pos` is beyond the end of the input.
+pub fn input_at<I: Input>(orig: &I, pos: usize) -> Option<I> {
+    if pos > orig.input_len() {
+        return None;
+    }
+    Some(orig.take_from(pos))
+}
+
+/// Return the bytes of `orig` from position `start` (included) to `end` (excluded)
+///
+/// Return `None` if the range is invalid, or out of the bounds of the input.
+pub fn input_range<I: Input>(orig: &I, start: usize, end: usize) -> Option<I> {
+    if start > end || end > orig.input_len() {
+        return None;
+    }
+    Some(orig.take_from(start).take(end - start))
+}
 
 /// Common trait for all parsers in nom-derive
 ///
@@ -55,9 +138,9 @@ impl InputSlice for &[u8] {}
 ///
 /// # Generic type parameters and input
 ///
-/// Note: `I` is a generic type that is mostly equivalent to `&'a [u8]`. It is used to
-/// "hide" the lifetime of the input slice `&'a [u8]` and simplify traits implementation
-/// and generation of derived code.
+/// Note: `I` is a generic type that is mostly equivalent to `&'a [u8]` (or `&'a str` for text
+/// inputs). It is used to "hide" the lifetime of the input slice `&'a [u8]` and simplify traits
+/// implementation and generation of derived code.
 ///
 /// It is possible to implement the `Parse` trait only for `&[u8]` if the
 /// implementation contains non-generic functions.
@@ -109,7 +192,7 @@ impl InputSlice for &[u8] {}
 /// ```
 pub trait Parse<I, E = Error<I>>
 where
-    I: InputSlice,
+    I: ParseInput,
     E: ParseError<I>,
     Self: Sized,
 {
@@ -132,19 +215,58 @@ where
 
 macro_rules! impl_primitive_type {
     ( $ty:ty, $be_fn: ident, $le_fn: ident ) => {
+        impl PrimitiveParser<$ty> for u8 {
+            fn parse_primitive_be<I, E>(i: I) -> IResult<I, $ty, E>
+            where
+                I: Input<Item = u8>,
+                E: ParseError<I>,
+            {
+                $be_fn(i)
+            }
+            fn parse_primitive_le<I, E>(i: I) -> IResult<I, $ty, E>
+            where
+                I: Input<Item = u8>,
+                E: ParseError<I>,
+            {
+                $le_fn(i)
+            }
+        }
+
         impl<I, E> Parse<I, E> for $ty
         where
             E: ParseError<I>,
-            I: InputSlice,
+            I: ParseInput,
+            I::Item: PrimitiveParser<$ty>,
         {
             fn parse(i: I) -> IResult<I, Self, E> {
                 Self::parse_be(i)
             }
             fn parse_be(i: I) -> IResult<I, Self, E> {
-                $be_fn(i)
+                <I::Item as PrimitiveParser<$ty>>::parse_primitive_be(i)
             }
             fn parse_le(i: I) -> IResult<I, Self, E> {
-                $le_fn(i)
+                <I::Item as PrimitiveParser<$ty>>::parse_primitive_le(i)
+            }
+        }
+    };
+}
+
+macro_rules! impl_text_primitive_type {
+    ( $ty:ty ) => {
+        impl PrimitiveParser<$ty> for char {
+            fn parse_primitive_be<I, E>(i: I) -> IResult<I, $ty, E>
+            where
+                I: Input<Item = char>,
+                E: ParseError<I>,
+            {
+                crate::text::decimal(i)
+            }
+            fn parse_primitive_le<I, E>(i: I) -> IResult<I, $ty, E>
+            where
+                I: Input<Item = char>,
+                E: ParseError<I>,
+            {
+                crate::text::decimal(i)
             }
         }
     };
@@ -165,6 +287,164 @@ impl_primitive_type!(u128, be_u128, le_u128);
 impl_primitive_type!(f32, be_f32, le_f32);
 impl_primitive_type!(f64, be_f64, le_f64);
 
+impl_text_primitive_type!(i8);
+impl_text_primitive_type!(i16);
+impl_text_primitive_type!(i32);
+impl_text_primitive_type!(i64);
+impl_text_primitive_type!(i128);
+
+impl_text_primitive_type!(u8);
+impl_text_primitive_type!(u16);
+impl_text_primitive_type!(u32);
+impl_text_primitive_type!(u64);
+impl_text_primitive_type!(u128);
+
+/// Apply the conversion `g` to the result of the parser `f`
+///
+/// Like `nom::combinator::map_res`, but conversion errors have kind `ErrorKind::Verify`.
+fn try_map<I, O, T, X, E, F>(
+    mut f: F,
+    g: fn(O) -> Result<T, X>,
+) -> impl FnMut(I) -> IResult<I, T, E>
+where
+    I: Clone,
+    E: FromExternalError<I, X>,
+    F: Parser<I, Output = O, Error = E>,
+{
+    move |i: I| {
+        let (rem, o) = f.parse(i.clone())?;
+        match g(o) {
+            Ok(t) => Ok((rem, t)),
+            Err(e) => Err(Err::Error(E::from_external_error(i, ErrorKind::Verify, e))),
+        }
+    }
+}
+
+/// Parse a `bool` from a byte, accepting any non-zero value as `true`
+///
+/// The `Parse` implementation for `bool` is strict, and only accepts `0` and `1`. This function
+/// can be used with the `Parse` attribute: `#[nom(Parse = "nom_derive::lenient_bool")]`.
+pub fn lenient_bool<I, E>(i: I) -> IResult<I, bool, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    map(be_u8, |b| b != 0).parse(i)
+}
+
+/// Parse a `bool` from a byte: `0` is `false` and `1` is `true`
+///
+/// Other values are rejected with a `Verify` error (see [`lenient_bool`] to accept them).
+impl<I, E> Parse<I, E> for bool
+where
+    E: ParseError<I>,
+    I: InputSlice,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        map(verify(be_u8, |b| *b < 2), |b| b != 0).parse(i)
+    }
+}
+
+/// Parse a `char` from a `u32` code point
+///
+/// Invalid code points are rejected with a `Verify` error, using `FromExternalError`.
+impl<I, E> Parse<I, E> for char
+where
+    E: ParseError<I> + FromExternalError<I, CharTryFromError>,
+    I: InputSlice,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        try_map(be_u32, char::try_from)(i)
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        try_map(le_u32, char::try_from)(i)
+    }
+}
+
+macro_rules! impl_nonzero_type {
+    ( $ty:ty, $nz:ident ) => {
+        /// Zero is rejected with a `Verify` error, using `FromExternalError`.
+        impl<I, E> Parse<I, E> for $nz
+        where
+            E: ParseError<I> + FromExternalError<I, TryFromIntError>,
+            I: ParseInput,
+            I::Item: PrimitiveParser<$ty>,
+        {
+            fn parse(i: I) -> IResult<I, Self, E> {
+                Self::parse_be(i)
+            }
+            fn parse_be(i: I) -> IResult<I, Self, E> {
+                try_map(<$ty>::parse_be, $nz::try_from)(i)
+            }
+            fn parse_le(i: I) -> IResult<I, Self, E> {
+                try_map(<$ty>::parse_le, $nz::try_from)(i)
+            }
+        }
+    };
+}
+
+impl_nonzero_type!(i8, NonZeroI8);
+impl_nonzero_type!(i16, NonZeroI16);
+impl_nonzero_type!(i32, NonZeroI32);
+impl_nonzero_type!(i64, NonZeroI64);
+impl_nonzero_type!(i128, NonZeroI128);
+
+impl_nonzero_type!(u8, NonZeroU8);
+impl_nonzero_type!(u16, NonZeroU16);
+impl_nonzero_type!(u32, NonZeroU32);
+impl_nonzero_type!(u64, NonZeroU64);
+impl_nonzero_type!(u128, NonZeroU128);
+
+/// Parse an IPv4 address from 4 bytes (in network order, whatever the endianness)
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl<I, E> Parse<I, E> for Ipv4Addr
+where
+    E: ParseError<I>,
+    I: InputSlice,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        map(be_u32, Ipv4Addr::from).parse(i)
+    }
+}
+
+/// Parse an IPv6 address from 16 bytes (in network order, whatever the endianness)
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl<I, E> Parse<I, E> for Ipv6Addr
+where
+    E: ParseError<I>,
+    I: InputSlice,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        map(be_u128, Ipv6Addr::from).parse(i)
+    }
+}
+
+/// Parse an IPv4 address (4 bytes, in network order) followed by a port (`u16`, using the
+/// endianness)
+#[rustversion::attr(before(1.77), cfg(feature = "std"))]
+impl<I, E> Parse<I, E> for SocketAddrV4
+where
+    E: ParseError<I>,
+    I: InputSlice,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        Self::parse_be(i)
+    }
+    fn parse_be(i: I) -> IResult<I, Self, E> {
+        let (i, ip) = <Ipv4Addr>::parse(i)?;
+        let (i, port) = be_u16(i)?;
+        Ok((i, SocketAddrV4::new(ip, port)))
+    }
+    fn parse_le(i: I) -> IResult<I, Self, E> {
+        let (i, ip) = <Ipv4Addr>::parse(i)?;
+        let (i, port) = le_u16(i)?;
+        Ok((i, SocketAddrV4::new(ip, port)))
+    }
+}
+
 #[cfg(feature = "alloc")]
 impl<'a, E> Parse<&'a [u8], E> for String
 where
@@ -179,7 +459,7 @@ where
 
 impl<T, I, E> Parse<I, E> for Option<T>
 where
-    I: Clone + InputSlice,
+    I: Clone + ParseInput,
     E: ParseError<I>,
     T: Parse<I, E>,
 {
@@ -197,7 +477,7 @@ where
 #[cfg(feature = "alloc")]
 impl<T, I, E> Parse<I, E> for Vec<T>
 where
-    I: Clone + PartialEq + InputSlice,
+    I: Clone + PartialEq + ParseInput,
     E: ParseError<I>,
     T: Parse<I, E>,
 {
@@ -212,30 +492,112 @@ where
     }
 }
 
-impl<T1, T2, I, E> Parse<I, E> for (T1, T2)
+/// Implement `Parse` for smart pointers, by parsing the pointed value
+///
+/// This can be used for recursive types (for ex. `Option<Box<Self>>`).
+#[cfg(feature = "alloc")]
+macro_rules! impl_pointer_type {
+    ( $ptr:ident ) => {
+        impl<T, I, E> Parse<I, E> for $ptr<T>
+        where
+            I: ParseInput,
+            E: ParseError<I>,
+            T: Parse<I, E>,
+        {
+            fn parse(i: I) -> IResult<I, Self, E> {
+                map(<T>::parse, $ptr::new).parse(i)
+            }
+            fn parse_be(i: I) -> IResult<I, Self, E> {
+                map(<T>::parse_be, $ptr::new).parse(i)
+            }
+            fn parse_le(i: I) -> IResult<I, Self, E> {
+                map(<T>::parse_le, $ptr::new).parse(i)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_pointer_type!(Box);
+#[cfg(feature = "alloc")]
+impl_pointer_type!(Rc);
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl_pointer_type!(Arc);
+
+/// Parse all remaining items, like `Vec<T>`
+#[cfg(feature = "alloc")]
+impl<T, I, E> Parse<I, E> for Box<[T]>
 where
-    I: Clone + PartialEq + InputSlice,
+    I: Clone + PartialEq + ParseInput,
     E: ParseError<I>,
-    T1: Parse<I, E>,
-    T2: Parse<I, E>,
+    T: Parse<I, E>,
 {
     fn parse(i: I) -> IResult<I, Self, E> {
-        pair(T1::parse, T2::parse).parse(i)
+        map(<Vec<T>>::parse, Vec::into_boxed_slice).parse(i)
     }
     fn parse_be(i: I) -> IResult<I, Self, E> {
-        pair(T1::parse_be, T2::parse_be).parse(i)
+        map(<Vec<T>>::parse_be, Vec::into_boxed_slice).parse(i)
     }
     fn parse_le(i: I) -> IResult<I, Self, E> {
-        pair(T1::parse_le, T2::parse_le).parse(i)
+        map(<Vec<T>>::parse_le, Vec::into_boxed_slice).parse(i)
+    }
+}
+
+/// Borrow all remaining bytes, like `Vec<u8>` (without copying)
+#[cfg(feature = "alloc")]
+impl<'a, 'b: 'a, E> Parse<&'b [u8], E> for Cow<'a, [u8]>
+where
+    E: ParseError<&'b [u8]>,
+{
+    fn parse(i: &'b [u8]) -> IResult<&'b [u8], Self, E> {
+        map(rest, Cow::Borrowed).parse(i)
     }
 }
 
+/// Implement `Parse` for tuples, by parsing all elements in order
+macro_rules! impl_tuple_type {
+    ( $( $ty:ident $var:ident ),+ ) => {
+        impl<$($ty,)+ I, E> Parse<I, E> for ($($ty,)+)
+        where
+            I: ParseInput,
+            E: ParseError<I>,
+            $( $ty: Parse<I, E>, )+
+        {
+            fn parse(i: I) -> IResult<I, Self, E> {
+                $( let (i, $var) = <$ty>::parse(i)?; )+
+                Ok((i, ($($var,)+)))
+            }
+            fn parse_be(i: I) -> IResult<I, Self, E> {
+                $( let (i, $var) = <$ty>::parse_be(i)?; )+
+                Ok((i, ($($var,)+)))
+            }
+            fn parse_le(i: I) -> IResult<I, Self, E> {
+                $( let (i, $var) = <$ty>::parse_le(i)?; )+
+                Ok((i, ($($var,)+)))
+            }
+        }
+    };
+}
+
+impl_tuple_type!(T1 t1);
+impl_tuple_type!(T1 t1, T2 t2);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10, T11 t11);
+impl_tuple_type!(T1 t1, T2 t2, T3 t3, T4 t4, T5 t5, T6 t6, T7 t7, T8 t8, T9 t9, T10 t10, T11 t11, T12 t12);
+
 /// *Note: this implementation uses const generics and requires rust >= 1.51*
 #[rustversion::since(1.51)]
 #[cfg(feature = "alloc")]
 impl<T, I, E, const N: usize> Parse<I, E> for [T; N]
 where
-    I: Clone + PartialEq + InputSlice,
+    I: Clone + PartialEq + ParseInput,
     E: ParseError<I> + FromExternalError<I, Vec<T>>,
     T: Parse<I, E>,
 {
@@ -261,7 +623,7 @@ where
 #[cfg(not(feature = "alloc"))]
 impl<T, I, E, const N: usize> Parse<I, E> for [T; N]
 where
-    I: Clone + PartialEq + InputSlice,
+    I: Clone + PartialEq + ParseInput,
     E: ParseError<I>,
     T: Parse<I, E> + Default + Copy,
 {
@@ -307,6 +669,51 @@ mod tests_alloc {
         assert_eq!(res.unwrap(), (b"" as &[u8], vec![0, 1, 2, 3]));
     }
 
+    #[test]
+    fn test_parse_trait_tuples() {
+        let input: &[u8] = b"\x00\x01\x02\x03\x04\x05\x06\x07";
+
+        type T = (u8, u16, u32);
+        let res: IResult<_, _, Error<&[u8]>> = <T>::parse_le(input);
+        assert_eq!(res.unwrap(), (&input[7..], (0, 0x0201, 0x0605_0403)));
+        type U4 = (u8, u8, u8, u8);
+        type T12 = (
+            u8,
+            u8,
+            u8,
+            u8,
+            U4,
+            u8,
+            u8,
+            (u8,),
+            Option<u8>,
+            [u8; 0],
+            u8,
+            u8,
+        );
+        let input: &[u8] = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d";
+        let res: IResult<_, _, Error<&[u8]>> = <T12>::parse_be(input);
+        let expected = (0, 1, 2, 3, (4, 5, 6, 7), 8, 9, (10,), Some(11), [], 12, 13);
+        assert_eq!(res.unwrap(), (b"" as &[u8], expected));
+    }
+
+    #[test]
+    fn test_parse_trait_pointers() {
+        let input: &[u8] = b"\x00\x01\x02\x03";
+
+        let res: IResult<_, _, Error<&[u8]>> = <Box<u16>>::parse_le(input);
+        assert_eq!(res.unwrap(), (&input[2..], Box::new(0x0100)));
+        let res: IResult<_, _, Error<&[u8]>> = <Rc<u16>>::parse(input);
+        assert_eq!(res.unwrap(), (&input[2..], Rc::new(0x0001)));
+        let res: IResult<_, _, Error<&[u8]>> = <Arc<u16>>::parse_be(input);
+        assert_eq!(res.unwrap(), (&input[2..], Arc::new(0x0001)));
+        let res: IResult<_, _, Error<&[u8]>> = <Box<[u16]>>::parse_le(input);
+        let expected: Box<[u16]> = Box::new([0x0100, 0x0302]);
+        assert_eq!(res.unwrap(), (b"" as &[u8], expected));
+        let res: IResult<_, _, Error<&[u8]>> = <Cow<[u8]>>::parse(input);
+        assert_eq!(res.unwrap(), (b"" as &[u8], Cow::Borrowed(input)));
+    }
+
     #[test]
     fn test_parse_trait_string() {
         let input: &[u8] = b"\x00\x00\x00\x04abcd";
@@ -0,0 +1,136 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom_derive::*;
+
+/// Start of IPv4 header
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Ipv4HeaderStart {
+    #[nom(Bits = 4)]
+    version: u8,
+    #[nom(Bits = 4, Verify = "*ihl >= 5")]
+    ihl: u8,
+    #[nom(Bits = 6)]
+    dscp: u8,
+    #[nom(Bits = 2)]
+    ecn: u8,
+    total_length: u16,
+    #[nom(Bits = 3)]
+    flags: u8,
+    #[nom(Bits = 13)]
+    fragment_offset: u16,
+}
+
+/// TCP flags, using struct-level bit mode
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Bits, Serialize)]
+struct TcpFlags {
+    #[nom(Bits = 4)]
+    data_offset: u8,
+    #[nom(Bits = 3)]
+    reserved: u8,
+    ns: bool,
+    cwr: bool,
+    ece: bool,
+    urg: bool,
+    ack: bool,
+    psh: bool,
+    rst: bool,
+    syn: bool,
+    fin: bool,
+    #[nom(Value(syn && !ack))]
+    is_connection_request: bool,
+}
+
+/// 802.11 frame control, least significant bit first
+#[derive(Debug, PartialEq, Nom)]
+#[nom(BitOrder = "lsb", Serialize)]
+struct FrameControl {
+    #[nom(Bits = 2)]
+    protocol_version: u8,
+    #[nom(Bits = 2)]
+    frame_type: u8,
+    #[nom(Bits = 4)]
+    subtype: u8,
+    #[nom(Bits = 1)]
+    to_ds: bool,
+    #[nom(Bits = 1)]
+    from_ds: bool,
+    #[nom(Bits = 6)]
+    flags: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct SignedBits {
+    #[nom(Bits = 4)]
+    a: i8,
+    #[nom(Bits = 4)]
+    b: i8,
+}
+
+#[test]
+fn test_bits_ipv4() {
+    let input = b"\x45\x02\x00\x73\x40\x01";
+    let (rem, res) = Ipv4HeaderStart::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        res,
+        Ipv4HeaderStart {
+            version: 4,
+            ihl: 5,
+            dscp: 0,
+            ecn: 2,
+            total_length: 0x73,
+            flags: 2,
+            fragment_offset: 1,
+        }
+    );
+    let mut out = Vec::new();
+    res.serialize(&mut out).unwrap();
+    assert_eq!(&out, input);
+    // verify is applied to bit fields
+    assert!(Ipv4HeaderStart::parse(b"\x44\x02\x00\x73\x40\x01").is_err());
+    // group bytes are read at once
+    assert!(Ipv4HeaderStart::parse(b"\x45").unwrap_err().is_incomplete());
+}
+
+#[test]
+fn test_bits_struct_mode() {
+    let input = b"\x50\x12";
+    let (rem, res) = TcpFlags::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.data_offset, 5);
+    assert!(res.syn && res.ack && !res.fin && !res.ns);
+    assert!(!res.is_connection_request);
+    let mut out = Vec::new();
+    res.serialize(&mut out).unwrap();
+    assert_eq!(&out, input);
+}
+
+#[test]
+fn test_bits_lsb0() {
+    // beacon frame: type 0, subtype 8
+    let input = b"\x80\x01";
+    let (_, res) = FrameControl::parse(input).expect("parsing failed");
+    assert_eq!(
+        res,
+        FrameControl {
+            protocol_version: 0,
+            frame_type: 0,
+            subtype: 8,
+            to_ds: true,
+            from_ds: false,
+            flags: 0,
+        }
+    );
+    let mut out = Vec::new();
+    res.serialize(&mut out).unwrap();
+    assert_eq!(&out, input);
+
+    let (_, res) = SignedBits::parse(b"\xf7").expect("parsing failed");
+    assert_eq!(res, SignedBits { a: -1, b: 7 });
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "alloc")]
+
+use nom::error::ErrorKind;
+use nom_derive::*;
+
+/// CRC over all previous fields
+#[derive(Debug, PartialEq, Nom)]
+struct RecordWithCrc<'a> {
+    pub tag: u16,
+    #[nom(LengthCount = "nom::number::complete::be_u8")]
+    pub data: Vec<u8>,
+    #[nom(Take = "2")]
+    pub extra: &'a [u8],
+    #[nom(Checksum(crc32))]
+    pub crc: u32,
+}
+
+/// Checksum before the covered fields
+#[derive(Debug, PartialEq, NomLE)]
+struct RecordWithLeadingChecksum {
+    #[nom(Checksum = "adler32, a..=b")]
+    pub checksum: u32,
+    pub a: u16,
+    pub b: u16,
+    pub c: u16,
+}
+
+/// Checksum over fixed offsets
+#[derive(Debug, PartialEq, Nom)]
+struct RecordWithOffsets {
+    pub a: u16,
+    pub b: u16,
+    #[nom(Checksum(internet, 2..4))]
+    pub checksum: u16,
+}
+
+fn xor_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |acc, b| acc ^ b)
+}
+
+/// User checksum function, over a range of fields
+#[derive(Debug, PartialEq, Nom)]
+struct RecordWithUserChecksum {
+    pub a: u8,
+    pub b: u8,
+    pub c: u8,
+    #[nom(Checksum(xor_checksum, b..))]
+    pub checksum: u8,
+}
+
+fn check_verify_error<T: std::fmt::Debug>(res: nom::IResult<&[u8], T>) {
+    match res {
+        Err(nom::Err::Error(e)) => assert_eq!(e.code, ErrorKind::Verify),
+        _ => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_checksum_crc32() {
+    let mut input = b"\x00\x01\x03abcde".to_vec();
+    let crc = checksum::crc32(&input);
+    input.extend_from_slice(&crc.to_be_bytes());
+    let (rem, res) = RecordWithCrc::parse(&input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.crc, crc);
+    // change one byte
+    input[3] = b'A';
+    check_verify_error(RecordWithCrc::parse(&input));
+}
+
+#[test]
+fn test_checksum_leading() {
+    let mut input = checksum::adler32(b"\x01\x00\x02\x00")
+        .to_le_bytes()
+        .to_vec();
+    input.extend_from_slice(b"\x01\x00\x02\x00\x03\x00");
+    let (_, res) = RecordWithLeadingChecksum::parse(&input).expect("parsing failed");
+    assert_eq!((res.a, res.b, res.c), (1, 2, 3));
+    input[8] = 4; // c is not covered
+    assert!(RecordWithLeadingChecksum::parse(&input).is_ok());
+    input[6] = 4;
+    check_verify_error(RecordWithLeadingChecksum::parse(&input));
+}
+
+#[test]
+fn test_checksum_offsets_and_user_fn() {
+    let input = b"\x00\x01\x12\x34\xed\xcb";
+    let (_, res) = RecordWithOffsets::parse(input).expect("parsing failed");
+    assert_eq!(res.checksum, 0xedcb);
+    check_verify_error(RecordWithOffsets::parse(b"\x00\x01\x12\x35\xed\xcb"));
+
+    let (_, res) = RecordWithUserChecksum::parse(b"\xff\x01\x02\x03").expect("parsing failed");
+    assert_eq!(res.checksum, 3);
+    check_verify_error(RecordWithUserChecksum::parse(b"\xff\x01\x02\x04"));
+}
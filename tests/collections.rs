@@ -0,0 +1,382 @@
+#![cfg(feature = "std")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind, ParseError};
+use nom::Err;
+use nom_derive::*;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+#[derive(Debug, PartialEq, Nom)]
+struct Deque {
+    n: u8,
+    #[nom(Count = "n")]
+    items: VecDeque<u16>,
+}
+
+#[test]
+fn test_count_vecdeque() {
+    let input = b"\x02\x00\x01\x00\x02\xff";
+    let res = Deque::parse(input);
+    assert_eq!(
+        res,
+        Ok((
+            &input[5..],
+            Deque {
+                n: 2,
+                items: VecDeque::from(vec![1, 2])
+            }
+        ))
+    );
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian)]
+struct BoxedSlice {
+    #[nom(LengthCount = "nom::number::complete::le_u8")]
+    items: Box<[u16]>,
+}
+
+#[test]
+fn test_length_count_boxed_slice() {
+    let input = b"\x02\x01\x00\x02\x00";
+    let res = BoxedSlice::parse(input);
+    assert_eq!(
+        res,
+        Ok((
+            &input[5..],
+            BoxedSlice {
+                items: vec![1, 2].into_boxed_slice()
+            }
+        ))
+    );
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Maps {
+    n: u8,
+    #[nom(Count = "n")]
+    btree: BTreeMap<u8, u16>,
+    #[nom(Count = "1")]
+    hash: HashMap<u8, u8>,
+}
+
+#[test]
+fn test_count_maps() {
+    let input = b"\x02\x02\x00\x20\x01\x00\x10\x05\x06";
+    let (rem, res) = Maps::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    let btree: BTreeMap<_, _> = vec![(1, 0x10), (2, 0x20)].into_iter().collect();
+    assert_eq!(res.btree, btree);
+    assert_eq!(res.hash.get(&5), Some(&6));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct SerializeMaps {
+    n: u8,
+    #[nom(Count = "n")]
+    btree: BTreeMap<u8, u16>,
+    #[nom(Count = "1")]
+    hash: HashMap<u8, u8>,
+    #[nom(LengthCount = "nom::number::complete::be_u8")]
+    more: BTreeMap<u8, u8>,
+}
+
+#[test]
+fn test_serialize_maps() {
+    let input = b"\x02\x01\x00\x10\x02\x00\x20\x05\x06\x01\x07\x08";
+    let (rem, res) = SerializeMaps::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    let mut out = Vec::new();
+    res.serialize(&mut out).expect("serialization failed");
+    assert_eq!(out, input);
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct CondVec {
+    a: u8,
+    #[nom(Cond = "a == 1")]
+    b: Vec<u16>,
+}
+
+#[test]
+fn test_cond_vec() {
+    let input = b"\x01\x00\x02";
+    let res = CondVec::parse(input);
+    assert_eq!(res, Ok((&input[3..], CondVec { a: 1, b: vec![2] })));
+    let input = b"\x00\x00\x02";
+    let res = CondVec::parse(input);
+    assert_eq!(res, Ok((&input[1..], CondVec { a: 0, b: vec![] })));
+}
+
+/// A user collection, keeping only the sum of items
+#[derive(Debug, PartialEq)]
+struct Sum(u32);
+
+impl FromParsedIter for Sum {
+    type Item = u8;
+
+    fn from_parsed_iter<It: Iterator<Item = u8>>(iter: It) -> Option<Self> {
+        Some(Sum(iter.map(u32::from).sum()))
+    }
+}
+
+type Words = VecDeque<u16>;
+
+#[derive(Debug, PartialEq, Nom)]
+struct UserTypes {
+    #[nom(Count = "3")]
+    sum: Sum,
+    #[nom(Count = "1")]
+    words: Words,
+}
+
+#[test]
+fn test_count_user_types() {
+    let input = b"\x01\x02\x03\x12\x34";
+    let res = UserTypes::parse(input);
+    assert_eq!(
+        res,
+        Ok((
+            &input[5..],
+            UserTypes {
+                sum: Sum(6),
+                words: VecDeque::from(vec![0x1234])
+            }
+        ))
+    );
+}
+
+/// A user collection, with a generic parameter which is not the type of items
+#[derive(Debug, PartialEq)]
+struct Wrapper<T> {
+    entries: Vec<Entry>,
+    tag: core::marker::PhantomData<T>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Entry {
+    key: u8,
+    value: u8,
+}
+
+#[derive(Debug, PartialEq)]
+struct Tag;
+
+impl<T> FromParsedIter for Wrapper<T> {
+    type Item = Entry;
+
+    fn from_parsed_iter<It: Iterator<Item = Entry>>(iter: It) -> Option<Self> {
+        Some(Wrapper {
+            entries: iter.collect(),
+            tag: core::marker::PhantomData,
+        })
+    }
+}
+
+type Entries = Wrapper<Tag>;
+
+#[derive(Debug, PartialEq, Nom)]
+struct GenericArgumentNotItem {
+    #[nom(Count = "1")]
+    wrapper: Wrapper<Tag>,
+    #[nom(Count = "1")]
+    alias: Entries,
+    #[nom(Count = "2")]
+    arrays: Vec<[u8; 2]>,
+}
+
+#[test]
+fn test_count_item_type() {
+    let input = b"\x01\x02\x03\x04\x05\x06\x07\x08";
+    let (rem, res) = GenericArgumentNotItem::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.wrapper.entries, vec![Entry { key: 1, value: 2 }]);
+    assert_eq!(res.alias.entries, vec![Entry { key: 3, value: 4 }]);
+    assert_eq!(res.arrays, vec![[5, 6], [7, 8]]);
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct GenericErrorsItems {
+    #[nom(Count = "2")]
+    chars: Vec<char>,
+}
+
+#[derive(Debug, PartialEq)]
+struct CustomError;
+
+impl<I> ParseError<I> for CustomError {
+    fn from_error_kind(_input: I, _kind: ErrorKind) -> Self {
+        CustomError
+    }
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I, E> nom::error::FromExternalError<I, E> for CustomError {
+    fn from_external_error(_input: I, _kind: ErrorKind, _e: E) -> Self {
+        CustomError
+    }
+}
+
+#[test]
+fn test_count_generic_errors() {
+    let input = b"\x00\x00\x00\x61\x00\x00\x00\x62";
+    let res: nom::IResult<_, _, CustomError> = GenericErrorsItems::parse(&input[..]);
+    assert_eq!(res.map(|(_, s)| s.chars), Ok(vec!['a', 'b']));
+    let input = b"\x00\x00\xd8\x00\x00\x00\x00\x62";
+    let res: nom::IResult<_, _, CustomError> = GenericErrorsItems::parse(&input[..]);
+    assert_eq!(res, Err(Err::Error(CustomError)));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct CondOption {
+    #[nom(Count = "2")]
+    a: Option<u8>,
+}
+
+#[test]
+fn test_count_too_large() {
+    let input = b"\x01\x02";
+    let res = CondOption::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[..], ErrorKind::TooLarge)))
+    );
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn test_count_smallvec() {
+    use smallvec::SmallVec;
+
+    #[derive(Debug, PartialEq, Nom)]
+    struct S {
+        #[nom(Count = "3")]
+        items: SmallVec<[u16; 2]>,
+    }
+
+    let input = b"\x00\x01\x00\x02\x00\x03";
+    let (rem, res) = S::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.items.as_slice(), &[1, 2, 3]);
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn test_count_heapless() {
+    #[derive(Debug, PartialEq, Nom)]
+    struct S {
+        n: u8,
+        #[nom(Count = "n")]
+        items: heapless::Vec<u8, 2>,
+    }
+
+    let input = b"\x02\x01\x02";
+    let (rem, res) = S::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.items.as_slice(), &[1, 2]);
+    let input = b"\x03\x01\x02\x03";
+    let res = S::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[1..], ErrorKind::TooLarge)))
+    );
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn test_count_arrayvec() {
+    use arrayvec::ArrayVec;
+
+    #[derive(Debug, PartialEq, Nom)]
+    struct S {
+        #[nom(LengthCount = "nom::number::complete::be_u8")]
+        items: ArrayVec<u16, 4>,
+    }
+
+    let input = b"\x01\x12\x34";
+    let (rem, res) = S::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.items.as_slice(), &[0x1234]);
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct TcpOption {
+    kind: u8,
+    #[nom(Cond = "kind != 0")]
+    len: Option<u8>,
+}
+
+type Options = Vec<TcpOption>;
+
+#[derive(Debug, PartialEq, Nom)]
+struct ElemAlias {
+    n: u8,
+    #[nom(Count = "n", Elem = "TcpOption")]
+    options: Options,
+    #[nom(RepeatUntil = "|o| o.kind == 0", Elem = "TcpOption")]
+    more: Options,
+}
+
+#[test]
+fn test_elem_alias() {
+    let input = b"\x01\x02\x04\x01\x03\x00";
+    let res = ElemAlias::parse(input);
+    let expected = ElemAlias {
+        n: 1,
+        options: vec![TcpOption {
+            kind: 2,
+            len: Some(4),
+        }],
+        more: vec![
+            TcpOption {
+                kind: 1,
+                len: Some(3),
+            },
+            TcpOption { kind: 0, len: None },
+        ],
+    };
+    assert_eq!(res, Ok((&input[6..], expected)));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct ElemGeneric<C> {
+    n: u8,
+    #[nom(Count = "n", Elem = "u16")]
+    items: C,
+}
+
+#[test]
+fn test_elem_generic() {
+    let input = b"\x02\x00\x01\x00\x02";
+    let res = <ElemGeneric<VecDeque<u16>>>::parse(input);
+    assert_eq!(
+        res,
+        Ok((
+            &input[5..],
+            ElemGeneric {
+                n: 2,
+                items: VecDeque::from(vec![1, 2])
+            }
+        ))
+    );
+    let res = <ElemGeneric<Sum16>>::parse(input);
+    assert_eq!(res.map(|(_, s)| s.items), Ok(Sum16(3)));
+}
+
+#[derive(Debug, PartialEq)]
+struct Sum16(u32);
+
+impl FromParsedIter for Sum16 {
+    type Item = u16;
+
+    fn from_parsed_iter<It: Iterator<Item = u16>>(iter: It) -> Option<Self> {
+        Some(Sum16(iter.map(u32::from).sum()))
+    }
+}
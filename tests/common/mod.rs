@@ -0,0 +1,15 @@
+//! Helpers shared by integration tests
+
+use nom_derive::{Parse, Serialize};
+
+/// Parse `input`, then serialize the result and check it gives back the same bytes
+pub fn check_roundtrip<'a, T>(input: &'a [u8])
+where
+    T: Parse<&'a [u8]> + Serialize + std::fmt::Debug,
+{
+    let (rem, obj) = T::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    let mut out = Vec::new();
+    obj.serialize(&mut out).expect("serialization failed");
+    assert_eq!(out, input);
+}
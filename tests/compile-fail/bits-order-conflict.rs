@@ -0,0 +1,11 @@
+use nom_derive::Nom;
+
+#[derive(Debug, PartialEq, Nom)]
+struct S {
+    #[nom(Bits = 4)]
+    a: u8,
+    #[nom(Bits = 4, BitOrder = "lsb")]
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use nom_derive::Nom;
+
+#[derive(Debug, PartialEq, Nom)]
+struct S {
+    #[nom(Bits = 4)]
+    a: u8,
+    #[nom(Bits = 3)]
+    b: u8,
+    c: u16,
+}
+
+fn main() {}
@@ -0,0 +1,193 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{ContextError, ErrorKind, ParseError};
+use nom_derive::collections::IndexContextError;
+use nom_derive::*;
+
+/// An error type recording the contexts, from outermost to innermost
+#[derive(Debug, PartialEq)]
+struct PathError {
+    kind: ErrorKind,
+    path: Vec<&'static str>,
+}
+
+impl<I> ParseError<I> for PathError {
+    fn from_error_kind(_input: I, kind: ErrorKind) -> Self {
+        PathError { kind, path: vec![] }
+    }
+
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> ContextError<I> for PathError {
+    fn add_context(_input: I, ctx: &'static str, mut other: Self) -> Self {
+        other.path.insert(0, ctx);
+        other
+    }
+}
+
+impl<I> IndexContextError<I> for PathError {}
+
+/// An error type recording the indices of elements, from outermost to innermost
+#[derive(Debug, PartialEq)]
+struct IndexError(Vec<usize>);
+
+impl<I> ParseError<I> for IndexError {
+    fn from_error_kind(_input: I, _kind: ErrorKind) -> Self {
+        IndexError(vec![])
+    }
+
+    fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> ContextError<I> for IndexError {}
+
+impl<I> IndexContextError<I> for IndexError {
+    fn add_index(_input: I, idx: usize, mut other: Self) -> Self {
+        other.0.insert(0, idx);
+        other
+    }
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors)]
+struct TcpOption {
+    kind: u8,
+    #[nom(Verify = "*len >= 2")]
+    len: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors)]
+struct Header {
+    version: u8,
+    #[nom(Count = "2")]
+    options: Vec<TcpOption>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors, Complete)]
+struct Packet {
+    header: Header,
+    tail: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors, Complete, Selector = "u8")]
+enum Message {
+    #[nom(Selector = "0")]
+    Empty,
+    #[nom(Selector = "1")]
+    Data(u8, u16),
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors)]
+struct OptionList {
+    #[nom(LengthCount = "nom::number::streaming::be_u8")]
+    options: Vec<TcpOption>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context)]
+struct DefaultErrors(u8, u16);
+
+#[test]
+fn test_context_path() {
+    let res: nom::IResult<_, _, PathError> = Packet::parse(b"\x01\x02\x04\x03\x01\x00\x00");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(PathError {
+            kind: ErrorKind::Verify,
+            path: vec!["Packet.header", "Header.options", "[1]", "TcpOption.len"],
+        }))
+    );
+
+    let res: nom::IResult<_, _, PathError> = Packet::parse(b"\x01\x02\x04\x03\x04\x00");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(PathError {
+            kind: ErrorKind::Complete,
+            path: vec!["Packet.tail"],
+        }))
+    );
+
+    let input = b"\x01\x02\x04\x03\x04\x00\x00";
+    let res: nom::IResult<_, _, PathError> = Packet::parse(input);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_context_index() {
+    let mut input = vec![12];
+    for _ in 0..11 {
+        input.extend_from_slice(b"\x01\x02");
+    }
+    input.extend_from_slice(b"\x01\x00");
+    let res: nom::IResult<_, _, PathError> = OptionList::parse(&input);
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(PathError {
+            kind: ErrorKind::Verify,
+            path: vec!["OptionList.options", "[11]", "TcpOption.len"],
+        }))
+    );
+    assert_eq!(nom_derive::collections::index_context(255), "[255]");
+    assert_eq!(nom_derive::collections::index_context(256), "[..]");
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Context, GenericErrors)]
+struct LongOptionList {
+    n: u16,
+    #[nom(Count = "n")]
+    options: Vec<TcpOption>,
+}
+
+#[test]
+fn test_context_large_index() {
+    let mut input = vec![0x01, 0x2c];
+    for _ in 0..256 {
+        input.extend_from_slice(b"\x01\x02");
+    }
+    input.extend_from_slice(b"\x01\x00");
+    // the default implementation cannot record the index in a static context
+    let res: nom::IResult<_, _, PathError> = LongOptionList::parse(&input);
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(PathError {
+            kind: ErrorKind::Verify,
+            path: vec!["LongOptionList.options", "[..]", "TcpOption.len"],
+        }))
+    );
+    let res: nom::IResult<_, _, IndexError> = LongOptionList::parse(&input);
+    assert_eq!(res, Err(nom::Err::Error(IndexError(vec![256]))));
+}
+
+#[test]
+fn test_context_enum() {
+    let res: nom::IResult<_, _, PathError> = Message::parse(&b"\x02\x00"[..], 1);
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(PathError {
+            kind: ErrorKind::Complete,
+            path: vec!["Message::Data.1"],
+        }))
+    );
+    let res: nom::IResult<_, _, PathError> = Message::parse(&b""[..], 0);
+    assert_eq!(res, Ok((&b""[..], Message::Empty)));
+}
+
+#[test]
+fn test_context_default_errors() {
+    let res = DefaultErrors::parse(b"\x01\x00\x02");
+    assert_eq!(res, Ok((&b""[..], DefaultErrors(1, 2))));
+}
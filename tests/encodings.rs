@@ -0,0 +1,190 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+mod common;
+
+use common::check_roundtrip;
+use nom::error::{Error, ErrorKind, FromExternalError, ParseError};
+use nom::number::Endianness;
+use nom::IResult;
+use nom_derive::strings::EncodingError;
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, Serialize)]
+struct UsbStringDescriptor {
+    b_length: u8,
+    b_descriptor_type: u8,
+    // length in bytes
+    #[nom(Encoding = "utf16le", Take = "b_length - 2")]
+    string: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Utf16Strings {
+    n: u8,
+    // length in code units
+    #[nom(Encoding = "utf16be", Count = "n")]
+    be: String,
+    #[nom(Encoding = "utf16le", NulTerminated)]
+    le: String,
+    #[nom(Encoding = "utf16be", LengthPrefix = "u16")]
+    prefixed: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct FieldEndian {
+    // uses the endianness of the struct
+    #[nom(Encoding = "utf16", NulTerminated)]
+    default: String,
+    #[nom(Encoding = "utf16", LittleEndian, NulTerminated)]
+    le: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian)]
+struct LittleEndianStruct {
+    #[nom(Encoding = "utf16", Count = 2)]
+    s: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(ExtraArgs(endian: Endianness))]
+struct DynamicEndian {
+    #[nom(Encoding = "utf16", SetEndian(endian), Count = 2)]
+    s: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct LegacyStrings {
+    #[nom(Encoding = "latin1", Take = 4)]
+    latin1: String,
+    #[nom(Encoding = "windows-1252", NulTerminated)]
+    windows: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct Generic {
+    #[nom(Encoding = "utf16le", Count = 1)]
+    s: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct EncodingFailure(Option<EncodingError>);
+
+impl<I> ParseError<I> for EncodingFailure {
+    fn from_error_kind(_: I, _: ErrorKind) -> Self {
+        EncodingFailure(None)
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> FromExternalError<I, EncodingError> for EncodingFailure {
+    fn from_external_error(_: I, _: ErrorKind, e: EncodingError) -> Self {
+        EncodingFailure(Some(e))
+    }
+}
+
+#[test]
+fn test_utf16() {
+    let input = b"\x08\x03a\x00\xe9\x00c\x00";
+    let res = UsbStringDescriptor::parse(input);
+    let expected = UsbStringDescriptor {
+        b_length: 8,
+        b_descriptor_type: 3,
+        string: "a\u{e9}c".to_string(),
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    check_roundtrip::<UsbStringDescriptor>(input);
+
+    let input = b"\x03\x00a\xd8\x3d\xde\x00b\x00\x00\x00\x00\x04\x00x\x00y";
+    let res = Utf16Strings::parse(input);
+    let expected = Utf16Strings {
+        n: 3,
+        be: "a\u{1f600}".to_string(),
+        le: "b".to_string(),
+        prefixed: "xy".to_string(),
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    check_roundtrip::<Utf16Strings>(input);
+
+    // missing NUL code unit
+    let res = Utf16Strings::parse(b"\x00b\x00\x00");
+    assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+}
+
+#[test]
+fn test_utf16_endianness() {
+    let input = b"\x00a\x00\x00b\x00\x00\x00";
+    let res = FieldEndian::parse_le(input);
+    let expected = FieldEndian {
+        default: "\u{6100}".to_string(),
+        le: "b".to_string(),
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    let (_, s) = FieldEndian::parse(input).expect("parsing failed");
+    assert_eq!(s.default, "a");
+    check_roundtrip::<FieldEndian>(input);
+
+    let res = LittleEndianStruct::parse(b"a\x00b\x00");
+    assert_eq!(res.map(|(_, s)| s.s), Ok("ab".to_string()));
+
+    let res = DynamicEndian::parse(b"a\x00b\x00", Endianness::Little);
+    assert_eq!(res.map(|(_, s)| s.s), Ok("ab".to_string()));
+    let res = DynamicEndian::parse(b"\x00a\x00b", Endianness::Big);
+    assert_eq!(res.map(|(_, s)| s.s), Ok("ab".to_string()));
+}
+
+#[test]
+fn test_legacy_encodings() {
+    let input = b"caf\xe9\x80 \x9f\x00";
+    let res = LegacyStrings::parse(input);
+    let expected = LegacyStrings {
+        latin1: "caf\u{e9}".to_string(),
+        windows: "\u{20ac} \u{178}".to_string(),
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    check_roundtrip::<LegacyStrings>(input);
+
+    // 0x81 is not defined in Windows-1252
+    let res = LegacyStrings::parse(b"abcd\x81\x00");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(Error::new(
+            &b"\x81\x00"[..],
+            ErrorKind::MapRes
+        )))
+    );
+
+    let s = LegacyStrings {
+        latin1: "\u{20ac}".to_string(),
+        windows: String::new(),
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+}
+
+#[test]
+fn test_encoding_errors() {
+    let res: IResult<_, _, Error<&[u8]>> = Generic::parse(b"a\x00");
+    assert_eq!(res.map(|(_, g)| g.s), Ok("a".to_string()));
+
+    // unpaired surrogate
+    let res: IResult<_, _, EncodingFailure> = Generic::parse(b"\x00\xd8");
+    assert!(matches!(
+        res,
+        Err(nom::Err::Error(EncodingFailure(Some(
+            EncodingError::Utf16(_)
+        ))))
+    ));
+}
@@ -122,6 +122,41 @@ pub enum U9 {
     Field2(u32),
 }
 
+/// A fieldless enum with a catch-all variant
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+#[repr(u16)]
+pub enum U10 {
+    A = 1,
+    B,
+    C = 8,
+    #[nom(Unknown)]
+    Other(u16),
+}
+
+/// An enum with a catch-all variant, capturing the selector and the payload
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Selector = "u8", Serialize)]
+pub enum U11<'a> {
+    #[nom(Selector = "0")]
+    Field1(u16),
+    #[nom(Selector = "_", Unknown)]
+    Other(u8, &'a [u8]),
+}
+
+/// An enum with a catch-all variant with named fields, and owned payload
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Selector = "MessageType")]
+pub enum U12 {
+    #[nom(Unknown)]
+    Other {
+        msg_type: MessageType,
+        data: Vec<u8>,
+    },
+    #[nom(Selector = "MessageType(0)")]
+    Field1(u16),
+}
+
 #[test]
 fn test_enum_unnamed() {
     let input = b"\x00\x00\x00\x02";
@@ -190,3 +225,46 @@ fn test_struct_in_enum() {
     let res = U8::parse(input, 1);
     assert_eq!(res, Ok((&input[4..], U8::Field2(2))));
 }
+
+#[test]
+fn test_enum_fieldless_unknown() {
+    let empty: &[u8] = b"";
+    assert_eq!(U10::parse(b"\x00\x01"), Ok((empty, U10::A)));
+    assert_eq!(U10::parse(b"\x00\x02"), Ok((empty, U10::B)));
+    assert_eq!(U10::parse(b"\x00\x08"), Ok((empty, U10::C)));
+    assert_eq!(U10::parse(b"\x01\x00"), Ok((empty, U10::Other(256))));
+    assert_eq!(U10::parse_le(b"\x02\x00"), Ok((empty, U10::B)));
+
+    #[cfg(feature = "alloc")]
+    {
+        let mut out = Vec::new();
+        U10::C.serialize(&mut out).unwrap();
+        U10::Other(0x1234).serialize(&mut out).unwrap();
+        assert_eq!(out, b"\x00\x08\x12\x34");
+    }
+}
+
+#[test]
+fn test_enum_unknown_payload() {
+    let input = b"\x00\x02";
+    let res = U11::parse(input, 0);
+    assert_eq!(res, Ok((&input[2..], U11::Field1(2))));
+    let res = U11::parse(input, 7);
+    assert_eq!(res, Ok((&input[2..], U11::Other(7, &input[..]))));
+
+    #[cfg(feature = "alloc")]
+    {
+        let mut out = Vec::new();
+        U11::Other(7, b"abc").serialize(&mut out).unwrap();
+        assert_eq!(out, b"abc");
+    }
+
+    let res = U12::parse(input, MessageType(3));
+    let expected = U12::Other {
+        msg_type: MessageType(3),
+        data: input.to_vec(),
+    };
+    assert_eq!(res, Ok((&input[2..], expected)));
+    let res = U12::parse(input, MessageType(0));
+    assert_eq!(res, Ok((&input[2..], U12::Field1(2))));
+}
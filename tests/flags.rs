@@ -0,0 +1,116 @@
+#![cfg(feature = "bitflags")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use bitflags::bitflags;
+use nom::error::{Error, ErrorKind};
+use nom_derive::*;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TcpFlags: u8 {
+        const FIN = 0x01;
+        const SYN = 0x02;
+        const RST = 0x04;
+        const ACK = 0x10;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Access: u16 {
+        const READ = 0x0001;
+        const WRITE = 0x0002;
+        const EXEC = 0x0100;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Nibble: u8 {
+        const A = 0b0001;
+        const B = 0b1000;
+    }
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Policies {
+    #[nom(Flags)]
+    truncate: TcpFlags,
+    #[nom(Flags = "retain")]
+    retain: TcpFlags,
+    #[nom(Flags = "strict")]
+    strict: TcpFlags,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, Serialize)]
+struct Endian {
+    #[nom(Flags)]
+    le: Access,
+    #[nom(Flags, BigEndian)]
+    be: Access,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Packed {
+    #[nom(Bits = 4, Flags = "strict")]
+    flags: Nibble,
+    #[nom(Bits = 4)]
+    version: u8,
+}
+
+#[test]
+fn test_flags_policies() {
+    let input = b"\x32\x32\x12";
+    let (rem, p) = Policies::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(p.truncate, TcpFlags::SYN | TcpFlags::ACK);
+    assert_eq!(p.retain.bits(), 0x32);
+    assert_eq!(p.strict, TcpFlags::SYN | TcpFlags::ACK);
+
+    let mut out = Vec::new();
+    p.serialize(&mut out).expect("serialization failed");
+    assert_eq!(out, b"\x12\x32\x12");
+
+    // unknown bit 0x20
+    let res = Policies::parse(b"\x32\x32\x32");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(Error::new(&b"\x32"[..], ErrorKind::MapOpt)))
+    );
+}
+
+#[test]
+fn test_flags_endianness() {
+    let input = b"\x01\x01\x01\x01";
+    let res = Endian::parse(input);
+    let expected = Endian {
+        le: Access::READ | Access::EXEC,
+        be: Access::READ | Access::EXEC,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    let res = Endian::parse(b"\x02\x00\x00\x02");
+    let expected = Endian {
+        le: Access::WRITE,
+        be: Access::WRITE,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+}
+
+#[test]
+fn test_flags_bits() {
+    let (rem, p) = Packed::parse(b"\x94").expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(p.flags, Nibble::A | Nibble::B);
+    assert_eq!(p.version, 4);
+    let mut out = Vec::new();
+    p.serialize(&mut out).expect("serialization failed");
+    assert_eq!(out, b"\x94");
+
+    assert!(Packed::parse(b"\x24").is_err());
+}
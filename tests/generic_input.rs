@@ -0,0 +1,225 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::ParseError;
+use nom::number::streaming::be_u16;
+use nom::number::Endianness;
+use nom::{AsBytes, IResult, Input, Needed};
+use nom_derive::*;
+
+/// An input type wrapping a byte slice, and counting how many times it was split
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TrackedInput<'a> {
+    data: &'a [u8],
+    splits: usize,
+}
+
+impl<'a> TrackedInput<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        TrackedInput { data, splits: 0 }
+    }
+
+    fn with(&self, data: &'a [u8]) -> Self {
+        TrackedInput {
+            data,
+            splits: self.splits + 1,
+        }
+    }
+}
+
+impl<'a> Input for TrackedInput<'a> {
+    type Item = u8;
+    type Iter = <&'a [u8] as Input>::Iter;
+    type IterIndices = <&'a [u8] as Input>::IterIndices;
+
+    fn input_len(&self) -> usize {
+        self.data.len()
+    }
+    fn take(&self, index: usize) -> Self {
+        self.with(&self.data[..index])
+    }
+    fn take_from(&self, index: usize) -> Self {
+        self.with(&self.data[index..])
+    }
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        let (prefix, suffix) = self.data.split_at(index);
+        (self.with(suffix), self.with(prefix))
+    }
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.data.position(predicate)
+    }
+    fn iter_elements(&self) -> Self::Iter {
+        self.data.iter_elements()
+    }
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.data.iter_indices()
+    }
+    fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+        self.data.slice_index(count)
+    }
+}
+
+impl AsBytes for TrackedInput<'_> {
+    fn as_bytes(&self) -> &[u8] {
+        self.data
+    }
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput)]
+struct SimpleStruct {
+    a: u8,
+    #[nom(AlignBefore(4))]
+    b: u16,
+    #[nom(Move(-2))]
+    b_again: u16,
+    #[nom(Count = "a")]
+    v: Vec<u8>,
+    #[nom(Checksum(internet, b..v))]
+    checksum: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput, LittleEndian)]
+struct GenericStruct<T> {
+    #[nom(MoveAbs(1))]
+    t: T,
+    opt: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput, ExtraArgs(endian: Endianness), SetEndian(endian))]
+struct WithExtraArgs<T> {
+    a: u16,
+    #[nom(Bits = 4)]
+    high: u8,
+    #[nom(Bits = 4)]
+    low: u8,
+    t: T,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput, GenericErrors, Selector = "u8")]
+enum SelectorEnum {
+    #[nom(Selector = "0")]
+    A(u16),
+    #[nom(Selector = "1")]
+    B { x: u8, y: u8 },
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput)]
+struct EncodedStrings {
+    n: u8,
+    #[nom(Encoding = "utf16be", Count = "n")]
+    be: String,
+    #[nom(Encoding = "utf16le", NulTerminated)]
+    le: String,
+    #[nom(Encoding = "latin1", LengthPrefix = "u8")]
+    latin1: String,
+}
+
+/// A type with a manual parser, generic over binary inputs
+#[derive(Debug, PartialEq)]
+struct Port(u16);
+
+impl<I, E> Parse<I, E> for Port
+where
+    I: InputSlice,
+    E: ParseError<I>,
+{
+    fn parse(i: I) -> IResult<I, Self, E> {
+        let (i, v) = be_u16(i)?;
+        Ok((i, Port(v)))
+    }
+}
+
+const SIMPLE: &[u8] = b"\x02\x00\x00\x00\x12\x34\x56\x78\xed\xcb";
+
+#[test]
+fn test_generic_input_slice() {
+    // a generic parser can still be used with slices
+    let res: IResult<&[u8], SimpleStruct> = SimpleStruct::parse(SIMPLE);
+    let expected = SimpleStruct {
+        a: 2,
+        b: 0x1234,
+        b_again: 0x1234,
+        v: vec![0x56, 0x78],
+        checksum: 0xedcb,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+}
+
+#[test]
+fn test_generic_input_custom() {
+    let input = TrackedInput::new(SIMPLE);
+    let (rem, s) = SimpleStruct::parse(input).expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert!(rem.splits > 0);
+    assert_eq!((s.a, s.b_again, s.checksum), (2, 0x1234, 0xedcb));
+    // wrong checksum
+    let res = SimpleStruct::parse(TrackedInput::new(
+        b"\x02\x00\x00\x00\x12\x34\x56\x78\xed\xcc",
+    ));
+    assert!(res.is_err());
+
+    let input = TrackedInput::new(b"\xff\x01\x00\x02\x00\x00\x00");
+    let (rem, s) = <GenericStruct<u16>>::parse(input).expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert_eq!(s, GenericStruct { t: 1, opt: Some(2) });
+
+    let input = TrackedInput::new(b"\xff\x00\x50\x02\x00\x00\x00");
+    let (rem, s) = <GenericStruct<Port>>::parse(input).expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert_eq!(
+        s,
+        GenericStruct {
+            t: Port(0x50),
+            opt: Some(2)
+        }
+    );
+}
+
+#[test]
+fn test_generic_input_special_case() {
+    let input = TrackedInput::new(b"\x01\x00\x5a\x02");
+    let (rem, s) = <WithExtraArgs<u8>>::parse(input, Endianness::Little).expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert_eq!(
+        s,
+        WithExtraArgs {
+            a: 1,
+            high: 5,
+            low: 10,
+            t: 2
+        }
+    );
+
+    let input = TrackedInput::new(b"\x12\x34");
+    let res: IResult<_, _, nom::error::Error<_>> = SelectorEnum::parse(input, 1);
+    let (rem, e) = res.expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert_eq!(e, SelectorEnum::B { x: 0x12, y: 0x34 });
+}
+
+#[test]
+fn test_generic_input_encoding() {
+    let input = TrackedInput::new(b"\x01\x00ab\x00\x00\x00\x01\xe9");
+    let (rem, s) = EncodedStrings::parse(input).expect("parsing failed");
+    assert_eq!(rem.input_len(), 0);
+    assert_eq!(
+        s,
+        EncodedStrings {
+            n: 1,
+            be: "a".to_string(),
+            le: "b".to_string(),
+            latin1: "\u{e9}".to_string(),
+        }
+    );
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind};
+use nom::Err;
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Inner {
+    a: u16,
+    b: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Outer {
+    body: LengthValue<u8, Inner>,
+    #[nom(LittleEndian)]
+    items: LengthValue<u16, Vec<u16>>,
+    tail: u8,
+}
+
+#[test]
+fn test_length_value() {
+    let input = b"\x04\x12\x34\x56\xff\x04\x00\x01\x00\x02\x00\xaa";
+    let res = Outer::parse(input);
+    let expected = Outer {
+        body: LengthValue::new(Inner { a: 0x1234, b: 0x56 }),
+        items: LengthValue::new(vec![1, 2]),
+        tail: 0xaa,
+    };
+    assert_eq!(res, Ok((&input[12..], expected)));
+}
+
+#[test]
+fn test_length_value_serialize() {
+    let value = Outer {
+        body: LengthValue::new(Inner { a: 0x1234, b: 0x56 }),
+        items: LengthValue::new(vec![1, 2, 3]),
+        tail: 0xaa,
+    };
+    let mut out = Vec::new();
+    value.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x03\x12\x34\x56\x06\x00\x01\x00\x02\x00\x03\x00\xaa");
+    let (rem, res) = Outer::parse(&out).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res, value);
+}
+
+#[test]
+fn test_length_value_window_too_short() {
+    // the window ends before the value: an error, not Incomplete
+    let input = b"\x02\x12\x34\x56";
+    let res = <LengthValue<u8, Inner>>::parse(&input[..]);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[3..], ErrorKind::Complete)))
+    );
+    // the input ends before the window
+    let res = <LengthValue<u8, Inner>>::parse(&b"\x04\x12\x34"[..]);
+    assert!(matches!(res, Err(Err::Incomplete(_))));
+}
+
+#[test]
+fn test_length_value_strict() {
+    let input = b"\x04\x12\x34\x56\xff";
+    let res = <StrictLengthValue<u8, Inner>>::parse(&input[..]);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[4..], ErrorKind::Eof)))
+    );
+    let input = b"\x03\x12\x34\x56\xff";
+    let res = <StrictLengthValue<u8, Inner>>::parse(&input[..]);
+    let expected = LengthValue::new(Inner { a: 0x1234, b: 0x56 });
+    assert_eq!(res, Ok((&input[4..], expected)));
+}
+
+#[test]
+fn test_length_value_too_large() {
+    let value: LengthValue<u8, Vec<u8>> = LengthValue::new(vec![0; 256]);
+    let mut out = Vec::new();
+    assert_eq!(
+        value.serialize(&mut out),
+        Err(SerializeError::ValueOutOfRange)
+    );
+}
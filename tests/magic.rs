@@ -0,0 +1,113 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind};
+use nom::{Err, IResult};
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Tag(b"\x89PNG"), Serialize)]
+struct PngHeader {
+    version: u8,
+}
+
+#[test]
+fn test_top_level_tag() {
+    let input = b"\x89PNG\x01\xff";
+    let res = PngHeader::parse(input);
+    assert_eq!(res, Ok((&input[5..], PngHeader { version: 1 })));
+    let input = b"\x89PNX\x01";
+    let res = PngHeader::parse(input);
+    assert_eq!(res, Err(Err::Error(Error::new(&input[..], ErrorKind::Tag))));
+    let mut out = Vec::new();
+    PngHeader { version: 1 }.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x89PNG\x01");
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Magic = 0xA1B2C3D4u32, LittleEndian, Serialize)]
+struct PcapHeader {
+    major: u16,
+    minor: u16,
+}
+
+#[test]
+fn test_top_level_magic() {
+    let input = b"\xd4\xc3\xb2\xa1\x02\x00\x04\x00";
+    let res = PcapHeader::parse(input);
+    assert_eq!(res, Ok((&input[8..], PcapHeader { major: 2, minor: 4 })));
+    let input = b"\xa1\xb2\xc3\xd4\x02\x00\x04\x00";
+    let res = PcapHeader::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[..], ErrorKind::Verify)))
+    );
+    let mut out = Vec::new();
+    PcapHeader { major: 2, minor: 4 }
+        .serialize(&mut out)
+        .unwrap();
+    assert_eq!(out, b"\xd4\xc3\xb2\xa1\x02\x00\x04\x00");
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Tag(b"MZ"), Magic = 0x1234u16)]
+struct Both(u8);
+
+#[test]
+fn test_top_level_tag_and_magic() {
+    let input = b"MZ\x12\x34\x05";
+    let res = Both::parse(input);
+    assert_eq!(res, Ok((&input[5..], Both(5))));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Tag(b"E"), Selector = "u8")]
+enum Message {
+    #[nom(Selector = "0")]
+    Ping(u8),
+    #[nom(Selector = "1")]
+    Pong,
+}
+
+#[test]
+fn test_top_level_tag_enum() {
+    let input = b"E\x07";
+    let res = Message::parse(input, 0);
+    assert_eq!(res, Ok((&input[2..], Message::Ping(7))));
+    let res = Message::parse(&input[1..], 1);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[1..], ErrorKind::Tag)))
+    );
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[repr(u8)]
+#[nom(Magic = 0xffu8)]
+enum Kind {
+    A = 1,
+    B = 2,
+}
+
+#[test]
+fn test_top_level_magic_fieldless_enum() {
+    let input = b"\xff\x02";
+    let res = Kind::parse(input);
+    assert_eq!(res, Ok((&input[2..], Kind::B)));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericInput, Tag(b"GI"))]
+struct Generic {
+    a: u8,
+}
+
+#[test]
+fn test_top_level_tag_generic_input() {
+    let input = &b"GI\x01"[..];
+    let res: IResult<&[u8], Generic> = Generic::parse(input);
+    assert_eq!(res, Ok((&input[3..], Generic { a: 1 })));
+}
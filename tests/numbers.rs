@@ -0,0 +1,109 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind};
+use nom::{Err, IResult};
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Header {
+    #[nom(Uint24)]
+    len: u32,
+    #[nom(Int24, LittleEndian)]
+    delta: i32,
+    #[nom(Uint48)]
+    timestamp: u64,
+}
+
+#[test]
+fn test_odd_width_attributes() {
+    let input = b"\x01\x02\x03\xfe\xff\xff\x00\x00\x00\x00\x01\x00\xff";
+    let (rem, res) = Header::parse(input).expect("parsing failed");
+    assert_eq!(rem, b"\xff");
+    assert_eq!(
+        res,
+        Header {
+            len: 0x01_0203,
+            delta: -2,
+            timestamp: 256
+        }
+    );
+    let mut out = Vec::new();
+    res.serialize(&mut out).unwrap();
+    assert_eq!(out, &input[..12]);
+}
+
+#[test]
+fn test_odd_width_out_of_range() {
+    let h = Header {
+        len: 1 << 24,
+        delta: 0,
+        timestamp: 0,
+    };
+    let mut out = Vec::new();
+    assert_eq!(h.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Small {
+    #[nom(Uint24)]
+    value: u16,
+}
+
+#[test]
+fn test_u24_length_prefix() {
+    let res: IResult<_, _> = <LengthData<U24, &[u8]>>::parse_le(&b"\x02\x00\x00abc"[..]);
+    assert_eq!(res, Ok((&b"c"[..], LengthData::new(&b"ab"[..]))));
+}
+
+#[test]
+fn test_u24_too_large() {
+    let input = b"\x00\x01\x00";
+    let res = Small::parse(input);
+    assert_eq!(res, Ok((&input[3..], Small { value: 0x100 })));
+    // value does not fit in the field type
+    let input = b"\x01\x00\x00";
+    let res = Small::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[..], ErrorKind::TooLarge)))
+    );
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, Serialize)]
+struct Exotic {
+    half: F16,
+    brain: BF16,
+    #[nom(BigEndian)]
+    date: Bcd<4>,
+    fixed: Fixed<i32, 16>,
+}
+
+#[test]
+fn test_exotic_numbers() {
+    let input = b"\x00\x3c\x20\xc0\x20\x26\x10\x17\x00\x80\xff\xff";
+    let (rem, res) = Exotic::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(res.half, F16(1.0));
+    assert_eq!(res.brain, BF16(-2.5));
+    assert_eq!(res.date, Bcd(20261017));
+    assert_eq!(res.fixed.to_f64(), -0.5);
+    let mut out = Vec::new();
+    res.serialize(&mut out).unwrap();
+    assert_eq!(out, &input[..]);
+}
+
+#[test]
+fn test_bcd_invalid_digit() {
+    let input = b"\x12\x3a";
+    let res = <Bcd<2>>::parse(&input[..]);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[..], ErrorKind::Verify)))
+    );
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+extern crate alloc;
+
+use nom_derive::*;
+
+mod types {
+    use nom_derive::*;
+
+    #[derive(Debug, PartialEq, Nom)]
+    #[nom(Serialize)]
+    pub struct Point {
+        pub x: u8,
+        pub y: u8,
+    }
+}
+
+/// A struct using qualified type paths
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Qualified<T> {
+    a: ::std::primitive::u16,
+    n: u8,
+    #[nom(Count = "n")]
+    v: std::vec::Vec<u16>,
+    #[nom(Cond = "n > 1")]
+    opt: core::option::Option<u8>,
+    #[nom(LengthCount = "nom::number::streaming::be_u8")]
+    points: alloc::vec::Vec<crate::types::Point>,
+    p: crate::types::Point,
+    phantom: core::marker::PhantomData<T>,
+}
+
+/// A struct using qualified paths for default values
+#[derive(Debug, PartialEq, Nom)]
+struct Defaults {
+    #[nom(Ignore)]
+    v: std::vec::Vec<u8>,
+    #[nom(Ignore)]
+    opt: std::option::Option<u8>,
+    #[nom(Ignore)]
+    phantom: std::marker::PhantomData<u8>,
+}
+
+#[test]
+fn test_qualified_paths() {
+    let input = b"\x00\x01\x02\x00\x03\x00\x04\x05\x01\x06\x07\x08\x09";
+    let res = Qualified::<u32>::parse(input);
+    let expected = Qualified {
+        a: 1,
+        n: 2,
+        v: vec![3, 4],
+        opt: Some(5),
+        points: vec![types::Point { x: 6, y: 7 }],
+        p: types::Point { x: 8, y: 9 },
+        phantom: core::marker::PhantomData,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let (_, q) = Qualified::<u32>::parse(input).expect("parsing failed");
+    let mut out = Vec::new();
+    q.serialize(&mut out).expect("serialization failed");
+    assert_eq!(out, input);
+}
+
+#[test]
+fn test_qualified_paths_defaults() {
+    let res = Defaults::parse(b"");
+    let expected = Defaults {
+        v: Vec::new(),
+        opt: None,
+        phantom: std::marker::PhantomData,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+}
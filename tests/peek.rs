@@ -0,0 +1,71 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+struct V1Header {
+    version: u8,
+    len: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct V2Header {
+    version: u8,
+    flags: u8,
+    len: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Selector = "u8")]
+enum Header {
+    #[nom(Selector = "1")]
+    V1(V1Header),
+    #[nom(Selector = "2")]
+    V2(V2Header),
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Packet {
+    #[nom(Peek)]
+    version: u8,
+    #[nom(Parse = "{ |i| Header::parse(i, version) }")]
+    header: Header,
+    #[nom(Peek, Count = "2")]
+    next: Vec<u8>,
+    tail: u16,
+}
+
+#[test]
+fn test_peek() {
+    let input = b"\x01\x04\x12\x34";
+    let res = Packet::parse(input);
+    let expected = Packet {
+        version: 1,
+        header: Header::V1(V1Header { version: 1, len: 4 }),
+        next: vec![0x12, 0x34],
+        tail: 0x1234,
+    };
+    assert_eq!(res, Ok((&input[4..], expected)));
+
+    let input = b"\x02\x80\x00\x08\x12\x34";
+    let (rem, p) = Packet::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        p.header,
+        Header::V2(V2Header {
+            version: 2,
+            flags: 0x80,
+            len: 8
+        })
+    );
+}
+
+#[test]
+fn test_peek_incomplete() {
+    let res = Packet::parse(b"\x01\x04\x12");
+    assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+struct Header {
+    name_offset: u16,
+    #[nom(AtOffset = "name_offset")]
+    name: [u8; 4],
+    opt_offset: u8,
+    #[nom(Pointer = "opt_offset")]
+    extra: Option<u16>,
+    version: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, ExtraArgs(table: &'nom [u8]))]
+struct Entry {
+    offset: u8,
+    #[nom(AtOffset = "offset", OffsetBase = "table")]
+    value: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Table {
+    n: u8,
+    #[nom(Parse = "{ |i| nom::multi::count(|x| Entry::parse(x, orig_i), n as usize).parse(i) }")]
+    entries: Vec<Entry>,
+}
+
+#[test]
+fn test_at_offset() {
+    let input = b"\x00\x04\x08\x02abcd\x12\x34";
+    let res = Header::parse(input);
+    let expected = Header {
+        name_offset: 4,
+        name: *b"abcd",
+        opt_offset: 8,
+        extra: Some(0x1234),
+        version: 2,
+    };
+    // the cursor continues after the offset fields
+    assert_eq!(res, Ok((&input[4..], expected)));
+}
+
+#[test]
+fn test_at_offset_null() {
+    let input = b"\x00\x04\x00\x01abcd";
+    let (rem, h) = Header::parse(input).expect("parsing failed");
+    assert_eq!(rem, &input[4..]);
+    assert_eq!(h.extra, None);
+    assert_eq!(h.version, 1);
+}
+
+#[test]
+fn test_at_offset_out_of_range() {
+    // pointer past the end of the input
+    let input = b"\x00\x10\x00\x01abcd";
+    let res = Header::parse(input);
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(nom::error::Error::new(
+            &input[..],
+            nom::error::ErrorKind::Eof
+        )))
+    );
+    // pointer past the end of the base input
+    let table = b"\x00\x01";
+    let res = Entry::parse(b"\x03", table);
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(nom::error::Error::new(
+            &table[..],
+            nom::error::ErrorKind::Eof
+        )))
+    );
+}
+
+#[test]
+fn test_offset_base() {
+    let input = b"\x02\x03\x05\x01\x00\x02\x00";
+    let res = Table::parse(input);
+    let expected = Table {
+        n: 2,
+        entries: vec![
+            Entry {
+                offset: 3,
+                value: 1,
+            },
+            Entry {
+                offset: 5,
+                value: 2,
+            },
+        ],
+    };
+    assert_eq!(res, Ok((&input[3..], expected)));
+}
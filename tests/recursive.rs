@@ -0,0 +1,156 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::Error;
+use nom::IResult;
+use nom_derive::*;
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A linked list, stored as a sequence of values with a "next" flag
+#[derive(Debug, PartialEq, Nom)]
+struct Node {
+    value: u8,
+    has_next: u8,
+    #[nom(Cond = "has_next != 0")]
+    next: Option<Box<Node>>,
+}
+
+#[test]
+fn test_recursive_struct() {
+    let input = b"\x01\x01\x02\x01\x03\x00\xff";
+    let res = Node::parse(input);
+    let expected = Node {
+        value: 1,
+        has_next: 1,
+        next: Some(Box::new(Node {
+            value: 2,
+            has_next: 1,
+            next: Some(Box::new(Node {
+                value: 3,
+                has_next: 0,
+                next: None,
+            })),
+        })),
+    };
+    assert_eq!(res, Ok((&input[6..], expected)));
+}
+
+/// An expression tree, using a selector for the operator
+#[derive(Debug, PartialEq, Nom)]
+struct Expr {
+    op: u8,
+    #[nom(Parse = "{ |i| ExprKind::parse(i, op) }")]
+    kind: ExprKind,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Selector = "u8")]
+enum ExprKind {
+    #[nom(Selector = "0")]
+    Lit(u16),
+    #[nom(Selector = "1")]
+    Neg(Box<Expr>),
+    #[nom(Selector = "2")]
+    Add(Box<Expr>, Box<Expr>),
+}
+
+#[test]
+fn test_recursive_enum() {
+    // -(1 + 2)
+    let input = b"\x01\x02\x00\x00\x01\x00\x00\x02";
+    let res = Expr::parse(input);
+    let lit = |v| {
+        Box::new(Expr {
+            op: 0,
+            kind: ExprKind::Lit(v),
+        })
+    };
+    let expected = Expr {
+        op: 1,
+        kind: ExprKind::Neg(Box::new(Expr {
+            op: 2,
+            kind: ExprKind::Add(lit(1), lit(2)),
+        })),
+    };
+    assert_eq!(res, Ok((&input[8..], expected)));
+}
+
+/// Nested TLV containers, with borrowed values and generic errors
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct Tlv<'a> {
+    tag: u8,
+    len: u8,
+    #[nom(Cond = "tag & 0x20 == 0", Take = "len")]
+    value: Option<&'a [u8]>,
+    #[nom(Cond = "tag & 0x20 != 0", Count = "len")]
+    children: Option<Vec<Tlv<'a>>>,
+}
+
+#[test]
+fn test_recursive_lifetime() {
+    let input = b"\x30\x02\x02\x01\x05\x04\x00";
+    let res: IResult<_, _, Error<_>> = Tlv::parse(input);
+    let (rem, tlv) = res.expect("parsing failed");
+    assert!(rem.is_empty());
+    let children = tlv.children.expect("no children");
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].value, Some(&b"\x05"[..]));
+    assert_eq!(children[1].value, Some(&b""[..]));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian)]
+struct Shared<'a> {
+    a: Rc<u16>,
+    b: Arc<u16>,
+    #[nom(Count = "2")]
+    c: Box<[u8]>,
+    d: Cow<'a, [u8]>,
+}
+
+#[test]
+fn test_pointers() {
+    let input = b"\x01\x00\x02\x00\x03\x04\x05\x06";
+    let res: IResult<_, _, Error<_>> = Shared::parse(input);
+    let expected = Shared {
+        a: Rc::new(1),
+        b: Arc::new(2),
+        c: Box::new([3, 4]),
+        d: Cow::Borrowed(&input[6..]),
+    };
+    assert_eq!(res, Ok((&input[8..], expected)));
+    let res = <Box<Node>>::parse(b"\x01");
+    assert_eq!(res, Err(nom::Err::Incomplete(nom::Needed::new(1))));
+}
+
+/// A generic tree: only `T` is required to implement `Parse`
+#[derive(Debug, PartialEq, Nom)]
+struct Tree<T> {
+    value: T,
+    n: u8,
+    #[nom(Count = "n")]
+    children: Vec<Tree<T>>,
+}
+
+#[test]
+fn test_recursive_generic() {
+    let input = b"\x00\x01\x02\x00\x02\x00\x00\x03\x00";
+    let res = <Tree<u16>>::parse(input);
+    let leaf = |value| Tree {
+        value,
+        n: 0,
+        children: vec![],
+    };
+    let expected = Tree {
+        value: 1,
+        n: 2,
+        children: vec![leaf(2), leaf(3)],
+    };
+    assert_eq!(res, Ok((&input[9..], expected)));
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct TcpOption {
+    kind: u8,
+    #[nom(If = "kind != 0")]
+    value: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Included {
+    #[nom(RepeatUntil = "|o| o.kind == 0")]
+    options: Vec<TcpOption>,
+    trailer: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct Consumed {
+    #[nom(RepeatUntil = "|o| o.kind == 0", Terminator = "consume")]
+    options: Vec<TcpOption>,
+    trailer: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Kept {
+    #[nom(RepeatUntil = "|o| o.kind == 0", Terminator = "keep")]
+    options: Vec<TcpOption>,
+    end: TcpOption,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct TagTerminated {
+    #[nom(RepeatUntilTag(b"\xff\xff"))]
+    values: Vec<u16>,
+    trailer: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct TagKept {
+    #[nom(RepeatUntilTag(b"\xff"), Terminator = "keep")]
+    values: Vec<u8>,
+    end: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Complete)]
+struct CompleteTagTerminated {
+    #[nom(RepeatUntilTag(b"\x00"))]
+    values: Vec<u8>,
+}
+
+const OPTIONS: &[u8] = b"\x01\x0a\x02\x0b\x00\x42";
+
+fn opt(kind: u8, value: Option<u8>) -> TcpOption {
+    TcpOption { kind, value }
+}
+
+#[test]
+fn test_repeat_until_predicate() {
+    let res = Included::parse(OPTIONS);
+    let expected = Included {
+        options: vec![opt(1, Some(10)), opt(2, Some(11)), opt(0, None)],
+        trailer: 0x42,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let res = Consumed::parse(OPTIONS);
+    let expected = Consumed {
+        options: vec![opt(1, Some(10)), opt(2, Some(11))],
+        trailer: 0x42,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let res = Kept::parse(OPTIONS);
+    let expected = Kept {
+        options: vec![opt(1, Some(10)), opt(2, Some(11))],
+        end: opt(0, None),
+    };
+    assert_eq!(res, Ok((&b"\x42"[..], expected)));
+
+    // no elements before terminator
+    let (_, c) = Consumed::parse(b"\x00\x42").expect("parsing failed");
+    assert!(c.options.is_empty());
+}
+
+#[test]
+fn test_repeat_until_tag() {
+    let input = b"\x00\x01\x00\x02\xff\xff\x42";
+    let res = TagTerminated::parse(input);
+    let expected = TagTerminated {
+        values: vec![1, 2],
+        trailer: 0x42,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let res = TagKept::parse(b"\x01\x02\xff");
+    let expected = TagKept {
+        values: vec![1, 2],
+        end: 0xff,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+}
+
+#[test]
+fn test_repeat_until_incomplete() {
+    // streaming: missing terminator is Incomplete
+    let res = TagTerminated::parse(b"\x00\x01\x00\x02");
+    assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+    let res = Included::parse(b"\x01\x0a");
+    assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+    // complete: missing terminator is an error
+    let res = CompleteTagTerminated::parse(b"\x01\x02");
+    assert!(matches!(res, Err(nom::Err::Error(_))));
+    let res = CompleteTagTerminated::parse(b"\x01\x02\x00");
+    assert_eq!(
+        res,
+        Ok((&b""[..], CompleteTagTerminated { values: vec![1, 2] }))
+    );
+}
+
+#[test]
+fn test_repeat_until_serialize() {
+    fn roundtrip<'a, T: Parse<&'a [u8]> + Serialize>(input: &'a [u8]) -> Vec<u8> {
+        let (_, v) = T::parse(input).expect("parsing failed");
+        let mut out = Vec::new();
+        v.serialize(&mut out).expect("serialization failed");
+        out
+    }
+    assert_eq!(roundtrip::<Included>(OPTIONS), OPTIONS);
+    assert_eq!(roundtrip::<Kept>(OPTIONS), &OPTIONS[..5]);
+    let input = b"\x00\x01\x00\x02\xff\xff\x42";
+    assert_eq!(roundtrip::<TagTerminated>(input), input);
+    assert_eq!(roundtrip::<TagKept>(b"\x01\x02\xff"), b"\x01\x02\xff");
+}
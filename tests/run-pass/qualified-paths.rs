@@ -0,0 +1,46 @@
+use nom_derive::*;
+
+mod mytypes {
+    use nom_derive::*;
+
+    /// A user type with the same name as a standard type
+    #[derive(Debug, Default, PartialEq, Nom)]
+    pub struct Option<T>(pub T);
+}
+
+#[derive(Debug, PartialEq, Nom)]
+pub struct S2 {
+    h: ::std::primitive::u64,
+}
+
+// user types are not mistaken for the standard types with the same name
+#[derive(Debug, PartialEq, Nom)]
+pub struct S3 {
+    offset: u8,
+    #[nom(AtOffset = "offset")]
+    at: mytypes::Option<u8>,
+    #[nom(Ignore)]
+    ignored: mytypes::Option<u8>,
+    opt: self::mytypes::Option<u16>,
+}
+
+fn main() {
+    let input = b"\x00\x00\x00\x00\x00\x00\x00\x01";
+    let res = S2::parse(input);
+    assert_eq!(res, Ok((&input[8..], S2 { h: 1 })));
+
+    let input = b"\x01\x02\x00\x03";
+    let res = S3::parse(input);
+    assert_eq!(
+        res,
+        Ok((
+            &input[3..],
+            S3 {
+                offset: 1,
+                at: mytypes::Option(2),
+                ignored: mytypes::Option(0),
+                opt: mytypes::Option(0x0200),
+            }
+        ))
+    );
+}
@@ -0,0 +1,334 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+mod common;
+
+use common::check_roundtrip;
+use nom_derive::*;
+
+use nom::number::complete::be_u16;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct SimpleStruct {
+    a: u32,
+    b: u16,
+    c: [u8; 2],
+}
+
+#[derive(Debug, PartialEq, NomLE)]
+#[nom(Serialize)]
+struct LittleEndianStruct {
+    a: u32,
+    #[nom(BigEndian)]
+    b: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct TupleStruct(u8, u16);
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithTagAndCount<'a> {
+    #[nom(Tag(b"TAG"))]
+    tag: &'a [u8],
+    n: u8,
+    #[nom(Count = "n")]
+    v: Vec<u16>,
+    #[nom(Cond = "n > 1")]
+    opt: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithLengthCount<'a> {
+    #[nom(LengthCount = "be_u16")]
+    v: Vec<u8>,
+    sz: u8,
+    #[nom(Take = "sz")]
+    data: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithPadding {
+    #[nom(AlignAfter(4))]
+    a: u8,
+    #[nom(SkipBefore(2))]
+    b: u16,
+    #[nom(Value(a + 1))]
+    c: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[repr(u16)]
+#[nom(Serialize)]
+enum FieldlessEnum {
+    A = 1,
+    B = 0x102,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Selector = "u8", Serialize)]
+enum SelectorEnum {
+    #[nom(Selector = "0")]
+    Unit,
+    #[nom(Selector = "1")]
+    Value(u32),
+    #[nom(Selector = "_")]
+    Other { a: u8, b: u8 },
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithSelector {
+    t: u8,
+    #[nom(
+        Parse = "{ |i| SelectorEnum::parse(i, t) }",
+        SerializeWith = "Serialize::serialize_be"
+    )]
+    v: SelectorEnum,
+}
+
+fn ser_u16_raw<O: SerializeOutput + ?Sized>(v: &u16, out: &mut O) -> Result<(), SerializeError> {
+    out.write_bytes(&v.to_be_bytes())
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithSerializeWith {
+    #[nom(Parse = "be_u16", SerializeWith = "ser_u16_raw")]
+    a: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithPeek {
+    #[nom(Peek)]
+    version: u8,
+    header: u16,
+}
+
+#[test]
+fn test_serialize_simple() {
+    check_roundtrip::<SimpleStruct>(b"\x00\x00\x00\x01\x00\x02\x03\x04");
+    check_roundtrip::<LittleEndianStruct>(b"\x01\x00\x00\x00\x00\x02");
+    check_roundtrip::<TupleStruct>(b"\x01\x00\x02");
+    check_roundtrip::<StructWithPeek>(b"\x01\x23");
+}
+
+#[test]
+fn test_serialize_endianness() {
+    let s = SimpleStruct {
+        a: 1,
+        b: 2,
+        c: [3, 4],
+    };
+    let mut out = Vec::new();
+    s.serialize_le(&mut out).unwrap();
+    assert_eq!(out, b"\x01\x00\x00\x00\x02\x00\x03\x04");
+    // fixed endianness for object: same result for all functions
+    let s = LittleEndianStruct { a: 1, b: 2 };
+    let mut out = Vec::new();
+    s.serialize_be(&mut out).unwrap();
+    assert_eq!(out, b"\x01\x00\x00\x00\x00\x02");
+}
+
+#[test]
+fn test_serialize_tag_count() {
+    check_roundtrip::<StructWithTagAndCount>(b"TAG\x02\x00\x01\x00\x02\x03");
+    check_roundtrip::<StructWithTagAndCount>(b"TAG\x01\x00\x01");
+    check_roundtrip::<StructWithLengthCount>(b"\x00\x02\x01\x02\x03abc");
+}
+
+#[test]
+fn test_serialize_cond_count_mismatch() {
+    let tag = &b"TAG"[..];
+    // number of elements does not match Count
+    let s = StructWithTagAndCount {
+        tag,
+        n: 2,
+        v: vec![1],
+        opt: Some(3),
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+    // value present with a false condition
+    let s = StructWithTagAndCount {
+        tag,
+        n: 1,
+        v: vec![1],
+        opt: Some(3),
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+    // value missing with a true condition
+    let s = StructWithTagAndCount {
+        tag,
+        n: 2,
+        v: vec![1, 2],
+        opt: None,
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize, ExtraArgs(n: usize, present: bool))]
+struct StructWithExtraArgsCount {
+    #[nom(Count = "n")]
+    v: Vec<u8>,
+    #[nom(Cond = "present && v.len() > 1")]
+    opt: Option<u8>,
+}
+
+#[test]
+fn test_serialize_extra_args_count() {
+    let input = b"\x01\x02\x03";
+    let (rem, s) = StructWithExtraArgsCount::parse(input, 2, true).unwrap();
+    assert!(rem.is_empty());
+    // the count and condition are not known when serializing, and are not checked
+    let mut out = Vec::new();
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out, input);
+}
+
+#[test]
+fn test_serialize_padding() {
+    let input = b"\x01\x00\x00\x00\x00\x00\x00\x02";
+    let (_, s) = StructWithPadding::parse(input).unwrap();
+    assert_eq!(s, StructWithPadding { a: 1, b: 2, c: 2 });
+    let mut out = Vec::new();
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out, input);
+}
+
+#[test]
+fn test_serialize_enums() {
+    check_roundtrip::<FieldlessEnum>(b"\x01\x02");
+    check_roundtrip::<StructWithSelector>(b"\x00");
+    check_roundtrip::<StructWithSelector>(b"\x01\x00\x00\x00\x02");
+    check_roundtrip::<StructWithSelector>(b"\x07\x01\x02");
+}
+
+#[test]
+fn test_serialize_with() {
+    check_roundtrip::<StructWithSerializeWith>(b"\x12\x34");
+}
+
+#[test]
+fn test_serialize_slice_output() {
+    let s = SimpleStruct {
+        a: 1,
+        b: 2,
+        c: [3, 4],
+    };
+    let mut buf = [0u8; 8];
+    let mut out = SliceOutput::new(&mut buf);
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out.written(), b"\x00\x00\x00\x01\x00\x02\x03\x04");
+    let mut buf = [0u8; 4];
+    let mut out = SliceOutput::new(&mut buf);
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::BufferTooSmall));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithComputedFields<'a> {
+    #[nom(CountOf(v))]
+    n: u8,
+    #[nom(LengthOf(data), LittleEndian)]
+    len: u16,
+    #[nom(Count = "n")]
+    v: Vec<u16>,
+    #[nom(Take = "len")]
+    data: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithLengthOfSubStruct {
+    #[nom(LengthOf(s))]
+    len: u32,
+    #[nom(Cond = "len > 0")]
+    s: Option<SimpleStruct>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct StructWithTrailingLength {
+    #[nom(LengthCount = "nom::number::complete::u8")]
+    v: Vec<u8>,
+    #[nom(LengthOf(v))]
+    len: u8,
+}
+
+#[test]
+fn test_serialize_computed_fields() {
+    let s = StructWithComputedFields {
+        n: 0,
+        len: 0,
+        v: vec![1, 2],
+        data: b"abc",
+    };
+    let mut out = Vec::new();
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x02\x03\x00\x00\x01\x00\x02abc");
+    let (_, s) = StructWithComputedFields::parse(&out).unwrap();
+    assert_eq!((s.n, s.len), (2, 3));
+
+    let s = StructWithLengthOfSubStruct {
+        len: 0,
+        s: Some(SimpleStruct {
+            a: 1,
+            b: 2,
+            c: [3, 4],
+        }),
+    };
+    let mut out = Vec::new();
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x00\x00\x00\x08\x00\x00\x00\x01\x00\x02\x03\x04");
+    check_roundtrip::<StructWithLengthOfSubStruct>(&out);
+
+    let s = StructWithTrailingLength {
+        v: vec![1, 2],
+        len: 0,
+    };
+    let mut out = Vec::new();
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x02\x01\x02\x03");
+    let mut buf = [0u8; 4];
+    let mut out = SliceOutput::new(&mut buf);
+    s.serialize(&mut out).unwrap();
+    assert_eq!(out.written(), b"\x02\x01\x02\x03");
+    let s = StructWithTrailingLength {
+        v: vec![0; 255],
+        len: 0,
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+}
+
+#[test]
+fn test_parse_computed_fields_check() {
+    // v has 2 elements, data has 3 bytes
+    let res = StructWithComputedFields::parse(b"\x02\x03\x00\x00\x01\x00\x02abc");
+    assert!(res.is_ok());
+    // sub-struct is shorter than expected length
+    let res =
+        StructWithLengthOfSubStruct::parse(b"\x00\x00\x00\x09\x00\x00\x00\x01\x00\x02\x03\x04\x00");
+    if let Err(nom::Err::Error(e)) = res {
+        assert_eq!(e.code, nom::error::ErrorKind::Verify);
+    } else {
+        panic!("wrong result: {:?}", res);
+    }
+    // length includes the LengthCount prefix
+    let res = StructWithTrailingLength::parse(b"\x02\x01\x02\x03");
+    assert!(res.is_ok());
+    let res = StructWithTrailingLength::parse(b"\x02\x01\x02\x02");
+    assert!(res.is_err());
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind};
+use nom::Err;
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Header {
+    kind: u8,
+    len: u16,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Body {
+    version: u8,
+    items: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Record {
+    hdr: Header,
+    #[nom(Size = "hdr.len - 3")]
+    body: Body,
+    tail: u8,
+}
+
+#[test]
+fn test_size_vec_stops_at_window_end() {
+    let input = b"\x01\x00\x08\x02\x00\x01\x00\x02\xaa\xbb";
+    let res = Record::parse(input);
+    let expected = Record {
+        hdr: Header { kind: 1, len: 8 },
+        body: Body {
+            version: 2,
+            items: vec![1, 2],
+        },
+        tail: 0xaa,
+    };
+    assert_eq!(res, Ok((&input[9..], expected)));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Skip {
+    len: u8,
+    #[nom(Size = "len")]
+    value: u16,
+    tail: u8,
+}
+
+#[test]
+fn test_size_skip_leftover() {
+    let input = b"\x04\x12\x34\xff\xff\xaa";
+    let res = Skip::parse(input);
+    let expected = Skip {
+        len: 4,
+        value: 0x1234,
+        tail: 0xaa,
+    };
+    assert_eq!(res, Ok((&input[6..], expected)));
+    // the window is too small for the value
+    let input = b"\x01\x12\x34";
+    let res = Skip::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[2..], ErrorKind::Complete)))
+    );
+    // the input is too small for the window
+    let res = Skip::parse(&b"\x04\x12\x34"[..]);
+    assert!(matches!(res, Err(Err::Incomplete(_))));
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Strict {
+    len: u8,
+    #[nom(Size = "len", StrictSize)]
+    value: u16,
+}
+
+#[test]
+fn test_size_strict() {
+    let input = b"\x02\x12\x34";
+    let res = Strict::parse(input);
+    let expected = Strict {
+        len: 2,
+        value: 0x1234,
+    };
+    assert_eq!(res, Ok((&input[3..], expected)));
+    let input = b"\x03\x12\x34\x56";
+    let res = Strict::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[3..], ErrorKind::Eof)))
+    );
+}
+
+#[test]
+fn test_size_serialize() {
+    let value = Skip {
+        len: 4,
+        value: 0x1234,
+        tail: 0xaa,
+    };
+    let mut out = Vec::new();
+    value.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x04\x12\x34\x00\x00\xaa");
+
+    let value = Skip {
+        len: 1,
+        value: 0x1234,
+        tail: 0xaa,
+    };
+    let mut out = Vec::new();
+    assert_eq!(
+        value.serialize(&mut out),
+        Err(SerializeError::ValueOutOfRange)
+    );
+
+    let value = Strict {
+        len: 3,
+        value: 0x1234,
+    };
+    let mut out = Vec::new();
+    assert_eq!(
+        value.serialize(&mut out),
+        Err(SerializeError::ValueOutOfRange)
+    );
+
+    let value = Record {
+        hdr: Header { kind: 1, len: 8 },
+        body: Body {
+            version: 2,
+            items: vec![1, 2],
+        },
+        tail: 0xaa,
+    };
+    let mut out = Vec::new();
+    value.serialize(&mut out).unwrap();
+    assert_eq!(out, b"\x01\x00\x08\x02\x00\x01\x00\x02\xaa");
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct SizedFromSibling {
+    hdr_len: u8,
+    kind: u8,
+    #[nom(Size = "hdr_len as usize - 2")]
+    value: u16,
+}
+
+#[test]
+fn test_size_serialize_sibling_field() {
+    let input = b"\x05\x01\x12\x34\x00";
+    let (rem, value) = SizedFromSibling::parse(input).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(
+        value,
+        SizedFromSibling {
+            hdr_len: 5,
+            kind: 1,
+            value: 0x1234,
+        }
+    );
+    let mut out = Vec::new();
+    value.serialize(&mut out).unwrap();
+    assert_eq!(out, input);
+}
@@ -0,0 +1,89 @@
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+use core::num::{NonZeroI8, NonZeroU16};
+use nom::error::{Error, ErrorKind};
+use nom::{Err, IResult};
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct Values {
+    flag: bool,
+    #[nom(Parse = "nom_derive::lenient_bool")]
+    lenient: bool,
+    c: char,
+    id: NonZeroU16,
+    delta: Option<NonZeroI8>,
+}
+
+#[test]
+fn test_values() {
+    let input = b"\x01\x05\x00\x01\xf6\x00\x00\x12\xff";
+    let res: IResult<_, _, Error<_>> = Values::parse(input);
+    let expected = Values {
+        flag: true,
+        lenient: true,
+        c: '\u{1f600}',
+        id: NonZeroU16::new(0x12).unwrap(),
+        delta: NonZeroI8::new(-1),
+    };
+    assert_eq!(res, Ok((&input[9..], expected)));
+}
+
+#[test]
+fn test_values_errors() {
+    // strict bool
+    let input = b"\x02\x05\x00\x01\xf6\x00\x00\x12\xff";
+    let res: IResult<_, _, Error<_>> = Values::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[..], ErrorKind::Verify)))
+    );
+    // invalid code point
+    let input = b"\x00\x00\x00\x00\xd8\x00\x00\x12\xff";
+    let res: IResult<_, _, Error<_>> = Values::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[2..], ErrorKind::Verify)))
+    );
+    // zero
+    let input = b"\x00\x00\x00\x00\x00\x41\x00\x00\xff";
+    let res: IResult<_, _, Error<_>> = Values::parse(input);
+    assert_eq!(
+        res,
+        Err(Err::Error(Error::new(&input[6..], ErrorKind::Verify)))
+    );
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, Serialize)]
+struct Addresses {
+    v4: Ipv4Addr,
+    v6: Ipv6Addr,
+    sock: SocketAddrV4,
+    c: char,
+}
+
+#[test]
+fn test_addresses() {
+    let input = b"\xc0\xa8\x00\x01\
+        \x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\
+        \x0a\x00\x00\x01\x50\x00\
+        \x41\x00\x00\x00";
+    let res = Addresses::parse(input);
+    let expected = Addresses {
+        v4: Ipv4Addr::new(192, 168, 0, 1),
+        v6: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+        sock: SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 80),
+        c: 'A',
+    };
+    assert_eq!(res, Ok((&input[30..], expected)));
+
+    let mut buf = [0u8; 30];
+    let mut out = SliceOutput::new(&mut buf);
+    res.unwrap().1.serialize(&mut out).unwrap();
+    assert_eq!(out.written(), &input[..]);
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+mod common;
+
+use common::check_roundtrip;
+use nom::error::{Error, ErrorKind};
+use nom::IResult;
+use nom_derive::*;
+use std::ffi::CStr;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct CStrings<'a> {
+    #[nom(NulTerminated)]
+    name: &'a str,
+    #[nom(NulTerminated)]
+    owned: String,
+    #[nom(NulTerminated)]
+    raw: &'a CStr,
+    version: u8,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct PaddedStrings<'a> {
+    #[nom(Padded = 8)]
+    name: &'a str,
+    #[nom(Padded = 6, PadByte = 0x20)]
+    label: String,
+    #[nom(Padded = 4, Trim = "keep")]
+    raw: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct CPadded<'a> {
+    #[nom(Padded = 8, Trim = "nul")]
+    name: &'a str,
+    #[nom(Padded = 4, Trim = "nul")]
+    cname: &'a CStr,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct CPaddedKeep<'a> {
+    #[nom(Padded = 4)]
+    name: &'a CStr,
+    #[nom(Padded = 4, Trim = "keep")]
+    raw: &'a CStr,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian, Serialize)]
+struct Prefixed<'a> {
+    #[nom(LengthPrefix = "u8")]
+    pascal: String,
+    #[nom(LengthPrefix = "u16")]
+    name: &'a str,
+    // default prefix is a big-endian u32
+    s: String,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct Generic<'a> {
+    #[nom(NulTerminated)]
+    name: &'a str,
+    #[nom(Count = "2", NulTerminated)]
+    names: Vec<String>,
+}
+
+#[test]
+fn test_nul_terminated() {
+    let input = b"abc\x00de\x00f\x00\x02";
+    let (rem, s) = CStrings::parse(input).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(s.name, "abc");
+    assert_eq!(s.owned, "de");
+    assert_eq!(s.raw.to_bytes(), b"f");
+    assert_eq!(s.version, 2);
+    check_roundtrip::<CStrings>(input);
+
+    let res = CStrings::parse(b"abc");
+    assert!(matches!(res, Err(nom::Err::Incomplete(_))));
+    let res = CStrings::parse(b"\xff\x00");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(Error::new(
+            &b"\xff\x00"[..],
+            ErrorKind::MapRes
+        )))
+    );
+}
+
+#[test]
+fn test_padded() {
+    let input = b"name\x00\x00\x00\x00ab    xy\x00\x00";
+    let res = PaddedStrings::parse(input);
+    let expected = PaddedStrings {
+        name: "name",
+        label: "ab".to_string(),
+        raw: b"xy\x00\x00",
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    check_roundtrip::<PaddedStrings>(input);
+
+    let input = b"ab\x00garbaxyz\x00";
+    let (_, s) = CPadded::parse(input).expect("parsing failed");
+    assert_eq!(s.name, "ab");
+    assert_eq!(s.cname.to_bytes(), b"xyz");
+    // no NUL terminator for the CStr
+    assert!(CPadded::parse(b"ab\x00garbawxyz").is_err());
+
+    // the NUL terminator is searched in the field, whatever the trim policy
+    let input = b"ab\x00\x00c\x00\x00\x00";
+    let (_, s) = CPaddedKeep::parse(input).expect("parsing failed");
+    assert_eq!(s.name.to_bytes(), b"ab");
+    assert_eq!(s.raw.to_bytes(), b"c");
+    check_roundtrip::<CPaddedKeep>(input);
+    assert!(CPaddedKeep::parse(b"ab\x00\x00abcd").is_err());
+}
+
+#[test]
+fn test_length_prefix() {
+    let input = b"\x02ab\x03\x00cde\x00\x00\x00\x01f";
+    let res = Prefixed::parse(input);
+    let expected = Prefixed {
+        pascal: "ab".to_string(),
+        name: "cde",
+        s: "f".to_string(),
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+    check_roundtrip::<Prefixed>(input);
+
+    let s = Prefixed {
+        pascal: "x".repeat(256),
+        name: "",
+        s: String::new(),
+    };
+    let mut out = Vec::new();
+    assert_eq!(s.serialize(&mut out), Err(SerializeError::ValueOutOfRange));
+}
+
+#[test]
+fn test_strings_generic_errors() {
+    let res: IResult<_, _, Error<&[u8]>> = Generic::parse(b"a\x00b\x00c\x00");
+    let expected = Generic {
+        name: "a",
+        names: vec!["b".to_string(), "c".to_string()],
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+}
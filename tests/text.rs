@@ -0,0 +1,104 @@
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::character::streaming::{alpha1, alphanumeric1, not_line_ending};
+use nom::error::{Error, ErrorKind};
+use nom::IResult;
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(StrInput)]
+struct Header<'a> {
+    #[nom(Parse = "alpha1")]
+    key: &'a str,
+    #[nom(Tag("="))]
+    eq: &'a str,
+    value: u32,
+    #[nom(Tag(","))]
+    sep: &'a str,
+    #[nom(Hex)]
+    flags: u16,
+    #[nom(Tag("\r\n"))]
+    eol: &'a str,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(StrInput, Selector = "&str")]
+enum Command<'a> {
+    #[nom(Selector("HELO"))]
+    Helo {
+        #[nom(Tag(" "))]
+        sp: &'a str,
+        #[nom(Parse = "alphanumeric1")]
+        domain: &'a str,
+    },
+    #[nom(Selector("SIZE"))]
+    Size(#[nom(Tag(" "))] &'a str, i64),
+    #[nom(Selector("QUIT"))]
+    Quit,
+    #[nom(Selector(_))]
+    Unknown(#[nom(Parse = "not_line_ending")] &'a str),
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(StrInput)]
+struct CommandLine<'a> {
+    #[nom(Parse = "alpha1")]
+    verb: &'a str,
+    #[nom(Parse = "{ |i| Command::parse(i, verb) }")]
+    command: Command<'a>,
+    #[nom(Tag("\r\n"))]
+    eol: &'a str,
+}
+
+#[test]
+fn test_text_primitives() {
+    let res: IResult<&str, u32> = u32::parse("1234;");
+    assert_eq!(res, Ok((";", 1234)));
+    let res: IResult<&str, i16> = i16::parse_le("-42 ");
+    assert_eq!(res, Ok((" ", -42)));
+    let res: IResult<&str, Option<u8>> = Option::<u8>::parse("x");
+    assert_eq!(res, Ok(("x", None)));
+    let res: IResult<&str, u8> = u8::parse("300 ");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(Error::new("300 ", ErrorKind::TooLarge)))
+    );
+}
+
+#[test]
+fn test_text_struct() {
+    let res = Header::parse("Size=1500,0a0F\r\n");
+    let expected = Header {
+        key: "Size",
+        eq: "=",
+        value: 1500,
+        sep: ",",
+        flags: 0xa0f,
+        eol: "\r\n",
+    };
+    assert_eq!(res, Ok(("", expected)));
+    let res = Header::parse("Size:1500,0\r\n");
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_text_selector() {
+    let res = CommandLine::parse("HELO relay1\r\n");
+    let (rem, line) = res.expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(
+        line.command,
+        Command::Helo {
+            sp: " ",
+            domain: "relay1"
+        }
+    );
+    let (_, line) = CommandLine::parse("SIZE -3\r\n").expect("parsing failed");
+    assert_eq!(line.command, Command::Size(" ", -3));
+    let (_, line) = CommandLine::parse("QUIT\r\n").expect("parsing failed");
+    assert_eq!(line.command, Command::Quit);
+    let (_, line) = CommandLine::parse("NOOP now\r\n").expect("parsing failed");
+    assert_eq!(line.command, Command::Unknown(" now"));
+}
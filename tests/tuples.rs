@@ -0,0 +1,60 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Tuples {
+    a: (u16, u16, u32),
+    #[nom(Count = "2")]
+    pairs: Vec<(u8, u16)>,
+    #[nom(LengthCount = "nom::number::complete::be_u8")]
+    triples: Vec<(u8, u8, u8)>,
+}
+
+#[test]
+fn test_tuples() {
+    let input = b"\x00\x01\x00\x02\x00\x00\x00\x03\x04\x00\x05\x06\x00\x07\x01\x08\x09\x0a";
+    let res = Tuples::parse(input);
+    let expected = Tuples {
+        a: (1, 2, 3),
+        pairs: vec![(4, 5), (6, 7)],
+        triples: vec![(8, 9, 10)],
+    };
+    assert_eq!(res, Ok((&input[18..], expected)));
+
+    let mut out = Vec::new();
+    res.unwrap().1.serialize(&mut out).unwrap();
+    assert_eq!(out, input);
+}
+
+type Tuple12 = (u16, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16);
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(LittleEndian)]
+struct TuplesLE {
+    a: (u16, (u8, u32)),
+    #[nom(BigEndian)]
+    b: (u16, u16),
+    #[nom(Count = "1")]
+    c: Vec<Tuple12>,
+    #[nom(Ignore)]
+    d: (u8, Option<u8>),
+}
+
+#[test]
+fn test_tuples_endianness() {
+    let input = b"\x01\x00\x02\x03\x00\x00\x00\x00\x04\x00\x05\x06\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x00\x0b";
+    let res = TuplesLE::parse(input);
+    let expected = TuplesLE {
+        a: (1, (2, 3)),
+        b: (4, 5),
+        c: vec![(6, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0x0b00)],
+        d: (0, None),
+    };
+    assert_eq!(res, Ok((&input[25..], expected)));
+}
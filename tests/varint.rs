@@ -0,0 +1,93 @@
+#![cfg(feature = "alloc")]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate pretty_assertions;
+
+use nom::error::{Error, ErrorKind};
+use nom_derive::*;
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(Serialize)]
+struct Record<'a> {
+    #[nom(Varint = "leb128")]
+    id: u64,
+    #[nom(Varint = "zigzag")]
+    delta: i32,
+    #[nom(Varint = "quic")]
+    count: u16,
+    #[nom(Count = "count")]
+    values: Vec<u8>,
+    #[nom(Varint = "ber")]
+    len: usize,
+    #[nom(Take = "len")]
+    data: &'a [u8],
+}
+
+#[derive(Debug, PartialEq, Nom)]
+struct WithLengthSources {
+    #[nom(LengthCount = "<Leb128<u32>>::parse")]
+    items: Vec<u16>,
+    #[nom(LengthCount = "<QuicVarInt<u32>>::parse")]
+    blob: Vec<u8>,
+    #[nom(Varint = "sleb128")]
+    offset: i64,
+}
+
+#[derive(Debug, PartialEq, Nom)]
+#[nom(GenericErrors)]
+struct Small {
+    #[nom(Varint = "leb128")]
+    v: u8,
+}
+
+#[test]
+fn test_varint_fields() {
+    let input = b"\xe5\x8e\x26\x03\x40\x02\xaa\xbb\x81\x03abc";
+    let res = Record::parse(input);
+    let expected = Record {
+        id: 624_485,
+        delta: -2,
+        count: 2,
+        values: vec![0xaa, 0xbb],
+        len: 3,
+        data: b"abc",
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let (_, r) = Record::parse(input).unwrap();
+    let mut out = Vec::new();
+    r.serialize(&mut out).unwrap();
+    // serialization uses the shortest encodings
+    assert_eq!(out, b"\xe5\x8e\x26\x03\x02\xaa\xbb\x03abc");
+}
+
+#[test]
+fn test_varint_length_sources() {
+    let input = b"\x02\x00\x01\x00\x02\x02xy\x7f";
+    let res = WithLengthSources::parse(input);
+    let expected = WithLengthSources {
+        items: vec![1, 2],
+        blob: b"xy".to_vec(),
+        offset: -1,
+    };
+    assert_eq!(res, Ok((&b""[..], expected)));
+
+    let res: nom::IResult<_, _, Error<&[u8]>> =
+        <LengthData<BerLength<u16>, &[u8]>>::parse(&b"\x81\x02xyz"[..]);
+    assert_eq!(res, Ok((&b"z"[..], LengthData::new(&b"xy"[..]))));
+}
+
+#[test]
+fn test_varint_overflow() {
+    let res: nom::IResult<_, _, Error<&[u8]>> = Small::parse(b"\x80\x02");
+    assert_eq!(
+        res,
+        Err(nom::Err::Error(Error::new(
+            &b"\x80\x02"[..],
+            ErrorKind::TooLarge
+        )))
+    );
+    let res: nom::IResult<_, _, Error<&[u8]>> = Small::parse(b"\xff\x01");
+    assert_eq!(res, Ok((&b""[..], Small { v: 255 })));
+}